@@ -0,0 +1,171 @@
+#![no_std]
+use soroban_sdk::{contractclient, contracterror, contracttype, Address, Env, String, Vec};
+
+// The public interface of the governance budget allocator: the types its core entrypoints take
+// and return, and those entrypoints as traits with generated clients. Contracts that call an
+// allocator depend on this crate instead of the allocator itself (or a hand-written client).
+// The allocator implements both traits, so its own client stays in sync with these.
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct BudgetState {
+    pub current: i128,
+    pub min: i128,
+    pub max: i128,
+}
+// BudgetState stores the budget data:
+// current is the current budget value
+// min is the lower limit
+// max is the upper limit
+// This struct represents the main financial state stored on-chain.
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Receipt {
+    pub id: u64,
+    pub previous: i128,
+    pub new: i128,
+    pub ledger: u32,
+    pub timestamp: u64,
+    pub caller: Address,
+    pub on_behalf_of: Option<Address>,
+}
+// Receipt is returned by every budget adjustment:
+// id is the adjustment id (as used by veto and history queries)
+// previous and new are the current value before and after the adjustment
+// ledger is the ledger the adjustment was applied in, timestamp that ledger's close time in seconds
+// caller is the operator that made it
+// on_behalf_of is the operator a deputy acted for (see deputy.rs), None otherwise
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct LimitCheck {
+    pub error: u32,
+    pub attempted: i128,
+    pub limit: i128,
+}
+// LimitCheck is the outcome of check_adjustment:
+// error is 0 when the adjustment would succeed, otherwise the BudgetError code it would fail with
+// attempted is the resulting current value (the amount itself when the amount is rejected before that)
+// limit is the max or floor the result is checked against (0 for failures that are not about a limit)
+
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum BudgetError {
+    NotOwner = 1,
+    NotOperator = 2,
+    AlreadyOperator = 3,
+    NotOperatorFound = 4,
+    Overflow = 5,
+    Underflow = 6,
+    ExceedsMax = 7,
+    BelowMin = 8,
+    InvalidLimits = 9,
+    AdjustmentNotFound = 10,
+    VetoWindowClosed = 11,
+    Paused = 12,
+    InvalidConfig = 13,
+    ConfirmationRequired = 14,
+    NoPendingAction = 15,
+    ActionExpired = 16,
+    Decommissioned = 17,
+    TimelockActive = 18,
+    AlreadyRegistered = 19,
+    NotRegistered = 20,
+    SignatureExpired = 21,
+    BadNonce = 22,
+    InvalidAmount = 23,
+    TokenNotSet = 24,
+    ProposalNotFound = 25,
+    ProposalClosed = 26,
+    ProposalExpired = 27,
+    Blacklisted = 28,
+    AlreadyVoted = 29,
+    QuorumNotReached = 30,
+    TokenVoteRequired = 31,
+    CategoryFrozen = 32,
+    InvalidGranularity = 33,
+    TooManyOperators = 34,
+    WrongPhase = 35,
+    InvalidReveal = 36,
+    ProposalChallenged = 37,
+    EmergencyCapReached = 38,
+    TokenNotAllowed = 39,
+    StalePrice = 40,
+    TierLimitExceeded = 41,
+    OperatorsLocked = 42,
+    BadgeRequired = 43,
+    InsufficientStake = 44,
+    RateLimited = 45,
+    NoOp = 46,
+    PolicyViolation = 47,
+    PayeeNotAllowed = 48,
+    AlreadyInitialized = 49,
+}
+
+// BudgetError defines all failure cases:
+// Access control errors
+// Arithmetic errors
+// Budget limit violations
+// Invalid initialization
+// Each error has a numeric value so failures are deterministic and testable.
+
+
+// The version of the traits below, raised whenever an entrypoint in them changes incompatibly.
+pub const INTERFACE_VERSION: u32 = 2;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ContractVersion {
+    pub version: String,
+    pub interface: u32,
+}
+// ContractVersion identifies a deployed allocator:
+// version is the semantic version of the allocator crate it was built from
+// interface is the INTERFACE_VERSION it implements
+
+
+#[contractclient(name = "BudgetAllocatorClient")]
+pub trait BudgetAllocator {
+    /// Increase the budget (operators only)
+    fn increase_budget(env: Env, caller: Address, amount: i128) -> Result<Receipt, BudgetError>;
+
+    /// Decrease the budget (operators only)
+    fn decrease_budget(env: Env, caller: Address, amount: i128) -> Result<Receipt, BudgetError>;
+
+    /// Get current budget state
+    fn get_budget(env: Env) -> BudgetState;
+
+    /// Get contract owner address
+    fn get_owner(env: Env) -> Address;
+
+    /// Get list of authorized operators
+    fn get_operators(env: Env) -> Vec<Address>;
+
+    /// Check if an address is an operator
+    fn is_operator(env: Env, address: Address) -> bool;
+
+    /// Check an adjustment without applying it, with the values behind a failure
+    fn check_adjustment(env: Env, caller: Address, amount: i128, increase: bool) -> LimitCheck;
+
+    /// Get the version of the deployed code and the interface version it implements
+    fn get_version(env: Env) -> ContractVersion;
+}
+// BudgetAllocator is the budget itself: adjusting it within its limits and reading it
+
+
+#[contractclient(name = "BudgetTreasuryClient")]
+pub trait BudgetTreasury {
+    /// Decrease the budget and transfer the same amount of the backing token (operators only)
+    fn spend(env: Env, caller: Address, recipient: Address, amount: i128, memo: String) -> Result<u64, BudgetError>;
+
+    /// Get the backing token, if token-backed mode is on
+    fn get_token(env: Env) -> Option<Address>;
+}
+// BudgetTreasury is the token-backed side, paying out of the budget
@@ -0,0 +1,17 @@
+#!/bin/sh
+# Builds the release wasm with the given features (none: the core contract alone) and fails if it is
+# larger than the network's contract size limit.
+# Usage: ./size.sh [comma separated features]
+set -eu
+
+LIMIT=65536
+FEATURES="${1:-}"
+WASM=target/wasm32-unknown-unknown/release/governance_budget.wasm
+
+cargo build --quiet --release --target wasm32-unknown-unknown --no-default-features --features "$FEATURES"
+SIZE=$(wc -c < "$WASM")
+echo "$SIZE bytes (limit $LIMIT) with features: ${FEATURES:-none}"
+if [ "$SIZE" -gt "$LIMIT" ]; then
+    echo "the wasm is over the contract size limit, leave out some features" >&2
+    exit 1
+fi
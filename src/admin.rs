@@ -0,0 +1,389 @@
+// Two-step destructive admin actions.
+// Dangerous owner operations are never applied by a single call. The owner first records the
+// intent with propose_action(), then repeats the exact same action with confirm_action() within
+// the confirmation window. A different action, an expired intent or a cancelled intent all fail.
+// Decommission can additionally be held back by a timelock: it only becomes confirmable once the
+// decommission delay has passed, and the confirmation window starts from there.
+
+use soroban_sdk::{contractimpl, contracttype, token, Address, Env, Vec};
+
+use crate::{
+    read_budget, read_operators, require_owner, set_listed, write_budget, write_owner, BudgetError, DataKey,
+    GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient,
+};
+#[cfg(feature = "federation")]
+use crate::federation;
+#[cfg(feature = "token_vote")]
+use crate::token_vote;
+#[cfg(feature = "treasury")]
+use crate::treasury;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub enum AdminAction {
+    TransferOwnership(Address),
+    SetLimits(i128, i128),
+    Decommission(Address, Vec<Address>),
+}
+// AdminAction lists the operations that need confirmation:
+// TransferOwnership hands the owner role to a new address
+// SetLimits(min, max) replaces the limits even when current falls outside them (current is clamped)
+// Decommission(recipient, tokens) sweeps the listed token balances, less what is held for third parties (bonds,
+// escrow, deposits), to recipient and retires the contract
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PendingAction {
+    pub action: AdminAction,
+    pub ledger: u32,
+}
+// PendingAction is the recorded intent and the ledger it was recorded in.
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum AdminKey {
+    PendingAction,
+    ConfirmWindow,
+    DecommissionDelay,
+}
+// PendingAction stores the single outstanding PendingAction
+// ConfirmWindow stores the confirmation window in ledgers
+// DecommissionDelay stores the timelock in ledgers before a decommission can be confirmed
+
+
+// Roughly one hour of ledgers.
+const DEFAULT_CONFIRM_WINDOW: u32 = 720;
+
+fn confirm_window(env: &Env) -> u32 {
+    env.storage().persistent().get(&AdminKey::ConfirmWindow).unwrap_or(DEFAULT_CONFIRM_WINDOW)
+}
+
+fn decommission_delay(env: &Env) -> u32 {
+    env.storage().persistent().get(&AdminKey::DecommissionDelay).unwrap_or(0)
+}
+
+// Ledgers that must pass after the intent before the action can be confirmed.
+fn delay_for(env: &Env, action: &AdminAction) -> u32 {
+    match action {
+        AdminAction::Decommission(_, _) => decommission_delay(env),
+        _ => 0,
+    }
+}
+
+pub(crate) fn check_limits(env: &Env, min: i128, max: i128) -> Result<(), BudgetError> {
+    if min > max {
+        return Err(BudgetError::InvalidLimits);
+    }
+    // A child allocator can never raise max past what its parent allocated
+    #[cfg(feature = "federation")]
+    federation::check_max(env, max)?;
+    Ok(())
+}
+
+fn execute(env: &Env, action: AdminAction) -> Result<(), BudgetError> {
+    match action {
+        AdminAction::TransferOwnership(new_owner) => {
+            write_owner(env, &new_owner);
+        }
+        AdminAction::SetLimits(min, max) => {
+            check_limits(env, min, max)?;
+            #[cfg(feature = "token_vote")]
+            token_vote::gate(env, max)?;
+            let mut budget = read_budget(env);
+            budget.min = min;
+            budget.max = max;
+            budget.current = budget.current.clamp(min, max);
+            write_budget(env, &budget);
+        }
+        AdminAction::Decommission(recipient, tokens) => {
+            // Sweep what the contract holds of each listed token, leaving what is held for third
+            // parties so bonds, escrowed payouts and deposits can still be paid back
+            let this = env.current_contract_address();
+            for token in tokens.iter() {
+                let client = token::Client::new(env, &token);
+                #[cfg(feature = "treasury")]
+                let sweepable = client.balance(&this) - treasury::held(env, &token);
+                #[cfg(not(feature = "treasury"))]
+                let sweepable = client.balance(&this);
+                if sweepable > 0 {
+                    client.transfer(&this, &recipient, &sweepable);
+                }
+            }
+
+            // Clear the roles and feature configuration, owner and budget stay readable
+            for operator in read_operators(env).iter() {
+                set_listed(env, &operator, false);
+            }
+            let storage = env.storage().persistent();
+            storage.set(&DataKey::Operators, &Vec::<Address>::new(env));
+            storage.remove(&DataKey::Admin);
+            #[cfg(feature = "veto")]
+            storage.remove(&crate::veto::VetoKey::VetoWindow);
+            #[cfg(feature = "breaker")]
+            storage.remove(&crate::breaker::BreakerKey::Breaker);
+            #[cfg(feature = "breaker")]
+            storage.remove(&crate::breaker::BreakerKey::DrainLog);
+            #[cfg(feature = "hooks")]
+            storage.remove(&crate::hooks::HookKey::Hooks);
+            #[cfg(feature = "alerts")]
+            storage.remove(&crate::alerts::AlertKey::AlertSubscribers);
+            #[cfg(feature = "consumer")]
+            storage.remove(&crate::consumer::ConsumerKey::Consumers);
+            storage.remove(&DataKey::EpochLength);
+            storage.set(&DataKey::Decommissioned, &true);
+            env.storage().instance().remove(&DataKey::Paused);
+        }
+    }
+    Ok(())
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Change the limits in one call when current stays within them (owner only)
+    pub fn set_limits(env: Env, caller: Address, min: i128, max: i128) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        check_limits(&env, min, max)?;
+        #[cfg(feature = "token_vote")]
+        token_vote::gate(&env, max)?;
+
+        // Shrinking the limits past current is destructive and has to be confirmed
+        let mut budget = read_budget(&env);
+        if budget.current < min || budget.current > max {
+            return Err(BudgetError::ConfirmationRequired);
+        }
+
+        budget.min = min;
+        budget.max = max;
+        write_budget(&env, &budget);
+        Ok(())
+    }
+
+    /// Record the intent to perform a destructive action (owner only), replacing any earlier intent
+    pub fn propose_action(env: Env, caller: Address, action: AdminAction) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        let pending = PendingAction {
+            action,
+            ledger: env.ledger().sequence(),
+        };
+        env.storage().persistent().set(&AdminKey::PendingAction, &pending);
+        Ok(())
+    }
+
+    /// Execute the recorded intent; the action must match it exactly (owner only)
+    pub fn confirm_action(env: Env, caller: Address, action: AdminAction) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+
+        let pending: PendingAction = env
+            .storage()
+            .persistent()
+            .get(&AdminKey::PendingAction)
+            .ok_or(BudgetError::NoPendingAction)?;
+        if pending.action != action {
+            return Err(BudgetError::NoPendingAction);
+        }
+        let ready = pending.ledger.saturating_add(delay_for(&env, &action));
+        if env.ledger().sequence() < ready {
+            return Err(BudgetError::TimelockActive);
+        }
+        if env.ledger().sequence() > ready.saturating_add(confirm_window(&env)) {
+            return Err(BudgetError::ActionExpired);
+        }
+
+        env.storage().persistent().remove(&AdminKey::PendingAction);
+        execute(&env, action)
+    }
+
+    /// Retire the contract: confirms a recorded Decommission(recipient, tokens) intent (owner only)
+    pub fn decommission(env: Env, caller: Address, recipient: Address, tokens: Vec<Address>) -> Result<(), BudgetError> {
+        Self::confirm_action(env, caller, AdminAction::Decommission(recipient, tokens))
+    }
+
+    /// Drop the recorded intent (owner only)
+    pub fn cancel_action(env: Env, caller: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().remove(&AdminKey::PendingAction);
+        Ok(())
+    }
+
+    /// Set how many ledgers an intent stays confirmable (owner only)
+    pub fn set_confirm_window(env: Env, caller: Address, ledgers: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        if ledgers == 0 {
+            return Err(BudgetError::InvalidConfig);
+        }
+        env.storage().persistent().set(&AdminKey::ConfirmWindow, &ledgers);
+        Ok(())
+    }
+
+    /// Set the timelock in ledgers between a decommission intent and its confirmation (owner only)
+    pub fn set_decommission_delay(env: Env, caller: Address, ledgers: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().set(&AdminKey::DecommissionDelay, &ledgers);
+        Ok(())
+    }
+
+    // Get the recorded intent, if any
+    pub fn get_pending_action(env: Env) -> Option<PendingAction> {
+        env.storage().persistent().get(&AdminKey::PendingAction)
+    }
+
+    // Get the confirmation window in ledgers
+    pub fn get_confirm_window(env: Env) -> u32 {
+        confirm_window(&env)
+    }
+
+    // Get the decommission timelock in ledgers
+    pub fn get_decommission_delay(env: Env) -> u32 {
+        decommission_delay(&env)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::{token, vec};
+
+    #[test]
+    fn test_two_step_ownership_transfer() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let new_owner = Address::generate(&env);
+        let other = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        let action = AdminAction::TransferOwnership(new_owner.clone());
+        client.propose_action(&owner, &action);
+        assert_eq!(client.get_owner(), owner);
+
+        // Confirming a different action does not execute anything
+        let wrong = AdminAction::TransferOwnership(other);
+        assert_eq!(client.try_confirm_action(&owner, &wrong), Err(Ok(BudgetError::NoPendingAction)));
+
+        client.confirm_action(&owner, &action);
+        assert_eq!(client.get_owner(), new_owner);
+        assert_eq!(client.get_pending_action(), None);
+    }
+
+    #[test]
+    fn test_limit_shrink_requires_confirmation() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+
+        client.initialize(&owner, &5000, &0, &10000);
+
+        env.mock_all_auths();
+        client.set_limits(&owner, &100, &8000);
+        assert_eq!(client.try_set_limits(&owner, &0, &4000), Err(Ok(BudgetError::ConfirmationRequired)));
+
+        let action = AdminAction::SetLimits(0, 4000);
+        client.propose_action(&owner, &action);
+        client.confirm_action(&owner, &action);
+
+        let budget = client.get_budget();
+        assert_eq!(budget.current, 4000);
+        assert_eq!(budget.max, 4000);
+    }
+
+    #[test]
+    fn test_expired_intent() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let new_owner = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.set_confirm_window(&owner, &10);
+        let action = AdminAction::TransferOwnership(new_owner);
+        client.propose_action(&owner, &action);
+
+        env.ledger().with_mut(|l| l.sequence_number += 11);
+        assert_eq!(client.try_confirm_action(&owner, &action), Err(Ok(BudgetError::ActionExpired)));
+        assert_eq!(client.get_owner(), owner);
+    }
+
+    #[test]
+    fn test_decommission_sweeps_and_disables() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let recipient = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+
+        let sac = env.register_stellar_asset_contract_v2(owner.clone());
+        token::StellarAssetClient::new(&env, &sac.address()).mint(&contract_id, &700);
+
+        client.set_decommission_delay(&owner, &100);
+        let tokens = vec![&env, sac.address()];
+        client.propose_action(&owner, &AdminAction::Decommission(recipient.clone(), tokens.clone()));
+
+        // The timelock has to pass first
+        assert_eq!(client.try_decommission(&owner, &recipient, &tokens), Err(Ok(BudgetError::TimelockActive)));
+        env.ledger().with_mut(|l| l.sequence_number += 100);
+        client.decommission(&owner, &recipient, &tokens);
+
+        assert!(client.is_decommissioned());
+        assert_eq!(token::Client::new(&env, &sac.address()).balance(&recipient), 700);
+        assert_eq!(client.get_operators().len(), 0);
+        assert_eq!(client.try_add_operator(&owner, &operator), Err(Ok(BudgetError::Decommissioned)));
+        assert_eq!(client.try_increase_budget(&operator, &1), Err(Ok(BudgetError::Decommissioned)));
+        assert_eq!(client.try_initialize(&owner, &0, &0, &0), Err(Ok(BudgetError::Decommissioned)));
+    }
+
+    #[test]
+    fn test_decommission_leaves_held_funds() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let staker = Address::generate(&env);
+        let recipient = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        let token = mocks::register_token(&env, &owner);
+        token.mint(&contract_id, &1000);
+        token.mint(&staker, &300);
+        client.set_token(&owner, &token.address);
+        client.stake(&staker, &300);
+
+        // Only the contract's own 1000 is swept, the bond stays behind
+        let tokens = vec![&env, token.address.clone()];
+        client.propose_action(&owner, &AdminAction::Decommission(recipient.clone(), tokens.clone()));
+        client.decommission(&owner, &recipient, &tokens);
+        let balances = token::Client::new(&env, &token.address);
+        assert_eq!(balances.balance(&recipient), 1000);
+
+        // and can still be withdrawn
+        client.unstake(&staker, &300);
+        assert_eq!(balances.balance(&staker), 300);
+        assert_eq!(balances.balance(&contract_id), 0);
+    }
+}
@@ -0,0 +1,184 @@
+// Budget alert subscribers.
+// The owner configures alert thresholds and registers subscriber contracts implementing
+// AlertSubscriber. Whenever a budget write moves current across a threshold, each subscriber's
+// notify is called with a BudgetAlert. Unlike hooks, a subscriber cannot block the change: a
+// failing notify is caught and reported with an "alertfail" event about the subscriber instead.
+
+use soroban_sdk::{contractclient, contractimpl, contracttype, Address, Env, Vec};
+
+use crate::{
+    events, require_owner, AlertFailEvent, BudgetError, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient,
+};
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct BudgetAlert {
+    pub threshold: i128,
+    pub previous: i128,
+    pub current: i128,
+    pub rising: bool,
+}
+// BudgetAlert describes one threshold crossing:
+// rising is true when current reached the threshold from below, false when it fell below it
+
+
+// Interface subscriber contracts implement to receive alerts.
+#[contractclient(name = "AlertSubscriberClient")]
+pub trait AlertSubscriber {
+    fn notify(env: Env, event: BudgetAlert);
+}
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum AlertKey {
+    AlertThresholds,
+    AlertSubscribers,
+}
+// AlertThresholds stores the current values that trigger alerts
+// AlertSubscribers stores the subscriber contracts
+
+
+// Each crossing costs a cross-contract call per subscriber, so both lists stay small.
+pub(crate) const MAX_THRESHOLDS: u32 = 10;
+const MAX_SUBSCRIBERS: u32 = 5;
+
+fn read_thresholds(env: &Env) -> Vec<i128> {
+    env.storage().persistent().get(&AlertKey::AlertThresholds).unwrap_or(Vec::new(env))
+}
+
+fn read_subscribers(env: &Env) -> Vec<Address> {
+    env.storage().persistent().get(&AlertKey::AlertSubscribers).unwrap_or(Vec::new(env))
+}
+
+// Called for every budget write with the previous and the new current value.
+pub(crate) fn on_change(env: &Env, previous: i128, current: i128) {
+    let subscribers = read_subscribers(env);
+    if subscribers.is_empty() {
+        return;
+    }
+    for threshold in read_thresholds(env).iter() {
+        let rising = previous < threshold && current >= threshold;
+        let falling = previous >= threshold && current < threshold;
+        if !(rising || falling) {
+            continue;
+        }
+        let alert = BudgetAlert { threshold, previous, current, rising };
+        for subscriber in subscribers.iter() {
+            if AlertSubscriberClient::new(env, &subscriber).try_notify(&alert).is_err() {
+                events::publish(env, events::ALERT_FAIL, &subscriber, AlertFailEvent { threshold });
+            }
+        }
+    }
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Set the current values that trigger alerts when crossed (owner only)
+    pub fn set_alert_thresholds(env: Env, caller: Address, thresholds: Vec<i128>) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        if thresholds.len() > MAX_THRESHOLDS {
+            return Err(BudgetError::InvalidConfig);
+        }
+        env.storage().persistent().set(&AlertKey::AlertThresholds, &thresholds);
+        Ok(())
+    }
+
+    /// Register a contract to be notified of threshold crossings (owner only)
+    pub fn subscribe_alerts(env: Env, caller: Address, subscriber: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+
+        let mut subscribers = read_subscribers(&env);
+        if subscribers.contains(&subscriber) {
+            return Err(BudgetError::AlreadyRegistered);
+        }
+        if subscribers.len() >= MAX_SUBSCRIBERS {
+            return Err(BudgetError::InvalidConfig);
+        }
+
+        subscribers.push_back(subscriber);
+        env.storage().persistent().set(&AlertKey::AlertSubscribers, &subscribers);
+        Ok(())
+    }
+
+    /// Stop notifying a subscriber (owner only)
+    pub fn unsubscribe_alerts(env: Env, caller: Address, subscriber: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+
+        let mut subscribers = read_subscribers(&env);
+        let index = subscribers.first_index_of(&subscriber).ok_or(BudgetError::NotRegistered)?;
+        subscribers.remove(index);
+        env.storage().persistent().set(&AlertKey::AlertSubscribers, &subscribers);
+        Ok(())
+    }
+
+    // Get the alert thresholds
+    pub fn get_alert_thresholds(env: Env) -> Vec<i128> {
+        read_thresholds(&env)
+    }
+
+    // Get the alert subscribers
+    pub fn get_alert_subscribers(env: Env) -> Vec<Address> {
+        read_subscribers(&env)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{contract, contractimpl, symbol_short, vec};
+
+    // Subscriber that remembers the last alert it received, or rejects it once told to
+    #[contract]
+    pub struct RecordingSubscriber;
+
+    #[contractimpl]
+    impl AlertSubscriber for RecordingSubscriber {
+        fn notify(env: Env, event: BudgetAlert) {
+            if env.storage().instance().has(&symbol_short!("reject")) {
+                panic!("rejected");
+            }
+            env.storage().instance().set(&symbol_short!("last"), &event);
+        }
+    }
+
+    #[test]
+    fn test_alert_subscribers() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+        let recording = env.register_contract(None, RecordingSubscriber);
+        let failing = env.register_contract(None, RecordingSubscriber);
+        env.as_contract(&failing, || env.storage().instance().set(&symbol_short!("reject"), &true));
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_alert_thresholds(&owner, &vec![&env, 500]);
+        client.subscribe_alerts(&owner, &failing);
+        client.subscribe_alerts(&owner, &recording);
+
+        // The failing subscriber does not block the decrease
+        client.decrease_budget(&operator, &600);
+        assert_eq!(client.get_budget().current, 400);
+        let last: BudgetAlert = env.as_contract(&recording, || {
+            env.storage().instance().get(&symbol_short!("last")).unwrap()
+        });
+        assert_eq!(last, BudgetAlert { threshold: 500, previous: 1000, current: 400, rising: false });
+
+        client.increase_budget(&operator, &100);
+        let last: BudgetAlert = env.as_contract(&recording, || {
+            env.storage().instance().get(&symbol_short!("last")).unwrap()
+        });
+        assert!(last.rising);
+    }
+}
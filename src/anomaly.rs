@@ -0,0 +1,154 @@
+// Anomalous adjustment sizes.
+// With a multiple set, every committed adjustment updates an exponentially weighted mean and
+// variance of adjustment sizes (each new size weighs 1/WINDOW), and an adjustment whose size is
+// more than multiple standard deviations above the mean so far emits an anomaly event for
+// monitoring. Nothing is blocked. The first MIN_SAMPLES adjustments after turning it on only build
+// the baseline; turning it off drops it.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env};
+
+use crate::{
+    events, require_owner, AnomalyEvent, BudgetError, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient,
+};
+
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[contracttype]
+pub struct SizeStats {
+    pub mean: i128,
+    pub variance: i128,
+    pub samples: u32,
+}
+// SizeStats is the running baseline of adjustment sizes:
+// mean and variance are weighted towards the most recent sizes
+// samples is the number of adjustments seen, capped once it reaches WINDOW
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum AnomalyKey {
+    AnomalyMultiple,
+    SizeStats,
+}
+// AnomalyMultiple stores how many standard deviations above the mean count as anomalous (missing or 0 means off)
+// SizeStats stores the SizeStats
+
+
+const WINDOW: i128 = 16;
+const MIN_SAMPLES: u32 = 5;
+
+fn multiple(env: &Env) -> u32 {
+    env.storage().persistent().get(&AnomalyKey::AnomalyMultiple).unwrap_or(0)
+}
+
+fn read_stats(env: &Env) -> SizeStats {
+    env.storage().persistent().get(&AnomalyKey::SizeStats).unwrap_or_default()
+}
+
+// Whether size lies more than multiple standard deviations above the mean, compared squared.
+fn is_anomalous(stats: &SizeStats, multiple: u32, size: i128) -> bool {
+    let excess = size - stats.mean;
+    if stats.samples < MIN_SAMPLES || excess <= 0 {
+        return false;
+    }
+    let allowed = stats.variance.saturating_mul((multiple as i128).pow(2));
+    excess.saturating_mul(excess) > allowed
+}
+
+// Called for every committed adjustment.
+pub(crate) fn record(env: &Env, caller: &Address, previous: i128, new: i128) {
+    let multiple = multiple(env);
+    if multiple == 0 {
+        return;
+    }
+    let size = new.abs_diff(previous).min(i128::MAX as u128) as i128;
+    let mut stats = read_stats(env);
+    if is_anomalous(&stats, multiple, size) {
+        events::publish(env, events::ANOMALY, caller, AnomalyEvent { size, mean: stats.mean });
+    }
+
+    if stats.samples == 0 {
+        stats.mean = size;
+    } else {
+        let diff = size.saturating_sub(stats.mean);
+        stats.mean = stats.mean.saturating_add(diff / WINDOW);
+        stats.variance = stats.variance.saturating_add(diff.saturating_mul(diff) / WINDOW).saturating_mul(WINDOW - 1) / WINDOW;
+    }
+    stats.samples = stats.samples.saturating_add(1).min(WINDOW as u32);
+    env.storage().persistent().set(&AnomalyKey::SizeStats, &stats);
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Emit an anomaly event for adjustments more than multiple standard deviations above the usual size, 0 to turn it off (owner only)
+    pub fn set_anomaly_multiple(env: Env, caller: Address, multiple: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        if multiple == 0 {
+            env.storage().persistent().remove(&AnomalyKey::SizeStats);
+        }
+        env.storage().persistent().set(&AnomalyKey::AnomalyMultiple, &multiple);
+        Ok(())
+    }
+
+    // Get the anomaly multiple (0 when off)
+    pub fn get_anomaly_multiple(env: Env) -> u32 {
+        multiple(&env)
+    }
+
+    // Get the baseline of adjustment sizes
+    pub fn get_size_stats(env: Env) -> SizeStats {
+        read_stats(&env)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Events};
+    use soroban_sdk::{symbol_short, IntoVal};
+
+    #[test]
+    fn test_anomaly_event() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &10000, &0, &100000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_anomaly_multiple(&owner, &3);
+
+        let topics = (symbol_short!("anomaly"), EVENT_SCHEMA_VERSION, operator.clone()).into_val(&env);
+        let flagged = || env.events().all().iter().any(|(_, event_topics, _)| event_topics == topics);
+
+        // Sizes around 100 build the baseline, deviations are not flagged before it has enough samples
+        for amount in [100, 90, 110, 100, 95, 105] {
+            client.increase_budget(&operator, &amount);
+            assert!(!flagged());
+        }
+        let stats = client.get_size_stats();
+        assert_eq!((stats.mean, stats.samples), (100, 6));
+
+        // Usual sizes pass in either direction, an outlier is flagged but still applied
+        client.decrease_budget(&operator, &104);
+        assert!(!flagged());
+        let mean = client.get_size_stats().mean;
+        client.decrease_budget(&operator, &1000);
+        assert!(flagged());
+        let (_, _, data) = env.events().all().iter().find(|(_, event_topics, _)| *event_topics == topics).unwrap();
+        let data: AnomalyEvent = data.into_val(&env);
+        assert_eq!(data, AnomalyEvent { size: 1000, mean });
+        assert_eq!(client.get_budget().current, 9496);
+
+        // Turning it off drops the baseline
+        client.set_anomaly_multiple(&owner, &0);
+        assert_eq!(client.get_size_stats(), SizeStats::default());
+    }
+}
@@ -0,0 +1,127 @@
+// Pluggable external authorizer.
+// Organizations with an on-chain RBAC system can register an authorizer contract implementing
+// Authorizer. Every operator check then asks it whether the address may perform the "operate"
+// action. In Supplement mode an address passes if it is in the operators list or the authorizer
+// approves it; in Replace mode the authorizer alone decides and the operators list is ignored.
+// Owner and admin checks never consult the authorizer.
+
+use soroban_sdk::{contractclient, contractimpl, contracttype, symbol_short, Address, Env, Symbol};
+
+use crate::{require_owner, BudgetError, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient};
+
+
+// Interface external authorizers implement.
+#[contractclient(name = "AuthorizerClient")]
+pub trait Authorizer {
+    fn is_authorized(env: Env, address: Address, action: Symbol) -> bool;
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[contracttype]
+pub enum AuthorizerMode {
+    Supplement,
+    Replace,
+}
+// AuthorizerMode tells whether the authorizer adds to the operators list or replaces it
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct AuthorizerConfig {
+    pub authorizer: Address,
+    pub mode: AuthorizerMode,
+}
+// AuthorizerConfig is the registered authorizer contract and how it is combined with the operators list
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum AuthorizerKey {
+    Authorizer,
+}
+// Authorizer stores the AuthorizerConfig (missing means only the operators list counts)
+
+
+pub(crate) fn config(env: &Env) -> Option<AuthorizerConfig> {
+    env.storage().persistent().get(&AuthorizerKey::Authorizer)
+}
+
+// Asks the authorizer whether an address may act as operator.
+pub(crate) fn approves(env: &Env, config: &AuthorizerConfig, address: &Address) -> bool {
+    AuthorizerClient::new(env, &config.authorizer).is_authorized(address, &symbol_short!("operate"))
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Register an external authorizer for operator checks (owner only)
+    pub fn set_authorizer(env: Env, caller: Address, authorizer: Address, mode: AuthorizerMode) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().set(&AuthorizerKey::Authorizer, &AuthorizerConfig { authorizer, mode });
+        Ok(())
+    }
+
+    /// Go back to the operators list alone (owner only)
+    pub fn clear_authorizer(env: Env, caller: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().remove(&AuthorizerKey::Authorizer);
+        Ok(())
+    }
+
+    // Get the external authorizer configuration, if any
+    pub fn get_authorizer(env: Env) -> Option<AuthorizerConfig> {
+        config(&env)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{contract, contractimpl, symbol_short, Symbol};
+
+    // RBAC stub that authorizes one stored address
+    #[contract]
+    pub struct Rbac;
+
+    #[contractimpl]
+    impl Rbac {
+        pub fn grant(env: Env, address: Address) {
+            env.storage().instance().set(&symbol_short!("member"), &address);
+        }
+
+        pub fn is_authorized(env: Env, address: Address, _action: Symbol) -> bool {
+            env.storage().instance().get(&symbol_short!("member")) == Some(address)
+        }
+    }
+
+    #[test]
+    fn test_authorizer_modes() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+        let rbac_id = env.register_contract(None, Rbac);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let member = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        RbacClient::new(&env, &rbac_id).grant(&member);
+
+        client.set_authorizer(&owner, &rbac_id, &AuthorizerMode::Supplement);
+        client.increase_budget(&operator, &100);
+        client.increase_budget(&member, &100);
+
+        // Only the RBAC system counts now
+        client.set_authorizer(&owner, &rbac_id, &AuthorizerMode::Replace);
+        assert_eq!(client.try_increase_budget(&operator, &100), Err(Ok(BudgetError::NotOperator)));
+        assert_eq!(client.increase_budget(&member, &100).new, 1300);
+    }
+}
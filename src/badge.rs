@@ -0,0 +1,110 @@
+// NFT-gated operator eligibility.
+// The owner can require operators to hold a badge from a configured NFT contract. The badge is
+// checked when an operator is nominated and when it accepts, and again on every operator check,
+// so an operator whose badge was revoked or transferred loses its rights until it holds one again.
+// A deputy acts on its operator's badge. Addresses approved by an external authorizer are governed
+// by that authorizer instead.
+
+use soroban_sdk::{contractclient, contractimpl, contracttype, Address, Env};
+
+use crate::{require_owner, BudgetError, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient};
+
+
+// The part of the NFT interface used to look up badges.
+#[contractclient(name = "BadgeNftClient")]
+pub trait BadgeNft {
+    fn balance(env: Env, owner: Address) -> u32;
+}
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum BadgeKey {
+    BadgeNft,
+}
+// BadgeNft stores the NFT contract operators must hold a token of (missing means no badge is required)
+
+
+// Fails if a badge is required and the address holds none.
+pub(crate) fn check(env: &Env, address: &Address) -> Result<(), BudgetError> {
+    let nft: Option<Address> = env.storage().persistent().get(&BadgeKey::BadgeNft);
+    match nft {
+        Some(nft) if BadgeNftClient::new(env, &nft).balance(address) == 0 => Err(BudgetError::BadgeRequired),
+        _ => Ok(()),
+    }
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Require operators to hold a token of an NFT contract, None lifts the requirement (owner only)
+    pub fn set_badge_nft(env: Env, caller: Address, nft: Option<Address>) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        match nft {
+            Some(nft) => env.storage().persistent().set(&BadgeKey::BadgeNft, &nft),
+            None => env.storage().persistent().remove(&BadgeKey::BadgeNft),
+        }
+        Ok(())
+    }
+
+    // Get the NFT contract operators must hold a token of, if any
+    pub fn get_badge_nft(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&BadgeKey::BadgeNft)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{contract, contractimpl, contracttype};
+
+    #[derive(Clone)]
+    #[contracttype]
+    pub enum NftKey {
+        Holder(Address),
+    }
+
+    // Badge collection whose holdings the test sets directly
+    #[contract]
+    pub struct Badges;
+
+    #[contractimpl]
+    impl Badges {
+        pub fn set(env: Env, holder: Address, count: u32) {
+            env.storage().instance().set(&NftKey::Holder(holder), &count);
+        }
+
+        pub fn balance(env: Env, owner: Address) -> u32 {
+            env.storage().instance().get(&NftKey::Holder(owner)).unwrap_or(0)
+        }
+    }
+
+    #[test]
+    fn test_badge_required() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+        let badges_id = env.register_contract(None, Badges);
+        let badges = BadgesClient::new(&env, &badges_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.set_badge_nft(&owner, &Some(badges_id.clone()));
+        assert_eq!(client.try_add_operator(&owner, &operator), Err(Ok(BudgetError::BadgeRequired)));
+
+        badges.set(&operator, &1);
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.increase_budget(&operator, &100);
+
+        // Losing the badge suspends the operator's rights
+        badges.set(&operator, &0);
+        assert_eq!(client.try_increase_budget(&operator, &100), Err(Ok(BudgetError::BadgeRequired)));
+    }
+}
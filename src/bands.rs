@@ -0,0 +1,271 @@
+// Approval bands.
+// The owner can map ranges of adjustment amounts to the approval they need, for example a single
+// operator below 1000, two operators up to 10000 and the owner plus a timelock above that. The
+// band of an amount is the last one whose lower bound it reaches. Every adjustment, however it is
+// made (directly, queued, signed, by a deputy or as a payment), fails with ConfirmationRequired
+// when its band needs more than the operator making it. Those amounts go through a request
+// instead: an operator requests the adjustment, approvals are collected with approve_adjustment,
+// and once the band's requirement is met anyone can execute it, for the requesting operator and
+// with every other check of a normal adjustment. The requirement is evaluated again at execution,
+// so a band change applies to open requests too. Payments can only be made within single bands.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env, Vec};
+
+use crate::{
+    check_operator, decrease_approved, increase_approved, is_listed, read_owner, require_operator, require_owner,
+    rotation, BudgetError, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient, Receipt,
+};
+#[cfg(feature = "category")]
+use crate::category;
+#[cfg(feature = "tiers")]
+use crate::tiers;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub enum Approval {
+    Single,
+    Operators(u32),
+    OwnerTimelock(u32),
+}
+// Approval is what an adjustment in a band needs:
+// Single is the operator making it, as without bands
+// Operators(n) is a request approved by n different operators, the requester included
+// OwnerTimelock(ledgers) is a request approved by the owner, executable that many ledgers later
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ApprovalBand {
+    pub from: i128,
+    pub approval: Approval,
+}
+// ApprovalBand applies its Approval to amounts from its lower bound up to the next band's
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct BandRequest {
+    pub caller: Address,
+    pub amount: i128,
+    pub increase: bool,
+    pub approvals: Vec<Address>,
+    pub ready_ledger: Option<u32>,
+}
+// BandRequest stores a requested adjustment:
+// caller is the operator who requested it and is credited with it when it executes
+// approvals are the operators that approved it so far, the requester first
+// ready_ledger is set by the owner's approval to the first ledger it can be executed in
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum BandKey {
+    ApprovalBands,
+    BandRequestCount,
+    BandRequest(u64),
+}
+// ApprovalBands stores the list of ApprovalBand in ascending order (missing or empty means no bands)
+// BandRequestCount stores the number of requests ever made (the next request id)
+// BandRequest stores an open BandRequest by id until it is executed or cancelled
+
+
+// Every band list is read on every adjustment, so it stays small.
+const MAX_BANDS: u32 = 10;
+
+fn read_bands(env: &Env) -> Vec<ApprovalBand> {
+    env.storage().persistent().get(&BandKey::ApprovalBands).unwrap_or(Vec::new(env))
+}
+
+fn approval_for(env: &Env, amount: i128) -> Approval {
+    let mut approval = Approval::Single;
+    for band in read_bands(env).iter() {
+        if amount >= band.from {
+            approval = band.approval;
+        }
+    }
+    approval
+}
+
+fn read_request(env: &Env, id: u64) -> Result<BandRequest, BudgetError> {
+    env.storage().persistent().get(&BandKey::BandRequest(id)).ok_or(BudgetError::AdjustmentNotFound)
+}
+
+// Rejects adjustments whose band needs more than the operator making them.
+pub(crate) fn check(env: &Env, amount: i128) -> Result<(), BudgetError> {
+    match approval_for(env, amount) {
+        Approval::Single => Ok(()),
+        _ => Err(BudgetError::ConfirmationRequired),
+    }
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Replace the approval bands, in ascending order of their lower bounds (owner only)
+    pub fn set_approval_bands(env: Env, caller: Address, bands: Vec<ApprovalBand>) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        if bands.len() > MAX_BANDS {
+            return Err(BudgetError::InvalidConfig);
+        }
+        let mut previous: Option<i128> = None;
+        for band in bands.iter() {
+            let unordered = previous.is_some_and(|from| band.from <= from);
+            if band.from < 0 || unordered || band.approval == Approval::Operators(0) {
+                return Err(BudgetError::InvalidConfig);
+            }
+            previous = Some(band.from);
+        }
+        env.storage().persistent().set(&BandKey::ApprovalBands, &bands);
+        Ok(())
+    }
+
+    /// Request an adjustment whose band needs further approval (operators only), returns the request id
+    pub fn request_adjustment(env: Env, caller: Address, amount: i128, increase: bool) -> Result<u64, BudgetError> {
+        require_operator(&env, &caller)?;
+        if amount <= 0 {
+            return Err(BudgetError::InvalidAmount);
+        }
+        if approval_for(&env, amount) == Approval::Single {
+            return Err(BudgetError::NoOp);
+        }
+        let id: u64 = env.storage().persistent().get(&BandKey::BandRequestCount).unwrap_or(0);
+        env.storage().persistent().set(&BandKey::BandRequestCount, &(id + 1));
+        let approvals = Vec::from_array(&env, [caller.clone()]);
+        let request = BandRequest { caller, amount, increase, approvals, ready_ledger: None };
+        env.storage().persistent().set(&BandKey::BandRequest(id), &request);
+        Ok(id)
+    }
+
+    /// Approve a requested adjustment (other operators, or the owner for owner bands)
+    pub fn approve_adjustment(env: Env, caller: Address, id: u64) -> Result<(), BudgetError> {
+        let mut request = read_request(&env, id)?;
+        if let Approval::OwnerTimelock(ledgers) = approval_for(&env, request.amount) {
+            require_owner(&env, &caller)?;
+            request.ready_ledger = Some(env.ledger().sequence().saturating_add(ledgers));
+        } else {
+            require_operator(&env, &caller)?;
+            if request.approvals.iter().any(|approver| rotation::is_same(&env, &approver, &caller)) {
+                return Err(BudgetError::AlreadyVoted);
+            }
+            request.approvals.push_back(caller);
+        }
+        env.storage().persistent().set(&BandKey::BandRequest(id), &request);
+        Ok(())
+    }
+
+    /// Execute a requested adjustment once its band's approvals are complete (anyone)
+    pub fn execute_adjustment(env: Env, id: u64) -> Result<Receipt, BudgetError> {
+        let request = read_request(&env, id)?;
+        match approval_for(&env, request.amount) {
+            Approval::Single => {}
+            Approval::Operators(required) => {
+                // Only approvals by addresses that are still operators count
+                let mut approvals = 0;
+                for approver in request.approvals.iter() {
+                    if is_listed(&env, &rotation::current(&env, &approver)) {
+                        approvals += 1;
+                    }
+                }
+                if approvals < required {
+                    return Err(BudgetError::ConfirmationRequired);
+                }
+            }
+            Approval::OwnerTimelock(_) => match request.ready_ledger {
+                None => return Err(BudgetError::ConfirmationRequired),
+                Some(ready) if env.ledger().sequence() < ready => return Err(BudgetError::TimelockActive),
+                Some(_) => {}
+            },
+        }
+        env.storage().persistent().remove(&BandKey::BandRequest(id));
+
+        let caller = rotation::current(&env, &request.caller);
+        check_operator(&env, &caller)?;
+        #[cfg(feature = "category")]
+        category::check_scope(&env, &caller, None)?;
+        #[cfg(feature = "tiers")]
+        tiers::check_direct(&env, &caller)?;
+        if request.increase {
+            increase_approved(&env, &caller, request.amount)
+        } else {
+            decrease_approved(&env, &caller, request.amount)
+        }
+    }
+
+    /// Drop a requested adjustment (the operator who requested it or the owner)
+    pub fn cancel_adjustment(env: Env, caller: Address, id: u64) -> Result<(), BudgetError> {
+        caller.require_auth();
+        let request = read_request(&env, id)?;
+        if !rotation::is_same(&env, &request.caller, &caller) && read_owner(&env) != caller {
+            return Err(BudgetError::NotOwner);
+        }
+        env.storage().persistent().remove(&BandKey::BandRequest(id));
+        Ok(())
+    }
+
+    // Get the approval bands
+    pub fn get_approval_bands(env: Env) -> Vec<ApprovalBand> {
+        read_bands(&env)
+    }
+
+    // Get an open adjustment request
+    pub fn get_adjustment_request(env: Env, id: u64) -> Option<BandRequest> {
+        env.storage().persistent().get(&BandKey::BandRequest(id))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::vec;
+
+    #[test]
+    fn test_approval_bands() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let alice = Address::generate(&env);
+        let bob = Address::generate(&env);
+
+        client.initialize(&owner, &20000, &0, &100000);
+
+        env.mock_all_auths();
+        for operator in [&alice, &bob] {
+            client.add_operator(&owner, operator);
+            client.accept_operator(operator);
+        }
+        let bands = vec![
+            &env,
+            ApprovalBand { from: 0, approval: Approval::Single },
+            ApprovalBand { from: 1000, approval: Approval::Operators(2) },
+            ApprovalBand { from: 10001, approval: Approval::OwnerTimelock(100) },
+        ];
+        client.set_approval_bands(&owner, &bands);
+
+        // Small amounts as before, larger ones need a request
+        client.decrease_budget(&alice, &999);
+        assert_eq!(client.try_decrease_budget(&alice, &1000), Err(Ok(BudgetError::ConfirmationRequired)));
+        assert_eq!(client.check_adjustment(&alice, &1000, &false).error, BudgetError::ConfirmationRequired as u32);
+
+        // Two operators
+        let id = client.request_adjustment(&alice, &5000, &false);
+        assert_eq!(client.try_execute_adjustment(&id), Err(Ok(BudgetError::ConfirmationRequired)));
+        assert_eq!(client.try_approve_adjustment(&alice, &id), Err(Ok(BudgetError::AlreadyVoted)));
+        client.approve_adjustment(&bob, &id);
+        let receipt = client.execute_adjustment(&id);
+        assert_eq!((receipt.new, receipt.caller), (14001, alice.clone()));
+
+        // Owner plus timelock
+        let id = client.request_adjustment(&bob, &20000, &true);
+        assert_eq!(client.try_approve_adjustment(&alice, &id), Err(Ok(BudgetError::NotOwner)));
+        client.approve_adjustment(&owner, &id);
+        assert_eq!(client.try_execute_adjustment(&id), Err(Ok(BudgetError::TimelockActive)));
+        env.ledger().with_mut(|l| l.sequence_number = 100);
+        assert_eq!(client.execute_adjustment(&id).new, 34001);
+        assert_eq!(client.get_adjustment_request(&id), None);
+    }
+}
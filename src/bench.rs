@@ -0,0 +1,154 @@
+// Cost benchmarks.
+// Measures the CPU instructions and memory each main entrypoint costs in the Soroban cost model,
+// with operator sets of different sizes, and fails when one grows past its recorded ceiling. The
+// ceilings are the measured costs plus some headroom; a change that raises them on purpose updates
+// the table (run with --nocapture to see the measured costs). In the test host every call also
+// gets dearer with the number of storage entries, so what an entrypoint adds itself as operators
+// are added shows when it is compared with get_budget at the same size.
+// The size of the release wasm is reported next to the costs, against the network's contract size
+// limit. Which features the last release build had is not known here, so size.sh is what enforces it.
+
+extern crate std;
+
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{Address, Env, String};
+
+use crate::{mocks, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient};
+
+
+const SIZES: [u32; 3] = [1, 10, 50];
+
+// Network limit on the size of a contract's wasm, in bytes (the same as in size.sh)
+const WASM_SIZE_LIMIT: u64 = 65_536;
+
+// (entrypoint, operators, max CPU instructions, max memory bytes)
+const CEILINGS: [(&str, u32, u64, u64); 24] = [
+    ("increase_budget", 1, 880_000, 200_000),
+    ("increase_budget", 10, 1_230_000, 330_000),
+    ("increase_budget", 50, 2_730_000, 900_000),
+    ("decrease_budget", 1, 980_000, 230_000),
+    ("decrease_budget", 10, 1_380_000, 370_000),
+    ("decrease_budget", 50, 3_050_000, 1_010_000),
+    ("check_adjustment", 1, 240_000, 40_000),
+    ("check_adjustment", 10, 340_000, 80_000),
+    ("check_adjustment", 50, 730_000, 240_000),
+    ("spend", 1, 1_340_000, 310_000),
+    ("spend", 10, 1_830_000, 490_000),
+    ("spend", 50, 3_910_000, 1_300_000),
+    ("is_operator", 1, 40_000, 10_000),
+    ("is_operator", 10, 40_000, 10_000),
+    ("is_operator", 50, 50_000, 30_000),
+    ("get_budget", 1, 80_000, 20_000),
+    ("get_budget", 10, 110_000, 30_000),
+    ("get_budget", 50, 250_000, 90_000),
+    ("add_operator", 1, 310_000, 60_000),
+    ("add_operator", 10, 430_000, 100_000),
+    ("add_operator", 50, 930_000, 290_000),
+    ("remove_operator", 1, 130_000, 30_000),
+    ("remove_operator", 10, 200_000, 50_000),
+    ("remove_operator", 50, 500_000, 160_000),
+];
+
+struct Fixture {
+    env: Env,
+    client: GovernanceBudgetAllocatorClient<'static>,
+    owner: Address,
+    operator: Address,
+    recipient: Address,
+}
+
+fn setup(operators: u32) -> Fixture {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+    let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+    let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+    let owner = Address::generate(&env);
+    client.initialize(&owner, &1_000_000, &0, &10_000_000);
+
+    let backing = mocks::register_token(&env, &owner);
+    backing.mint(&contract_id, &1_000_000);
+    client.set_token(&owner, &backing.address);
+
+    let mut operator = owner.clone();
+    for _ in 0..operators {
+        operator = Address::generate(&env);
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+    }
+    Fixture { client, owner, operator, recipient: Address::generate(&env), env }
+}
+
+// Cost of a single call, measured from a reset budget.
+fn measure(fixture: &Fixture, call: impl FnOnce(&Fixture)) -> (u64, u64) {
+    let mut budget = fixture.env.budget();
+    budget.reset_default();
+    call(fixture);
+    (budget.cpu_instruction_cost(), budget.memory_bytes_cost())
+}
+
+fn costs(operators: u32) -> std::vec::Vec<(&'static str, u64, u64)> {
+    let mut costs = std::vec::Vec::new();
+    let mut record = |name: &'static str, call: &dyn Fn(&Fixture)| {
+        let fixture = setup(operators);
+        let (cpu, mem) = measure(&fixture, call);
+        costs.push((name, cpu, mem));
+    };
+    record("increase_budget", &|f| {
+        f.client.increase_budget(&f.operator, &100);
+    });
+    record("decrease_budget", &|f| {
+        f.client.decrease_budget(&f.operator, &100);
+    });
+    record("check_adjustment", &|f| {
+        f.client.check_adjustment(&f.operator, &100, &true);
+    });
+    record("spend", &|f| {
+        f.client.spend(&f.operator, &f.recipient, &100, &String::from_str(&f.env, "bench"));
+    });
+    record("is_operator", &|f| {
+        f.client.is_operator(&f.operator);
+    });
+    record("get_budget", &|f| {
+        f.client.get_budget();
+    });
+    record("add_operator", &|f| {
+        let operator = Address::generate(&f.env);
+        f.client.add_operator(&f.owner, &operator);
+        f.client.accept_operator(&operator);
+    });
+    record("remove_operator", &|f| {
+        f.client.remove_operator(&f.owner, &f.operator);
+    });
+    costs
+}
+
+
+#[test]
+fn test_entrypoint_costs() {
+    let mut over = std::vec::Vec::new();
+    for operators in SIZES {
+        for (name, cpu, mem) in costs(operators) {
+            std::println!("{name:<18} operators={operators:<3} cpu={cpu:<10} mem={mem}");
+            let ceiling = CEILINGS.iter().find(|(entry, size, _, _)| *entry == name && *size == operators);
+            match ceiling {
+                Some((_, _, max_cpu, max_mem)) if cpu <= *max_cpu && mem <= *max_mem => {}
+                _ => over.push((name, operators)),
+            }
+        }
+    }
+    assert!(over.is_empty(), "costs over their ceiling (or without one): {over:?}");
+}
+
+#[test]
+fn test_wasm_size() {
+    let target = std::env::var("CARGO_TARGET_DIR").unwrap_or(std::format!("{}/target", env!("CARGO_MANIFEST_DIR")));
+    match std::fs::metadata(std::format!("{target}/wasm32-unknown-unknown/release/governance_budget.wasm")) {
+        Ok(wasm) => {
+            let size = wasm.len();
+            let verdict = if size <= WASM_SIZE_LIMIT { "within" } else { "over" };
+            std::println!("wasm size={size} limit={WASM_SIZE_LIMIT} ({verdict} the limit, check a deployment with size.sh)");
+        }
+        Err(_) => std::println!("wasm size: no release build found, run size.sh"),
+    }
+}
@@ -0,0 +1,113 @@
+// Blacklist of addresses that may never become operators.
+// The owner maintains the list; add_operator (and any other path that grants operator rights)
+// rejects listed addresses with Blacklisted. Blacklisting a current operator also removes it.
+// The admin cannot change the list, so it cannot undo an exclusion the owner made.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env, Vec};
+
+use crate::{read_operators, require_owner, set_listed, BudgetError, DataKey, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient};
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum BlacklistKey {
+    Blacklist,
+}
+// Blacklist stores the list of excluded addresses
+
+
+fn read_blacklist(env: &Env) -> Vec<Address> {
+    env.storage().persistent().get(&BlacklistKey::Blacklist).unwrap_or(Vec::new(env))
+}
+
+// Fails if the address is blacklisted.
+pub(crate) fn check(env: &Env, address: &Address) -> Result<(), BudgetError> {
+    if read_blacklist(env).contains(address) {
+        return Err(BudgetError::Blacklisted);
+    }
+    Ok(())
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Exclude an address from ever being an operator, removing it if it is one (owner only)
+    pub fn blacklist(env: Env, caller: Address, address: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+
+        let mut list = read_blacklist(&env);
+        if list.contains(&address) {
+            return Err(BudgetError::AlreadyRegistered);
+        }
+        list.push_back(address.clone());
+        env.storage().persistent().set(&BlacklistKey::Blacklist, &list);
+
+        let mut operators = read_operators(&env);
+        if let Some(index) = operators.first_index_of(&address) {
+            operators.remove(index);
+            env.storage().persistent().set(&DataKey::Operators, &operators);
+            set_listed(&env, &address, false);
+        }
+        Ok(())
+    }
+
+    /// Lift an exclusion (owner only)
+    pub fn unblacklist(env: Env, caller: Address, address: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+
+        let mut list = read_blacklist(&env);
+        let index = list.first_index_of(&address).ok_or(BudgetError::NotRegistered)?;
+        list.remove(index);
+        env.storage().persistent().set(&BlacklistKey::Blacklist, &list);
+        Ok(())
+    }
+
+    // Check if an address is blacklisted
+    pub fn is_blacklisted(env: Env, address: Address) -> bool {
+        read_blacklist(&env).contains(&address)
+    }
+
+    // Get every blacklisted address
+    pub fn get_blacklist(env: Env) -> Vec<Address> {
+        read_blacklist(&env)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+
+    #[test]
+    fn test_blacklisted_cannot_be_added() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let admin = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.set_admin(&owner, &admin);
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+
+        client.blacklist(&owner, &operator);
+        assert!(!client.is_operator(&operator));
+        assert!(client.is_blacklisted(&operator));
+
+        // Neither the admin nor the owner can re-add it, and the admin cannot lift the exclusion
+        assert_eq!(client.try_add_operator(&admin, &operator), Err(Ok(BudgetError::Blacklisted)));
+        assert_eq!(client.try_add_operator(&owner, &operator), Err(Ok(BudgetError::Blacklisted)));
+        assert_eq!(client.try_unblacklist(&admin, &operator), Err(Ok(BudgetError::NotOwner)));
+
+        client.unblacklist(&owner, &operator);
+        client.add_operator(&admin, &operator);
+        client.accept_operator(&operator);
+        assert!(client.is_operator(&operator));
+    }
+}
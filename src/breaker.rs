@@ -0,0 +1,151 @@
+// Circuit breaker that auto-pauses on rapid drain.
+// The owner configures a sliding window (in ledgers) and a threshold in basis points of max.
+// Every decrease is logged; when the decreases inside the window add up to more than the
+// threshold the contract pauses itself and emits an "alarm" event. The decrease that trips the
+// breaker is kept (failing it would also roll back the pause), everything after it is blocked
+// until the owner unpauses.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env, Vec};
+
+use crate::{
+    events, require_owner, set_paused, AlarmEvent, BudgetError, GovernanceBudgetAllocator,
+    GovernanceBudgetAllocatorClient,
+};
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct BreakerConfig {
+    pub window: u32,
+    pub threshold_bps: u32,
+}
+// BreakerConfig defines when the breaker trips:
+// window is the sliding window length in ledgers
+// threshold_bps is the allowed drain inside the window, in basis points of max
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum BreakerKey {
+    Breaker,
+    DrainLog,
+}
+// Breaker stores the BreakerConfig (missing means the breaker is off)
+// DrainLog stores the (ledger, amount) decreases still inside the window
+
+
+const BPS: i128 = 10_000;
+
+// Called for every committed decrease with the decreased amount and the current max.
+pub(crate) fn on_decrease(env: &Env, amount: i128, max: i128) {
+    let config: BreakerConfig = match env.storage().persistent().get(&BreakerKey::Breaker) {
+        Some(config) => config,
+        None => return,
+    };
+
+    // Drop decreases that slid out of the window and add this one
+    let ledger = env.ledger().sequence();
+    let log: Vec<(u32, i128)> = env.storage().persistent().get(&BreakerKey::DrainLog).unwrap_or(Vec::new(env));
+    let mut kept = Vec::new(env);
+    let mut drained: i128 = amount;
+    for (at, value) in log.iter() {
+        if ledger - at < config.window {
+            kept.push_back((at, value));
+            drained = drained.saturating_add(value);
+        }
+    }
+    kept.push_back((ledger, amount));
+    env.storage().persistent().set(&BreakerKey::DrainLog, &kept);
+
+    let limit = max.saturating_mul(config.threshold_bps as i128) / BPS;
+    if drained > limit {
+        set_paused(env, true);
+        events::publish_global(env, events::ALARM, AlarmEvent { drained, limit });
+    }
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Configure the drain circuit breaker (owner only)
+    pub fn set_circuit_breaker(env: Env, caller: Address, window: u32, threshold_bps: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        if window == 0 || threshold_bps > BPS as u32 {
+            return Err(BudgetError::InvalidConfig);
+        }
+        let config = BreakerConfig { window, threshold_bps };
+        env.storage().persistent().set(&BreakerKey::Breaker, &config);
+        Ok(())
+    }
+
+    /// Turn the circuit breaker off and forget the tracked decreases (owner only)
+    pub fn clear_circuit_breaker(env: Env, caller: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().remove(&BreakerKey::Breaker);
+        env.storage().persistent().remove(&BreakerKey::DrainLog);
+        Ok(())
+    }
+
+    // Get the circuit breaker configuration, if any
+    pub fn get_circuit_breaker(env: Env) -> Option<BreakerConfig> {
+        env.storage().persistent().get(&BreakerKey::Breaker)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Events, Ledger};
+
+    #[test]
+    fn test_breaker_pauses_on_drain() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &5000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        // At most 10% of max (1000) may be drained within 50 ledgers
+        client.set_circuit_breaker(&owner, &50, &1000);
+
+        client.decrease_budget(&operator, &600);
+        assert!(!client.is_paused());
+        client.decrease_budget(&operator, &600);
+        assert!(client.is_paused());
+        assert_eq!(env.events().all().len(), 1);
+
+        // Further adjustments are blocked until the owner unpauses
+        assert_eq!(client.try_decrease_budget(&operator, &1), Err(Ok(BudgetError::Paused)));
+        client.unpause(&owner);
+        assert_eq!(client.decrease_budget(&operator, &1).new, 3799);
+    }
+
+    #[test]
+    fn test_breaker_window_slides() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &5000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_circuit_breaker(&owner, &50, &1000);
+
+        client.decrease_budget(&operator, &600);
+        env.ledger().with_mut(|l| l.sequence_number += 50);
+        client.decrease_budget(&operator, &600);
+        assert!(!client.is_paused());
+    }
+}
@@ -0,0 +1,261 @@
+// Budget categories with individual freeze flags.
+// A category is a named slice of the budget (e.g. a department). Adjusting a category adjusts
+// the total budget by the same amount with all the usual checks, and also tracks how much of the
+// total belongs to that category. A category can never go below zero. The owner can freeze a
+// single category, which blocks its adjustments while the rest of the budget keeps working.
+// Operators can also be scoped to a set of categories. A scoped operator may only adjust those
+// categories, and none of the adjustment paths that do not name a category, so a department's
+// operator cannot move another department's money through the total budget either.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env, Symbol, Vec};
+
+use crate::{
+    acting_for, apply_decrease_in, apply_increase_in, require_owner, BudgetError, GovernanceBudgetAllocator,
+    GovernanceBudgetAllocatorClient,
+};
+
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[contracttype]
+pub struct Category {
+    pub current: i128,
+    pub frozen: bool,
+}
+// Category stores one category:
+// current is the part of the total budget held by the category
+// frozen blocks adjustments of the category
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum CategoryKey {
+    Category(Symbol),
+    Categories,
+    OperatorScope(Address),
+}
+// Category stores a Category by name
+// Categories stores the list of category names
+// OperatorScope stores the categories an operator is limited to (missing means unrestricted)
+
+
+pub(crate) fn read_category(env: &Env, name: &Symbol) -> Result<Category, BudgetError> {
+    env.storage().persistent().get(&CategoryKey::Category(name.clone())).ok_or(BudgetError::NotRegistered)
+}
+
+fn write_category(env: &Env, name: &Symbol, category: &Category) {
+    env.storage().persistent().set(&CategoryKey::Category(name.clone()), category);
+}
+
+// Loads a category that may be adjusted.
+pub(crate) fn open_category(env: &Env, name: &Symbol) -> Result<Category, BudgetError> {
+    let category = read_category(env, name)?;
+    if category.frozen {
+        return Err(BudgetError::CategoryFrozen);
+    }
+    Ok(category)
+}
+
+// Checks that an operator may adjust the given category, or the budget directly when there is none.
+// A deputy is held to the scope of the operator it stands in for.
+pub(crate) fn check_scope(env: &Env, caller: &Address, category: Option<&Symbol>) -> Result<(), BudgetError> {
+    let subject = acting_for(env, caller);
+    let scope: Option<Vec<Symbol>> = env.storage().persistent().get(&CategoryKey::OperatorScope(subject));
+    match (scope, category) {
+        (None, _) => Ok(()),
+        (Some(scope), Some(name)) if scope.contains(name) => Ok(()),
+        _ => Err(BudgetError::NotOperator),
+    }
+}
+
+fn set_frozen(env: &Env, name: &Symbol, frozen: bool) -> Result<(), BudgetError> {
+    let mut category = read_category(env, name)?;
+    category.frozen = frozen;
+    write_category(env, name, &category);
+    Ok(())
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Create an empty category (owner only)
+    pub fn add_category(env: Env, caller: Address, name: Symbol) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        if env.storage().persistent().has(&CategoryKey::Category(name.clone())) {
+            return Err(BudgetError::AlreadyRegistered);
+        }
+        write_category(&env, &name, &Category::default());
+
+        let mut names = Self::get_categories(env.clone());
+        names.push_back(name);
+        env.storage().persistent().set(&CategoryKey::Categories, &names);
+        Ok(())
+    }
+
+    /// Block adjustments of one category (owner only)
+    pub fn freeze_category(env: Env, caller: Address, name: Symbol) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        set_frozen(&env, &name, true)
+    }
+
+    /// Allow adjustments of a frozen category again (owner only)
+    pub fn unfreeze_category(env: Env, caller: Address, name: Symbol) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        set_frozen(&env, &name, false)
+    }
+
+    /// Increase a category and the total budget by the same amount (operators only)
+    pub fn increase_category(env: Env, caller: Address, name: Symbol, amount: i128) -> Result<i128, BudgetError> {
+        caller.require_auth();
+        if amount <= 0 {
+            return Err(BudgetError::InvalidAmount);
+        }
+        let mut category = open_category(&env, &name)?;
+        apply_increase_in(&env, &caller, Some(&name), amount)?;
+
+        category.current = category.current.checked_add(amount).ok_or(BudgetError::Overflow)?;
+        write_category(&env, &name, &category);
+        Ok(category.current)
+    }
+
+    /// Decrease a category and the total budget by the same amount (operators only)
+    pub fn decrease_category(env: Env, caller: Address, name: Symbol, amount: i128) -> Result<i128, BudgetError> {
+        caller.require_auth();
+        if amount <= 0 {
+            return Err(BudgetError::InvalidAmount);
+        }
+        let mut category = open_category(&env, &name)?;
+        if amount > category.current {
+            return Err(BudgetError::BelowMin);
+        }
+        apply_decrease_in(&env, &caller, Some(&name), amount)?;
+
+        category.current -= amount;
+        write_category(&env, &name, &category);
+        Ok(category.current)
+    }
+
+    /// Limit an operator to adjusting the given categories, an empty list lifts the limit (owner only)
+    pub fn set_operator_scope(env: Env, caller: Address, operator: Address, categories: Vec<Symbol>) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        let key = CategoryKey::OperatorScope(operator);
+        if categories.is_empty() {
+            env.storage().persistent().remove(&key);
+            return Ok(());
+        }
+        for name in categories.iter() {
+            read_category(&env, &name)?;
+        }
+        env.storage().persistent().set(&key, &categories);
+        Ok(())
+    }
+
+    // Get the categories an operator is limited to, if it is scoped
+    pub fn get_operator_scope(env: Env, operator: Address) -> Option<Vec<Symbol>> {
+        env.storage().persistent().get(&CategoryKey::OperatorScope(operator))
+    }
+
+    // Get a category by name
+    pub fn get_category(env: Env, name: Symbol) -> Option<Category> {
+        env.storage().persistent().get(&CategoryKey::Category(name))
+    }
+
+    // Get every category name
+    pub fn get_categories(env: Env) -> Vec<Symbol> {
+        env.storage().persistent().get(&CategoryKey::Categories).unwrap_or(Vec::new(&env))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::{symbol_short, vec};
+    use soroban_sdk::testutils::Address as _;
+
+    #[test]
+    fn test_freeze_single_category() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let eng = symbol_short!("eng");
+        let ops = symbol_short!("ops");
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.add_category(&owner, &eng);
+        client.add_category(&owner, &ops);
+
+        client.increase_category(&operator, &eng, &500);
+        client.increase_category(&operator, &ops, &300);
+        assert_eq!(client.get_budget().current, 1800);
+
+        client.freeze_category(&owner, &eng);
+        assert_eq!(client.try_decrease_category(&operator, &eng, &100), Err(Ok(BudgetError::CategoryFrozen)));
+
+        // The other category and the contract as a whole keep working
+        assert_eq!(client.decrease_category(&operator, &ops, &100), 200);
+        assert!(!client.is_paused());
+
+        client.unfreeze_category(&owner, &eng);
+        assert_eq!(client.decrease_category(&operator, &eng, &100), 400);
+        assert_eq!(client.get_budget().current, 1600);
+    }
+
+    #[test]
+    fn test_category_cannot_go_negative() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let eng = symbol_short!("eng");
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.add_category(&owner, &eng);
+        client.increase_category(&operator, &eng, &200);
+
+        assert_eq!(client.try_decrease_category(&operator, &eng, &300), Err(Ok(BudgetError::BelowMin)));
+        assert_eq!(client.try_increase_category(&operator, &symbol_short!("x"), &1), Err(Ok(BudgetError::NotRegistered)));
+    }
+
+    #[test]
+    fn test_scoped_operator() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let marketing = Address::generate(&env);
+        let eng = symbol_short!("eng");
+        let mkt = symbol_short!("mkt");
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &marketing);
+        client.accept_operator(&marketing);
+        client.add_category(&owner, &eng);
+        client.add_category(&owner, &mkt);
+        client.set_operator_scope(&owner, &marketing, &vec![&env, mkt.clone()]);
+
+        client.increase_category(&marketing, &mkt, &300);
+        assert_eq!(client.try_increase_category(&marketing, &eng, &100), Err(Ok(BudgetError::NotOperator)));
+        assert_eq!(client.try_decrease_budget(&marketing, &100), Err(Ok(BudgetError::NotOperator)));
+
+        // Lifting the scope restores global rights
+        client.set_operator_scope(&owner, &marketing, &vec![&env]);
+        client.increase_category(&marketing, &eng, &100);
+        assert_eq!(client.get_budget().current, 1400);
+    }
+}
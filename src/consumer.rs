@@ -0,0 +1,131 @@
+// Consumer contracts.
+// The owner registers contracts (e.g. an automated bounty contract) that may draw down the budget
+// through consume() without holding an operator key. The consumer passes its own address, which
+// it authorizes implicitly as the direct caller (env.current_contract_address() on its side), so
+// no other account can consume on its behalf. Consumption is a normal decrease in every other
+// respect: pause, granularity, limits, history and hooks all apply.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env, Vec};
+
+use crate::{
+    decrease_for, is_decommissioned, require_owner, BudgetError, GovernanceBudgetAllocator,
+    GovernanceBudgetAllocatorClient, Receipt,
+};
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum ConsumerKey {
+    Consumers,
+}
+// Consumers stores the list of registered consumer contracts
+
+
+fn read_consumers(env: &Env) -> Vec<Address> {
+    env.storage().persistent().get(&ConsumerKey::Consumers).unwrap_or(Vec::new(env))
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Allow a contract to draw down the budget with consume() (owner only)
+    pub fn register_consumer(env: Env, caller: Address, consumer: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        let mut consumers = read_consumers(&env);
+        if consumers.contains(&consumer) {
+            return Err(BudgetError::AlreadyRegistered);
+        }
+        consumers.push_back(consumer);
+        env.storage().persistent().set(&ConsumerKey::Consumers, &consumers);
+        Ok(())
+    }
+
+    /// Revoke a consumer contract (owner only)
+    pub fn remove_consumer(env: Env, caller: Address, consumer: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        let mut consumers = read_consumers(&env);
+        let index = consumers.first_index_of(&consumer).ok_or(BudgetError::NotRegistered)?;
+        consumers.remove(index);
+        env.storage().persistent().set(&ConsumerKey::Consumers, &consumers);
+        Ok(())
+    }
+
+    /// Decrease the budget on behalf of a registered consumer contract (the consumer itself only)
+    pub fn consume(env: Env, consumer: Address, amount: i128) -> Result<Receipt, BudgetError> {
+        consumer.require_auth();
+        if is_decommissioned(&env) {
+            return Err(BudgetError::Decommissioned);
+        }
+        if !read_consumers(&env).contains(&consumer) {
+            return Err(BudgetError::NotRegistered);
+        }
+        if amount <= 0 {
+            return Err(BudgetError::InvalidAmount);
+        }
+        decrease_for(&env, &consumer, amount)
+    }
+
+    // Get every registered consumer contract
+    pub fn get_consumers(env: Env) -> Vec<Address> {
+        read_consumers(&env)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{contract, contractimpl};
+
+    // Bounty contract that pays out of the allocator budget
+    #[contract]
+    pub struct Bounty;
+
+    #[contractimpl]
+    impl Bounty {
+        pub fn pay(env: Env, allocator: Address, amount: i128) -> i128 {
+            let client = GovernanceBudgetAllocatorClient::new(&env, &allocator);
+            client.consume(&env.current_contract_address(), &amount).new
+        }
+    }
+
+    #[test]
+    fn test_consumer_draws_down_budget() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+        let bounty_id = env.register_contract(None, Bounty);
+        let bounty = BountyClient::new(&env, &bounty_id);
+
+        let owner = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.register_consumer(&owner, &bounty_id);
+
+        // The bounty contract authorizes as the direct caller, no signatures involved
+        env.set_auths(&[]);
+        assert_eq!(bounty.pay(&contract_id, &300), 700);
+        assert_eq!(client.get_operator_stats(&bounty_id).total_decreased, 300);
+    }
+
+    #[test]
+    fn test_unregistered_consumer_rejected() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+        let bounty_id = env.register_contract(None, Bounty);
+
+        let owner = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        assert_eq!(client.try_consume(&bounty_id, &100), Err(Ok(BudgetError::NotRegistered)));
+        client.register_consumer(&owner, &bounty_id);
+        client.remove_consumer(&owner, &bounty_id);
+        assert_eq!(client.try_consume(&bounty_id, &100), Err(Ok(BudgetError::NotRegistered)));
+    }
+}
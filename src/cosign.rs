@@ -0,0 +1,189 @@
+// Co-signed decreases.
+// With a co-signing window set, no single operator can decrease the budget. Every decrease (a
+// payment included) fails with ConfirmationRequired; instead an operator initiates it with
+// initiate_decrease and a different operator confirms it with confirm_decrease within the window,
+// which applies it for the initiating operator with every check of a normal decrease. A deputy
+// counts as the operator it stands in for, on either side, so it cannot confirm its operator's
+// decrease or have its operator confirm its own. A pending decrease that is not confirmed in time
+// expires. Decreases executed through an approval band request (see bands.rs) already needed more
+// than one approver and are not co-signed again.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env};
+
+use crate::{
+    acting_for, check_operator, decrease_approved, read_owner, require_operator, require_owner, rotation, BudgetError,
+    GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient, Receipt,
+};
+#[cfg(feature = "bands")]
+use crate::bands;
+#[cfg(feature = "category")]
+use crate::category;
+#[cfg(feature = "tiers")]
+use crate::tiers;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PendingDecrease {
+    pub caller: Address,
+    pub amount: i128,
+    pub expiry: u32,
+}
+// PendingDecrease stores an initiated decrease:
+// caller is the operator who initiated it (through its deputy or itself) and is credited with it when it is confirmed
+// expiry is the last ledger it can be confirmed in
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum CosignKey {
+    CosignWindow,
+    PendingDecreaseCount,
+    PendingDecrease(u64),
+}
+// CosignWindow stores how many ledgers an initiated decrease can be confirmed for (missing or 0 means off)
+// PendingDecreaseCount stores the number of decreases ever initiated (the next id)
+// PendingDecrease stores a PendingDecrease by id until it is confirmed or cancelled
+
+
+fn window(env: &Env) -> u32 {
+    env.storage().persistent().get(&CosignKey::CosignWindow).unwrap_or(0)
+}
+
+fn read_pending(env: &Env, id: u64) -> Result<PendingDecrease, BudgetError> {
+    env.storage().persistent().get(&CosignKey::PendingDecrease(id)).ok_or(BudgetError::AdjustmentNotFound)
+}
+
+// Rejects decreases by a single operator while co-signing is on.
+pub(crate) fn check(env: &Env) -> Result<(), BudgetError> {
+    if window(env) > 0 {
+        return Err(BudgetError::ConfirmationRequired);
+    }
+    Ok(())
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Require every decrease to be confirmed by a second operator within ledgers, 0 to turn it off (owner only)
+    pub fn set_cosign_window(env: Env, caller: Address, ledgers: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().set(&CosignKey::CosignWindow, &ledgers);
+        Ok(())
+    }
+
+    /// Initiate a decrease for another operator to confirm (operators only), returns its id
+    pub fn initiate_decrease(env: Env, caller: Address, amount: i128) -> Result<u64, BudgetError> {
+        require_operator(&env, &caller)?;
+        if amount <= 0 {
+            return Err(BudgetError::InvalidAmount);
+        }
+        let window = window(&env);
+        if window == 0 {
+            return Err(BudgetError::NoOp);
+        }
+        let id: u64 = env.storage().persistent().get(&CosignKey::PendingDecreaseCount).unwrap_or(0);
+        env.storage().persistent().set(&CosignKey::PendingDecreaseCount, &(id + 1));
+        let expiry = env.ledger().sequence().saturating_add(window);
+        let caller = acting_for(&env, &caller);
+        env.storage().persistent().set(&CosignKey::PendingDecrease(id), &PendingDecrease { caller, amount, expiry });
+        Ok(id)
+    }
+
+    /// Confirm and apply a decrease initiated by another operator (operators only)
+    pub fn confirm_decrease(env: Env, caller: Address, id: u64) -> Result<Receipt, BudgetError> {
+        require_operator(&env, &caller)?;
+        let pending = read_pending(&env, id)?;
+        if rotation::is_same(&env, &pending.caller, &acting_for(&env, &caller)) {
+            return Err(BudgetError::AlreadyVoted);
+        }
+        if env.ledger().sequence() > pending.expiry {
+            return Err(BudgetError::ActionExpired);
+        }
+        env.storage().persistent().remove(&CosignKey::PendingDecrease(id));
+
+        let initiator = rotation::current(&env, &pending.caller);
+        check_operator(&env, &initiator)?;
+        #[cfg(feature = "category")]
+        category::check_scope(&env, &initiator, None)?;
+        #[cfg(feature = "tiers")]
+        tiers::check_direct(&env, &initiator)?;
+        #[cfg(feature = "bands")]
+        bands::check(&env, pending.amount)?;
+        decrease_approved(&env, &initiator, pending.amount)
+    }
+
+    /// Drop an initiated decrease (the operator who initiated it or the owner)
+    pub fn cancel_decrease(env: Env, caller: Address, id: u64) -> Result<(), BudgetError> {
+        caller.require_auth();
+        let pending = read_pending(&env, id)?;
+        if !rotation::is_same(&env, &pending.caller, &caller) && read_owner(&env) != caller {
+            return Err(BudgetError::NotOwner);
+        }
+        env.storage().persistent().remove(&CosignKey::PendingDecrease(id));
+        Ok(())
+    }
+
+    // Get the co-signing window in ledgers (0 when off)
+    pub fn get_cosign_window(env: Env) -> u32 {
+        window(&env)
+    }
+
+    // Get an initiated decrease awaiting confirmation
+    pub fn get_pending_decrease(env: Env, id: u64) -> Option<PendingDecrease> {
+        env.storage().persistent().get(&CosignKey::PendingDecrease(id))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    #[test]
+    fn test_cosigned_decrease() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let alice = Address::generate(&env);
+        let bob = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        for operator in [&alice, &bob] {
+            client.add_operator(&owner, operator);
+            client.accept_operator(operator);
+        }
+        client.set_cosign_window(&owner, &10);
+
+        // Increases are unaffected, decreases need a second operator
+        client.increase_budget(&alice, &100);
+        assert_eq!(client.try_decrease_budget(&alice, &100), Err(Ok(BudgetError::ConfirmationRequired)));
+        let id = client.initiate_decrease(&alice, &300);
+        assert_eq!(client.try_confirm_decrease(&alice, &id), Err(Ok(BudgetError::AlreadyVoted)));
+        let receipt = client.confirm_decrease(&bob, &id);
+        assert_eq!((receipt.new, receipt.caller), (800, alice.clone()));
+        assert_eq!(client.try_confirm_decrease(&bob, &id), Err(Ok(BudgetError::AdjustmentNotFound)));
+
+        // A deputy is the same person as its operator on either side
+        let deputy = Address::generate(&env);
+        client.delegate_operator(&alice, &deputy, &100);
+        let id = client.initiate_decrease(&alice, &100);
+        assert_eq!(client.try_confirm_decrease(&deputy, &id), Err(Ok(BudgetError::AlreadyVoted)));
+        let by_deputy = client.initiate_decrease(&deputy, &100);
+        assert_eq!(client.get_pending_decrease(&by_deputy).unwrap().caller, alice);
+        assert_eq!(client.try_confirm_decrease(&alice, &by_deputy), Err(Ok(BudgetError::AlreadyVoted)));
+        client.cancel_decrease(&alice, &id);
+        client.cancel_decrease(&alice, &by_deputy);
+
+        // Unconfirmed decreases expire
+        let id = client.initiate_decrease(&bob, &100);
+        env.ledger().with_mut(|l| l.sequence_number = 11);
+        assert_eq!(client.try_confirm_decrease(&alice, &id), Err(Ok(BudgetError::ActionExpired)));
+        assert_eq!(client.get_budget().current, 800);
+    }
+}
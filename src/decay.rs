@@ -0,0 +1,142 @@
+// Budget decay ("use it or lose it").
+// The owner sets an amount by which current shrinks every epoch. Like scheduled limits the decay
+// is applied lazily, the first time the budget is read in a later epoch, for every epoch that has
+// passed since. Decay never takes current below min (or below zero outside deficit mode).
+// Decay needs epochs to be enabled.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env};
+
+use crate::{
+    current_epoch, epoch_at, floor, require_owner, stored_budget, write_budget, BudgetError, BudgetState,
+    GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient,
+};
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct DecayPolicy {
+    pub amount: i128,
+    pub last_epoch: u32,
+}
+// DecayPolicy defines the decay:
+// amount is how much current shrinks per epoch
+// last_epoch is the epoch decay was last applied for
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DecayKey {
+    Decay,
+}
+// Decay stores the DecayPolicy (missing means no decay)
+
+
+fn read_policy(env: &Env) -> Option<DecayPolicy> {
+    env.storage().persistent().get(&DecayKey::Decay)
+}
+
+// Current after the decay of the epochs from the policy's last one up to epoch.
+fn decayed(env: &Env, policy: &DecayPolicy, epoch: u32, budget: &BudgetState) -> i128 {
+    let floor = floor(env, budget);
+    if budget.current <= floor {
+        return budget.current;
+    }
+    let elapsed = (epoch - policy.last_epoch) as i128;
+    budget.current.saturating_sub(policy.amount.saturating_mul(elapsed)).max(floor)
+}
+
+// Applies the decay of every epoch that started since it was last applied.
+pub(crate) fn apply_due(env: &Env) {
+    let mut policy = match read_policy(env) {
+        Some(policy) => policy,
+        None => return,
+    };
+    let epoch = match current_epoch(env) {
+        Some(epoch) if epoch > policy.last_epoch => epoch,
+        _ => return,
+    };
+
+    let mut budget = stored_budget(env);
+    let current = decayed(env, &policy, epoch, &budget);
+    policy.last_epoch = epoch;
+    env.storage().persistent().set(&DecayKey::Decay, &policy);
+    if current != budget.current {
+        budget.current = current;
+        write_budget(env, &budget);
+    }
+}
+
+// Applies the decay due by ledger to budget, without storing anything.
+pub(crate) fn project(env: &Env, budget: &mut BudgetState, ledger: u32) {
+    if let (Some(policy), Some(epoch)) = (read_policy(env), epoch_at(env, ledger)) {
+        if epoch > policy.last_epoch {
+            budget.current = decayed(env, &policy, epoch, budget);
+        }
+    }
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Shrink current by amount every epoch from now on (owner only, needs epochs)
+    pub fn set_decay(env: Env, caller: Address, amount: i128) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        let epoch = current_epoch(&env).ok_or(BudgetError::InvalidConfig)?;
+        if amount <= 0 {
+            return Err(BudgetError::InvalidAmount);
+        }
+        // Bring the budget up to date so an earlier policy is applied up to now
+        apply_due(&env);
+        let policy = DecayPolicy { amount, last_epoch: epoch };
+        env.storage().persistent().set(&DecayKey::Decay, &policy);
+        Ok(())
+    }
+
+    /// Turn decay off (owner only)
+    pub fn clear_decay(env: Env, caller: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        apply_due(&env);
+        env.storage().persistent().remove(&DecayKey::Decay);
+        Ok(())
+    }
+
+    // Get the decay policy, if any
+    pub fn get_decay(env: Env) -> Option<DecayPolicy> {
+        read_policy(&env)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    #[test]
+    fn test_decay_per_epoch() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        assert_eq!(client.try_set_decay(&owner, &50), Err(Ok(BudgetError::InvalidConfig)));
+        client.set_epoch_length(&owner, &100);
+        client.set_decay(&owner, &300);
+
+        // Nothing happens within the same epoch
+        env.ledger().with_mut(|l| l.sequence_number = 99);
+        assert_eq!(client.get_budget().current, 1000);
+
+        // Two epochs passed
+        env.ledger().with_mut(|l| l.sequence_number = 250);
+        assert_eq!(client.get_budget().current, 400);
+
+        // Decay stops at the floor
+        env.ledger().with_mut(|l| l.sequence_number = 450);
+        assert_eq!(client.get_budget().current, 0);
+    }
+}
@@ -0,0 +1,96 @@
+// Deficit mode.
+// By default current never goes below zero, even when min is negative. With deficit mode on,
+// decreases may take current below zero, down to a negative min, so a treasury can run a
+// temporary deficit without faking it with offset values.
+// Every write that moves current across zero emits an event: "deficit" when it drops below zero
+// and "recovered" when it gets back to zero or above, both with the new current value.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env};
+
+use crate::{
+    events, require_owner, BudgetError, BudgetState, DeficitEvent, GovernanceBudgetAllocator,
+    GovernanceBudgetAllocatorClient,
+};
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DeficitKey {
+    DeficitMode,
+}
+// DeficitMode stores whether current may go below zero
+
+
+pub(crate) fn enabled(env: &Env) -> bool {
+    env.storage().persistent().get(&DeficitKey::DeficitMode).unwrap_or(false)
+}
+
+// The lowest value a decrease may take current to.
+pub(crate) fn floor(env: &Env, budget: &BudgetState) -> i128 {
+    if enabled(env) {
+        budget.min
+    } else {
+        budget.min.max(0)
+    }
+}
+
+// Called for every budget write with the previous and the new current value.
+pub(crate) fn on_change(env: &Env, previous: i128, current: i128) {
+    if previous >= 0 && current < 0 {
+        events::publish_global(env, events::DEFICIT, DeficitEvent { current });
+    } else if previous < 0 && current >= 0 {
+        events::publish_global(env, events::RECOVERED, DeficitEvent { current });
+    }
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Allow or forbid current going below zero (owner only)
+    pub fn set_deficit_mode(env: Env, caller: Address, enabled: bool) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().set(&DeficitKey::DeficitMode, &enabled);
+        Ok(())
+    }
+
+    // Check if deficit mode is on
+    pub fn is_deficit_mode(env: Env) -> bool {
+        enabled(&env)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Events};
+
+    #[test]
+    fn test_deficit_mode() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &500, &-1000, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+
+        // A negative min alone does not allow a deficit
+        assert_eq!(client.try_decrease_budget(&operator, &600), Err(Ok(BudgetError::BelowMin)));
+
+        client.set_deficit_mode(&owner, &true);
+        assert_eq!(client.decrease_budget(&operator, &800).new, -300);
+        assert_eq!(env.events().all().len(), 1);
+        assert_eq!(client.try_decrease_budget(&operator, &800), Err(Ok(BudgetError::BelowMin)));
+
+        // Getting back to zero emits the recovery event
+        client.increase_budget(&operator, &300);
+        assert_eq!(env.events().all().len(), 2);
+        assert_eq!(client.get_budget().current, 0);
+    }
+}
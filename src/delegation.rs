@@ -0,0 +1,303 @@
+// Delegated voting.
+// Operators and governance token holders can offer their voting weight to another address with
+// delegate(to), which takes effect once that address accepts it with accept_delegation(from), and
+// take it back with undelegate(). Delegations are recorded with the ledger they were made in, and
+// tallies use the delegations in place when the proposal was created: the delegate votes with its
+// own weight plus that of everyone delegating to it at that ledger, and a delegator's own vote
+// carries no weight. A delegation that has ended since (undelegated or moved to another delegate)
+// no longer counts for the old delegate, and the weight falls back to the delegator instead. Every
+// weight counted towards a vote is marked as used on that proposal, so a delegator whose weight
+// its delegate already cast cannot cast it again after undelegating, and the other way round.
+// Delegation is one level deep; weight delegated to an address is not passed on if that address
+// delegates in turn.
+// The same delegations apply to operator voting (operator weights) and token voting (balances).
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env, Vec};
+
+use crate::{BudgetError, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient};
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DelegationKey {
+    Delegation(Address),
+    Delegators(Address),
+    DelegationOffer(Address),
+    OperatorWeightUsed(u64, Address),
+    TokenWeightUsed(u64, Address),
+}
+// Delegation stores the (ledger, delegate) history of an address, None meaning not delegated
+// Delegators stores the addresses currently delegating to an address
+// DelegationOffer stores the delegate an address offered its weight to, until that delegate accepts
+// OperatorWeightUsed marks that an address's operator weight was counted in a vote on a proposal
+// TokenWeightUsed marks that an address's token balance was counted in a token vote on a proposal
+
+
+// Every tally walks the delegators of the voter, so the list stays bounded.
+const MAX_DELEGATORS: u32 = 50;
+
+fn history(env: &Env, who: &Address) -> Vec<(u32, Option<Address>)> {
+    env.storage().persistent().get(&DelegationKey::Delegation(who.clone())).unwrap_or(Vec::new(env))
+}
+
+fn delegators(env: &Env, to: &Address) -> Vec<Address> {
+    env.storage().persistent().get(&DelegationKey::Delegators(to.clone())).unwrap_or(Vec::new(env))
+}
+
+fn record(env: &Env, who: &Address, to: Option<Address>) {
+    let mut entries = history(env, who);
+    let now = env.ledger().sequence();
+    // Only the last change within a ledger counts
+    if let Some((ledger, _)) = entries.last() {
+        if ledger == now {
+            entries.pop_back();
+        }
+    }
+    entries.push_back((now, to));
+    env.storage().persistent().set(&DelegationKey::Delegation(who.clone()), &entries);
+}
+
+// Drops who from the delegators of its current delegate, if it has one.
+fn leave_delegate(env: &Env, who: &Address) {
+    if let Some(delegate) = delegate_at(env, who, env.ledger().sequence()) {
+        let mut list = delegators(env, &delegate);
+        if let Some(index) = list.first_index_of(who) {
+            list.remove(index);
+            env.storage().persistent().set(&DelegationKey::Delegators(delegate), &list);
+        }
+    }
+}
+
+// The delegate of an address as of the given ledger.
+pub(crate) fn delegate_at(env: &Env, who: &Address, ledger: u32) -> Option<Address> {
+    let mut delegate = None;
+    for (at, to) in history(env, who).iter() {
+        if at > ledger {
+            break;
+        }
+        delegate = to;
+    }
+    delegate
+}
+
+// The weight a voter casts on proposal id, created at the given ledger: its own weight unless
+// delegated away to a delegate that still holds it, plus the weight of every address still
+// delegating to it since. Weights already used on the proposal (under the used key) are skipped,
+// and the ones counted are marked as used.
+pub(crate) fn cast_weight(
+    env: &Env,
+    used: fn(u64, Address) -> DelegationKey,
+    id: u64,
+    voter: &Address,
+    ledger: u32,
+    own_weight: impl Fn(&Address) -> i128,
+) -> i128 {
+    let unused_weight = |address: &Address| {
+        let key = used(id, address.clone());
+        if env.storage().persistent().has(&key) {
+            return 0;
+        }
+        env.storage().persistent().set(&key, &true);
+        own_weight(address)
+    };
+
+    let mut weight = 0i128;
+    let held_by_delegate = delegate_at(env, voter, ledger).is_some_and(|delegate| delegators(env, &delegate).contains(voter));
+    if !held_by_delegate {
+        weight = unused_weight(voter);
+    }
+    for delegator in delegators(env, voter).iter() {
+        if delegate_at(env, &delegator, ledger).as_ref() == Some(voter) {
+            weight = weight.saturating_add(unused_weight(&delegator));
+        }
+    }
+    weight
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Offer the caller's voting weight to another address, effective once it accepts (anyone)
+    pub fn delegate(env: Env, caller: Address, to: Address) -> Result<(), BudgetError> {
+        caller.require_auth();
+        if caller == to {
+            return Err(BudgetError::InvalidConfig);
+        }
+        env.storage().persistent().set(&DelegationKey::DelegationOffer(caller), &to);
+        Ok(())
+    }
+
+    /// Accept the voting weight offered by from, replacing any earlier delegation of from (the delegate only)
+    pub fn accept_delegation(env: Env, caller: Address, from: Address) -> Result<(), BudgetError> {
+        caller.require_auth();
+        let offer = DelegationKey::DelegationOffer(from.clone());
+        if env.storage().persistent().get::<_, Address>(&offer).as_ref() != Some(&caller) {
+            return Err(BudgetError::NoPendingAction);
+        }
+        let mut list = delegators(&env, &caller);
+        if !list.contains(&from) {
+            if list.len() >= MAX_DELEGATORS {
+                return Err(BudgetError::InvalidConfig);
+            }
+            leave_delegate(&env, &from);
+            list.push_back(from.clone());
+            env.storage().persistent().set(&DelegationKey::Delegators(caller.clone()), &list);
+        }
+        env.storage().persistent().remove(&offer);
+        record(&env, &from, Some(caller));
+        Ok(())
+    }
+
+    /// Take the caller's voting weight back, or withdraw an offer not accepted yet (anyone)
+    pub fn undelegate(env: Env, caller: Address) -> Result<(), BudgetError> {
+        caller.require_auth();
+        let offer = DelegationKey::DelegationOffer(caller.clone());
+        let offered = env.storage().persistent().has(&offer);
+        env.storage().persistent().remove(&offer);
+        if delegate_at(&env, &caller, env.ledger().sequence()).is_none() {
+            return if offered { Ok(()) } else { Err(BudgetError::NotRegistered) };
+        }
+        leave_delegate(&env, &caller);
+        record(&env, &caller, None);
+        Ok(())
+    }
+
+    // Get the current delegate of an address, if any
+    pub fn get_delegate(env: Env, address: Address) -> Option<Address> {
+        delegate_at(&env, &address, env.ledger().sequence())
+    }
+
+    // Get the addresses currently delegating to an address
+    pub fn get_delegators(env: Env, address: Address) -> Vec<Address> {
+        delegators(&env, &address)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::{symbol_short, vec, BytesN, String};
+
+    fn metadata(env: &Env) -> ProposalMetadata {
+        ProposalMetadata {
+            title: symbol_short!("limits"),
+            memo: String::from_str(env, "raise the limits"),
+            recipient: None,
+            content_hash: BytesN::from_array(env, &[1; 32]),
+        }
+    }
+
+    #[test]
+    fn test_delegated_operator_votes() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let lead = Address::generate(&env);
+        let member = Address::generate(&env);
+        let other = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &lead);
+        client.accept_operator(&lead);
+        client.add_operator(&owner, &member);
+        client.accept_operator(&member);
+        client.add_operator(&owner, &other);
+        client.accept_operator(&other);
+        client.set_quorum(&owner, &3);
+
+        client.delegate(&member, &lead);
+        client.delegate(&other, &lead);
+        let id = client.propose_limits(&lead, &0, &20000, &metadata(&env));
+        assert_eq!(client.try_accept_delegation(&other, &member), Err(Ok(BudgetError::NoPendingAction)));
+        env.ledger().with_mut(|l| l.sequence_number = 1);
+        client.accept_delegation(&lead, &member);
+        client.accept_delegation(&lead, &other);
+
+        // Delegations made after the proposal was created do not count for it
+        assert_eq!(client.vote(&lead, &id, &true), Tally { for_weight: 1, against_weight: 0 });
+
+        env.ledger().with_mut(|l| l.sequence_number = 5);
+        let id = client.propose_limits(&lead, &0, &20000, &metadata(&env));
+        assert_eq!(client.get_delegators(&lead), vec![&env, member.clone(), other.clone()]);
+
+        // A delegation ended since goes back to the delegator
+        env.ledger().with_mut(|l| l.sequence_number = 6);
+        client.undelegate(&other);
+        assert_eq!(client.get_delegate(&other), None);
+        assert_eq!(client.get_delegators(&lead), vec![&env, member.clone()]);
+
+        assert_eq!(client.vote(&member, &id, &false), Tally::default());
+        assert_eq!(client.vote(&lead, &id, &true), Tally { for_weight: 2, against_weight: 0 });
+        assert_eq!(client.vote(&other, &id, &true), Tally { for_weight: 3, against_weight: 0 });
+        client.execute_proposal(&id);
+        assert_eq!(client.get_budget().max, 20000);
+    }
+
+    #[test]
+    fn test_delegated_weight_counts_once() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let lead = Address::generate(&env);
+        let member = Address::generate(&env);
+        let other = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        for operator in [&lead, &member, &other] {
+            client.add_operator(&owner, operator);
+            client.accept_operator(operator);
+        }
+        client.set_quorum(&owner, &3);
+        client.delegate(&member, &lead);
+        client.accept_delegation(&lead, &member);
+        env.ledger().with_mut(|l| l.sequence_number = 1);
+        let id = client.propose_limits(&lead, &0, &20000, &metadata(&env));
+
+        // The member's weight was cast by the lead, undelegating does not let it vote again
+        assert_eq!(client.vote(&lead, &id, &true), Tally { for_weight: 2, against_weight: 0 });
+        client.undelegate(&member);
+        assert_eq!(client.vote(&member, &id, &true), Tally { for_weight: 2, against_weight: 0 });
+        assert_eq!(client.try_execute_proposal(&id), Err(Ok(BudgetError::QuorumNotReached)));
+        assert_eq!(client.vote(&other, &id, &true), Tally { for_weight: 3, against_weight: 0 });
+    }
+
+    #[test]
+    fn test_delegation_needs_acceptance() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let lead = Address::generate(&env);
+        let deputy = Address::generate(&env);
+        let member = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+        env.mock_all_auths();
+
+        // Unaccepted offers take no room in the delegate's list
+        for _ in 0..60 {
+            client.delegate(&Address::generate(&env), &lead);
+        }
+        assert_eq!(client.get_delegators(&lead).len(), 0);
+
+        // Moving a delegation takes it off the old delegate's list
+        client.delegate(&member, &lead);
+        client.accept_delegation(&lead, &member);
+        client.delegate(&member, &deputy);
+        client.accept_delegation(&deputy, &member);
+        assert_eq!(client.get_delegators(&lead).len(), 0);
+        assert_eq!(client.get_delegators(&deputy), vec![&env, member.clone()]);
+        assert_eq!(client.get_delegate(&member), Some(deputy));
+    }
+}
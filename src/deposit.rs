@@ -0,0 +1,165 @@
+// Proposal deposits.
+// To keep proposal creation from being flooded, the owner can require every new proposal to lock
+// a deposit in a configured token. The deposit is refunded to the proposer when the proposal is
+// executed, cancelled, rejected or expires (reclaim_deposit), and forfeited to this contract when
+// the owner rejects it as spam with reject_as_spam.
+
+use soroban_sdk::{contractimpl, contracttype, token, Address, Env};
+
+use crate::{
+    proposal, require_owner, rotation, treasury, BudgetError, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient,
+    Proposal, ProposalStatus,
+};
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Deposit {
+    pub token: Address,
+    pub amount: i128,
+}
+// Deposit is a token amount: the required deposit in the configuration, or what a proposal locked.
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DepositKey {
+    ProposalDeposit,
+    Deposit(u64),
+}
+// ProposalDeposit stores the Deposit required for new proposals (missing means none)
+// Deposit stores the Deposit a proposal locked, until it is refunded or forfeited
+
+
+// Called when a proposal is created; takes the required deposit from the proposer.
+pub(crate) fn lock(env: &Env, proposer: &Address, id: u64) {
+    let required: Option<Deposit> = env.storage().persistent().get(&DepositKey::ProposalDeposit);
+    if let Some(deposit) = required {
+        token::Client::new(env, &deposit.token).transfer(proposer, &env.current_contract_address(), &deposit.amount);
+        treasury::hold(env, &deposit.token, deposit.amount);
+        env.storage().persistent().set(&DepositKey::Deposit(id), &deposit);
+    }
+}
+
+// Called when a proposal is executed or properly closed; returns the deposit to the proposer.
+pub(crate) fn refund(env: &Env, proposal: &Proposal) {
+    let key = DepositKey::Deposit(proposal.id);
+    let locked: Option<Deposit> = env.storage().persistent().get(&key);
+    if let Some(deposit) = locked {
+        env.storage().persistent().remove(&key);
+        treasury::release(env, &deposit.token, deposit.amount);
+        let proposer = rotation::current(env, &proposal.proposer);
+        token::Client::new(env, &deposit.token).transfer(&env.current_contract_address(), &proposer, &deposit.amount);
+    }
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Require a deposit for new proposals (owner only)
+    pub fn set_proposal_deposit(env: Env, caller: Address, token: Address, amount: i128) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        if amount <= 0 {
+            return Err(BudgetError::InvalidAmount);
+        }
+        env.storage().persistent().set(&DepositKey::ProposalDeposit, &Deposit { token, amount });
+        Ok(())
+    }
+
+    /// Stop requiring deposits for new proposals (owner only)
+    pub fn clear_proposal_deposit(env: Env, caller: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().remove(&DepositKey::ProposalDeposit);
+        Ok(())
+    }
+
+    /// Reject an open proposal as spam, keeping its deposit (owner only)
+    pub fn reject_as_spam(env: Env, caller: Address, id: u64) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        // Drop the deposit record first so closing does not refund it; the forfeited tokens become sweepable
+        let key = DepositKey::Deposit(id);
+        if let Some(deposit) = env.storage().persistent().get::<_, Deposit>(&key) {
+            env.storage().persistent().remove(&key);
+            treasury::release(&env, &deposit.token, deposit.amount);
+        }
+        proposal::close(&env, id, ProposalStatus::Rejected)
+    }
+
+    /// Refund the deposit of an expired proposal to its proposer (anyone)
+    pub fn reclaim_deposit(env: Env, id: u64) -> Result<(), BudgetError> {
+        let proposal = Self::get_proposal(env.clone(), id).ok_or(BudgetError::ProposalNotFound)?;
+        if proposal.status != ProposalStatus::Expired {
+            return Err(BudgetError::ProposalClosed);
+        }
+        refund(&env, &proposal);
+        Ok(())
+    }
+
+    // Get the deposit required for new proposals, if any
+    pub fn get_proposal_deposit(env: Env) -> Option<Deposit> {
+        env.storage().persistent().get(&DepositKey::ProposalDeposit)
+    }
+
+    // Get the deposit a proposal still holds, if any
+    pub fn get_locked_deposit(env: Env, id: u64) -> Option<Deposit> {
+        env.storage().persistent().get(&DepositKey::Deposit(id))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::{symbol_short, token, BytesN, String};
+
+    fn metadata(env: &Env) -> ProposalMetadata {
+        ProposalMetadata {
+            title: symbol_short!("limits"),
+            memo: String::from_str(env, "raise the limits"),
+            recipient: None,
+            content_hash: BytesN::from_array(env, &[1; 32]),
+        }
+    }
+
+    #[test]
+    fn test_deposit_refund_and_forfeit() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let sac = env.register_stellar_asset_contract_v2(owner.clone());
+        let deposit_token = token::Client::new(&env, &sac.address());
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        token::StellarAssetClient::new(&env, &sac.address()).mint(&operator, &300);
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_proposal_deposit(&owner, &sac.address(), &100);
+        client.set_proposal_ttl(&owner, &50);
+
+        // Ratified: refunded
+        let ratified = client.propose_limits(&operator, &0, &20000, &metadata(&env));
+        assert_eq!(deposit_token.balance(&operator), 200);
+        client.ratify_proposal(&owner, &ratified);
+        assert_eq!(deposit_token.balance(&operator), 300);
+
+        // Spam: forfeited to the contract
+        let spam = client.propose_limits(&operator, &0, &20000, &metadata(&env));
+        client.reject_as_spam(&owner, &spam);
+        assert_eq!(deposit_token.balance(&operator), 200);
+        assert_eq!(deposit_token.balance(&contract_id), 100);
+
+        // Expired: reclaimable once expired
+        let stale = client.propose_limits(&operator, &0, &20000, &metadata(&env));
+        assert_eq!(client.try_reclaim_deposit(&stale), Err(Ok(BudgetError::ProposalClosed)));
+        env.ledger().with_mut(|l| l.sequence_number = 51);
+        client.reclaim_deposit(&stale);
+        assert_eq!(deposit_token.balance(&operator), 200);
+        assert_eq!(client.get_locked_deposit(&stale), None);
+    }
+}
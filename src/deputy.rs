@@ -0,0 +1,158 @@
+// Temporary operator delegation.
+// An operator can name a deputy that acts with the operator's rights until a given ledger, e.g.
+// during a vacation or a key rotation window, and can revoke it early. While active, the deputy
+// passes every operator check on the operator's behalf; its adjustments name the deputy as caller
+// and the operator in on_behalf_of, so history attributes them to both. Each operator has at most
+// one deputy and each deputy serves one operator. Removing the operator ends the deputy's rights.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env};
+
+use crate::{is_listed, require_operator, BudgetError, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient};
+#[cfg(feature = "blacklist")]
+use crate::blacklist;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Deputy {
+    pub operator: Address,
+    pub deputy: Address,
+    pub until: u32,
+}
+// Deputy is one delegation: deputy acts for operator up to and including ledger until
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DeputyKey {
+    DeputyFor(Address),
+    DeputyOf(Address),
+}
+// DeputyFor stores the Deputy an operator named
+// DeputyOf stores the Deputy a deputy address holds
+
+
+// The operator an address currently stands in for, if it is an active deputy of a current operator.
+pub(crate) fn principal_of(env: &Env, address: &Address) -> Option<Address> {
+    let deputy: Deputy = env.storage().persistent().get(&DeputyKey::DeputyOf(address.clone()))?;
+    if env.ledger().sequence() > deputy.until {
+        return None;
+    }
+    #[cfg(feature = "blacklist")]
+    if blacklist::check(env, address).is_err() {
+        return None;
+    }
+    is_listed(env, &deputy.operator).then_some(deputy.operator)
+}
+
+// The operator whose rights an address uses: itself if it is an operator, else its principal.
+pub(crate) fn acting_for(env: &Env, address: &Address) -> Address {
+    match is_listed(env, address) {
+        true => address.clone(),
+        false => principal_of(env, address).unwrap_or(address.clone()),
+    }
+}
+
+fn clear(env: &Env, operator: &Address) -> Result<(), BudgetError> {
+    let deputy: Deputy = env.storage().persistent().get(&DeputyKey::DeputyFor(operator.clone())).ok_or(BudgetError::NotRegistered)?;
+    env.storage().persistent().remove(&DeputyKey::DeputyFor(operator.clone()));
+    env.storage().persistent().remove(&DeputyKey::DeputyOf(deputy.deputy));
+    Ok(())
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Let another address act with the caller's operator rights until a ledger (operators only)
+    pub fn delegate_operator(env: Env, caller: Address, to: Address, until: u32) -> Result<(), BudgetError> {
+        require_operator(&env, &caller)?;
+        #[cfg(feature = "blacklist")]
+        blacklist::check(&env, &to)?;
+        if until < env.ledger().sequence() || to == caller {
+            return Err(BudgetError::InvalidConfig);
+        }
+        if is_listed(&env, &to) {
+            return Err(BudgetError::AlreadyOperator);
+        }
+        if principal_of(&env, &to).is_some() {
+            return Err(BudgetError::AlreadyRegistered);
+        }
+
+        // A new delegation replaces the previous one
+        let _ = clear(&env, &caller);
+        let deputy = Deputy { operator: caller.clone(), deputy: to.clone(), until };
+        env.storage().persistent().set(&DeputyKey::DeputyFor(caller), &deputy);
+        env.storage().persistent().set(&DeputyKey::DeputyOf(to), &deputy);
+        Ok(())
+    }
+
+    /// End the caller's delegation before it expires (operators only)
+    pub fn revoke_operator_delegation(env: Env, caller: Address) -> Result<(), BudgetError> {
+        require_operator(&env, &caller)?;
+        clear(&env, &caller)
+    }
+
+    // Get the deputy an operator named, if any
+    pub fn get_deputy(env: Env, operator: Address) -> Option<Deputy> {
+        env.storage().persistent().get(&DeputyKey::DeputyFor(operator))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    #[test]
+    fn test_deputy_acts_until_expiry() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let deputy = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        assert_eq!(client.try_increase_budget(&deputy, &100), Err(Ok(BudgetError::NotOperator)));
+
+        client.delegate_operator(&operator, &deputy, &100);
+        let receipt = client.increase_budget(&deputy, &100);
+        assert_eq!(receipt.caller, deputy);
+        assert_eq!(receipt.on_behalf_of, Some(operator.clone()));
+        assert_eq!(client.get_history_entry(&receipt.id), Some(receipt));
+
+        env.ledger().with_mut(|l| l.sequence_number = 101);
+        assert_eq!(client.try_increase_budget(&deputy, &100), Err(Ok(BudgetError::NotOperator)));
+    }
+
+    #[test]
+    fn test_revoke_deputy() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let deputy = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.blacklist(&owner, &deputy);
+        assert_eq!(client.try_delegate_operator(&operator, &deputy, &100), Err(Ok(BudgetError::Blacklisted)));
+        client.unblacklist(&owner, &deputy);
+
+        client.delegate_operator(&operator, &deputy, &100);
+        client.revoke_operator_delegation(&operator);
+        assert_eq!(client.try_decrease_budget(&deputy, &100), Err(Ok(BudgetError::NotOperator)));
+        assert_eq!(client.get_deputy(&operator), None);
+    }
+}
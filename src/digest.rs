@@ -0,0 +1,60 @@
+// State digest.
+// state_digest hashes what defines an instance, so two parties (or two replicas, e.g. before and
+// after export_state / import_state) can compare it with one value instead of reading everything:
+// sha256 over the XDR of (budget with its limits, operators list in its order, history root). Any
+// adjustment, limit change or operator change gives a different digest. Feature settings are not
+// covered.
+
+use soroban_sdk::{contractimpl, xdr::ToXdr, BytesN, Env};
+
+use crate::{BudgetAllocator, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient};
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    // Get a sha256 digest of the budget, limits, operators and history root
+    pub fn state_digest(env: Env) -> BytesN<32> {
+        let state = (
+            Self::get_budget(env.clone()),
+            Self::get_operators(env.clone()),
+            Self::get_history_root(env.clone()),
+        );
+        env.crypto().sha256(&state.to_xdr(&env)).into()
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+
+    #[test]
+    fn test_state_digest() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        // Two instances in the same state share a digest
+        let clients = [0, 1].map(|_| {
+            let client = GovernanceBudgetAllocatorClient::new(&env, &env.register_contract(None, GovernanceBudgetAllocator));
+            client.initialize(&owner, &1000, &0, &10000);
+            client.add_operator(&owner, &operator);
+            client.accept_operator(&operator);
+            client.increase_budget(&operator, &100);
+            client
+        });
+        let digest = clients[0].state_digest();
+        assert_eq!(clients[1].state_digest(), digest);
+
+        // Adjustments, limits and operators all change it
+        clients[1].decrease_budget(&operator, &100);
+        assert_ne!(clients[1].state_digest(), digest);
+        clients[0].set_limits(&owner, &0, &9000);
+        let limited = clients[0].state_digest();
+        assert_ne!(limited, digest);
+        clients[0].remove_operator(&owner, &operator);
+        assert_ne!(clients[0].state_digest(), limited);
+    }
+}
@@ -0,0 +1,117 @@
+// Donations with donor attribution.
+// Anyone can fund a token-backed budget with donate(): the backing tokens are pulled from the
+// donor and the budget is raised by the same amount, as long as that stays within max. Every
+// donation is kept in a donations log with the donor and a memo, so contributions can be credited.
+// The adjustment receipt of a donation names the donor as its caller. Donations can be matched
+// (see matching.rs).
+
+use soroban_sdk::{contractimpl, contracttype, token, Address, Env, String};
+
+use crate::{
+    commit_adjustment, events, exempt_from_veto, read_budget, require_not_paused, treasury, BudgetError, DonationEvent,
+    GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient,
+};
+#[cfg(feature = "matching")]
+use crate::matching;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Donation {
+    pub id: u64,
+    pub donor: Address,
+    pub amount: i128,
+    pub memo: String,
+    pub ledger: u32,
+}
+// Donation is one entry of the donations log; memo is free text supplied by the donor.
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DonationKey {
+    DonationCount,
+    Donation(u64),
+}
+// DonationCount stores the number of donations (the next donation id)
+// Donation stores a Donation by id
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Transfer backing tokens into the contract and raise the budget by the same amount (anyone), returns the donation id
+    pub fn donate(env: Env, from: Address, amount: i128, memo: String) -> Result<u64, BudgetError> {
+        from.require_auth();
+        require_not_paused(&env)?;
+        if amount <= 0 {
+            return Err(BudgetError::InvalidAmount);
+        }
+        let token = treasury::backing_token(&env)?;
+
+        let mut budget = read_budget(&env);
+        let new_value = budget.current.checked_add(amount).ok_or(BudgetError::Overflow)?;
+        if new_value > budget.max {
+            return Err(BudgetError::ExceedsMax);
+        }
+        token::Client::new(&env, &token).transfer(&from, &env.current_contract_address(), &amount);
+        let receipt = commit_adjustment(&env, &from, &mut budget, new_value);
+        exempt_from_veto(&env, &receipt);
+
+        let id: u64 = env.storage().persistent().get(&DonationKey::DonationCount).unwrap_or(0);
+        let donation = Donation { id, donor: from.clone(), amount, memo, ledger: env.ledger().sequence() };
+        env.storage().persistent().set(&DonationKey::Donation(id), &donation);
+        env.storage().persistent().set(&DonationKey::DonationCount, &(id + 1));
+        events::publish(&env, events::DONATE, &from, DonationEvent { donation_id: id, amount });
+        #[cfg(feature = "matching")]
+        matching::on_donation(&env, &from, id, amount);
+        Ok(id)
+    }
+
+    // Get the number of donations received
+    pub fn get_donation_count(env: Env) -> u64 {
+        env.storage().persistent().get(&DonationKey::DonationCount).unwrap_or(0)
+    }
+
+    // Get a donation by id
+    pub fn get_donation(env: Env, id: u64) -> Option<Donation> {
+        env.storage().persistent().get(&DonationKey::Donation(id))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{token, String};
+
+    #[test]
+    fn test_donation_raises_budget() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let donor = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &2000);
+
+        env.mock_all_auths();
+        let sac = env.register_stellar_asset_contract_v2(owner.clone());
+        token::StellarAssetClient::new(&env, &sac.address()).mint(&donor, &2000);
+        client.set_token(&owner, &sac.address());
+
+        let memo = String::from_str(&env, "for the hackathon");
+        assert_eq!(client.donate(&donor, &600, &memo), 0);
+        assert_eq!(client.get_budget().current, 1600);
+        assert_eq!(token::Client::new(&env, &sac.address()).balance(&contract_id), 600);
+
+        let donation = client.get_donation(&0).unwrap();
+        assert_eq!(donation.donor, donor);
+        assert_eq!(donation.memo, memo);
+
+        // Nothing is pulled when the donation would exceed max
+        assert_eq!(client.try_donate(&donor, &600, &memo), Err(Ok(BudgetError::ExceedsMax)));
+        assert_eq!(client.get_donation_count(), 1);
+    }
+}
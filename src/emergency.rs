@@ -0,0 +1,143 @@
+// Emergency fast-track adjustments.
+// Incident response sometimes cannot wait for queues, timelocks or votes. emergency_adjust lets
+// the owner adjust the budget directly, even while paused, but only within the limits and only up
+// to a per-epoch cap configured beforehand (no cap means the path is closed). Every use emits a
+// dedicated "emergency" event carrying the justification hash, so it cannot go unnoticed.
+// Without epochs the cap applies to the whole lifetime of the contract.
+
+use soroban_sdk::{contractimpl, contracttype, Address, BytesN, Env};
+
+use crate::{
+    cap, commit_adjustment, current_epoch, events, floor, read_budget, require_owner, BudgetError, EmergencyEvent,
+    GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient, Receipt,
+};
+
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[contracttype]
+pub enum Direction {
+    Increase,
+    Decrease,
+}
+// Direction tells whether an emergency adjustment adds to or subtracts from current.
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum EmergencyKey {
+    EmergencyCap,
+    EmergencyUsed(u32),
+}
+// EmergencyCap stores the total emergency adjustment allowed per epoch (missing means none)
+// EmergencyUsed stores the emergency adjustment total already used in an epoch
+
+
+fn used(env: &Env, epoch: u32) -> i128 {
+    env.storage().persistent().get(&EmergencyKey::EmergencyUsed(epoch)).unwrap_or(0)
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Set the total emergency adjustment allowed per epoch, 0 closes the path (owner only)
+    pub fn set_emergency_cap(env: Env, caller: Address, cap: i128) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        if cap < 0 {
+            return Err(BudgetError::InvalidConfig);
+        }
+        env.storage().persistent().set(&EmergencyKey::EmergencyCap, &cap);
+        Ok(())
+    }
+
+    /// Adjust the budget immediately, bypassing pause, queues and votes (owner only, capped per epoch)
+    pub fn emergency_adjust(
+        env: Env,
+        caller: Address,
+        amount: i128,
+        direction: Direction,
+        justification_hash: BytesN<32>,
+    ) -> Result<Receipt, BudgetError> {
+        require_owner(&env, &caller)?;
+        if amount <= 0 {
+            return Err(BudgetError::InvalidAmount);
+        }
+
+        let epoch = current_epoch(&env).unwrap_or(0);
+        let total = used(&env, epoch).checked_add(amount).ok_or(BudgetError::Overflow)?;
+        if total > Self::get_emergency_cap(env.clone()) {
+            return Err(BudgetError::EmergencyCapReached);
+        }
+
+        // The limits still apply
+        let mut budget = read_budget(&env);
+        let new_value = match direction {
+            Direction::Increase => budget.current.checked_add(amount).ok_or(BudgetError::Overflow)?,
+            Direction::Decrease => budget.current.checked_sub(amount).ok_or(BudgetError::Underflow)?,
+        };
+        if new_value > cap(&env, &budget) {
+            return Err(BudgetError::ExceedsMax);
+        }
+        if new_value < floor(&env, &budget) {
+            return Err(BudgetError::BelowMin);
+        }
+
+        env.storage().persistent().set(&EmergencyKey::EmergencyUsed(epoch), &total);
+        let receipt = commit_adjustment(&env, &caller, &mut budget, new_value);
+        let event = EmergencyEvent { adjustment_id: receipt.id, direction, amount, justification_hash };
+        events::publish(&env, events::EMERGENCY, &caller, event);
+        Ok(receipt)
+    }
+
+    // Get the emergency adjustment cap per epoch (0 means the path is closed)
+    pub fn get_emergency_cap(env: Env) -> i128 {
+        env.storage().persistent().get(&EmergencyKey::EmergencyCap).unwrap_or(0)
+    }
+
+    // Get how much of the emergency cap is still available in the current epoch
+    pub fn get_emergency_remaining(env: Env) -> i128 {
+        let epoch = current_epoch(&env).unwrap_or(0);
+        Self::get_emergency_cap(env.clone()).saturating_sub(used(&env, epoch)).max(0)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Events, Ledger};
+    use soroban_sdk::BytesN;
+
+    #[test]
+    fn test_emergency_adjust_capped_per_epoch() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let reason = BytesN::from_array(&env, &[3; 32]);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        assert_eq!(
+            client.try_emergency_adjust(&owner, &100, &Direction::Decrease, &reason),
+            Err(Ok(BudgetError::EmergencyCapReached))
+        );
+
+        client.set_epoch_length(&owner, &100);
+        client.set_emergency_cap(&owner, &500);
+        client.pause(&owner);
+
+        // Works while paused and always emits the dedicated event
+        assert_eq!(client.emergency_adjust(&owner, &400, &Direction::Decrease, &reason).new, 600);
+        assert_eq!(env.events().all().len(), 1);
+        assert_eq!(
+            client.try_emergency_adjust(&owner, &200, &Direction::Increase, &reason),
+            Err(Ok(BudgetError::EmergencyCapReached))
+        );
+
+        env.ledger().with_mut(|l| l.sequence_number = 100);
+        assert_eq!(client.get_emergency_remaining(), 500);
+        assert_eq!(client.emergency_adjust(&owner, &200, &Direction::Increase, &reason).new, 800);
+    }
+}
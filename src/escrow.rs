@@ -0,0 +1,246 @@
+// Escrowed payouts.
+// Pushing funds straight to a possibly wrong address cannot be undone. create_payout instead
+// decreases the budget into an escrow that the recipient has to claim (proving it controls the
+// address) before the expiry ledger. In token-backed mode the tokens stay in this contract until
+// the claim transfers them. A payout that is not claimed in time can be reverted by anyone,
+// which adds the amount back to the budget, up to what the budget can take at that point (the
+// vested max); anything above that stays in the contract outside the budget.
+
+use soroban_sdk::{contractimpl, contracttype, token, Address, Env, String};
+
+use crate::{
+    apply_decrease, cap, commit_adjustment, exempt_from_veto, read_budget, treasury, BudgetError,
+    GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient,
+};
+#[cfg(feature = "payee")]
+use crate::payee;
+#[cfg(feature = "policy")]
+use crate::policy;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub enum PayoutStatus {
+    Pending,
+    Claimed,
+    Reverted,
+}
+// Pending payouts wait for the recipient; Claimed and Reverted payouts are closed.
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Payout {
+    pub id: u64,
+    pub caller: Address,
+    pub recipient: Address,
+    pub amount: i128,
+    pub token: Option<Address>,
+    pub expiry: u32,
+    pub status: PayoutStatus,
+}
+// Payout stores one escrowed payout:
+// caller is the operator that created it
+// amount is what the recipient receives, the decrease less its fee
+// token is the backing token held in escrow, None when the budget is not token-backed
+// expiry is the last ledger the recipient can claim in
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum EscrowKey {
+    PayoutCount,
+    Payout(u64),
+}
+// PayoutCount stores the number of payouts (the next payout id)
+// Payout stores a Payout by id
+
+
+fn read_payout(env: &Env, id: u64) -> Result<Payout, BudgetError> {
+    let payout: Payout = env.storage().persistent().get(&EscrowKey::Payout(id)).ok_or(BudgetError::NotRegistered)?;
+    if payout.status != PayoutStatus::Pending {
+        return Err(BudgetError::NoPendingAction);
+    }
+    Ok(payout)
+}
+
+fn write_payout(env: &Env, payout: &Payout) {
+    env.storage().persistent().set(&EscrowKey::Payout(payout.id), payout);
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Decrease the budget into an escrow the recipient claims before expiry (operators only), returns the payout id
+    pub fn create_payout(env: Env, caller: Address, recipient: Address, amount: i128, expiry: u32) -> Result<u64, BudgetError> {
+        caller.require_auth();
+        if amount <= 0 {
+            return Err(BudgetError::InvalidAmount);
+        }
+        if expiry < env.ledger().sequence() {
+            return Err(BudgetError::InvalidConfig);
+        }
+        #[cfg(feature = "payee")]
+        payee::charge(&env, &recipient, amount)?;
+        #[cfg(feature = "policy")]
+        policy::check_recipient(&env, &recipient, amount)?;
+        // The payout holds the amount, a veto would credit it a second time
+        let receipt = apply_decrease(&env, &caller, amount)?;
+        exempt_from_veto(&env, &receipt);
+
+        let id: u64 = env.storage().persistent().get(&EscrowKey::PayoutCount).unwrap_or(0);
+        env.storage().persistent().set(&EscrowKey::PayoutCount, &(id + 1));
+        // The fee is taken when the budget is decreased, only the rest goes into escrow
+        let token = treasury::backing_token(&env).ok();
+        let amount = treasury::net_of_fee(&env, amount, token.as_ref());
+        if let Some(token) = &token {
+            treasury::hold(&env, token, amount);
+        }
+        let payout = Payout { id, caller, recipient, amount, token, expiry, status: PayoutStatus::Pending };
+        write_payout(&env, &payout);
+        Ok(id)
+    }
+
+    /// Claim a pending payout before it expires (recipient only)
+    pub fn claim(env: Env, id: u64) -> Result<(), BudgetError> {
+        let mut payout = read_payout(&env, id)?;
+        payout.recipient.require_auth();
+        if env.ledger().sequence() > payout.expiry {
+            return Err(BudgetError::ActionExpired);
+        }
+        payout.status = PayoutStatus::Claimed;
+        write_payout(&env, &payout);
+
+        if let Some(token) = &payout.token {
+            treasury::release(&env, token, payout.amount);
+            token::Client::new(&env, token).transfer(&env.current_contract_address(), &payout.recipient, &payout.amount);
+            let memo = String::from_str(&env, "payout");
+            treasury::log_payment(&env, &payout.caller, &payout.recipient, payout.amount, memo);
+        }
+        Ok(())
+    }
+
+    /// Add an expired, unclaimed payout back to the budget (anyone)
+    pub fn revert_payout(env: Env, id: u64) -> Result<i128, BudgetError> {
+        let mut payout = read_payout(&env, id)?;
+        if env.ledger().sequence() <= payout.expiry {
+            return Err(BudgetError::TimelockActive);
+        }
+        payout.status = PayoutStatus::Reverted;
+        write_payout(&env, &payout);
+
+        if let Some(token) = &payout.token {
+            treasury::release(&env, token, payout.amount);
+        }
+
+        // Undoes the decrease, so it is credited to the operator that made it. The credit is
+        // clamped to the cap so a budget that filled up in the meantime cannot strand the payout.
+        let mut budget = read_budget(&env);
+        let cap = cap(&env, &budget);
+        let new_value = budget.current.saturating_add(payout.amount).min(cap);
+        if new_value <= budget.current {
+            return Ok(budget.current);
+        }
+        let receipt = commit_adjustment(&env, &payout.caller, &mut budget, new_value);
+        exempt_from_veto(&env, &receipt);
+        Ok(new_value)
+    }
+
+    // Get a payout by id
+    pub fn get_payout(env: Env, id: u64) -> Option<Payout> {
+        env.storage().persistent().get(&EscrowKey::Payout(id))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::token;
+
+    #[test]
+    fn test_backed_payout_claim() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let recipient = Address::generate(&env);
+        let sac = env.register_stellar_asset_contract_v2(owner.clone());
+        let backing = token::Client::new(&env, &sac.address());
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        token::StellarAssetClient::new(&env, &sac.address()).mint(&contract_id, &1000);
+        client.set_token(&owner, &sac.address());
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+
+        let id = client.create_payout(&operator, &recipient, &300, &100);
+        assert_eq!(client.get_budget().current, 700);
+        assert_eq!(backing.balance(&recipient), 0);
+
+        client.claim(&id);
+        assert_eq!(backing.balance(&recipient), 300);
+        assert_eq!(client.get_payout(&id).unwrap().status, PayoutStatus::Claimed);
+        assert_eq!(client.try_claim(&id), Err(Ok(BudgetError::NoPendingAction)));
+    }
+
+    #[test]
+    fn test_unclaimed_payout_reverts() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let recipient = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+
+        let id = client.create_payout(&operator, &recipient, &300, &100);
+        assert_eq!(client.try_revert_payout(&id), Err(Ok(BudgetError::TimelockActive)));
+
+        env.ledger().with_mut(|l| l.sequence_number = 101);
+        assert_eq!(client.try_claim(&id), Err(Ok(BudgetError::ActionExpired)));
+        assert_eq!(client.revert_payout(&id), 1000);
+        assert_eq!(client.get_payout(&id).unwrap().status, PayoutStatus::Reverted);
+    }
+
+    #[test]
+    fn test_backed_payout_revert_clamps() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let recipient = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &1000);
+
+        env.mock_all_auths();
+        let token = mocks::register_token(&env, &owner);
+        token.mint(&contract_id, &1000);
+        client.set_token(&owner, &token.address);
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+
+        let id = client.create_payout(&operator, &recipient, &300, &100);
+        assert_eq!(client.get_held(&token.address), 300);
+
+        // The budget filled back up to max while the payout was pending, only 100 fits
+        client.increase_budget(&operator, &200);
+        env.ledger().with_mut(|l| l.sequence_number = 101);
+        assert_eq!(client.revert_payout(&id), 1000);
+        assert_eq!(client.get_payout(&id).unwrap().status, PayoutStatus::Reverted);
+        assert_eq!(client.get_held(&token.address), 0);
+    }
+}
@@ -0,0 +1,239 @@
+// Event schema.
+// Every event the contract emits is published through this module, so its shape is defined in one
+// place. The topics are (name, EVENT_SCHEMA_VERSION, subject), or (name, EVENT_SCHEMA_VERSION) for
+// events about the budget as a whole, and the data is one of the structs below (a map by field
+// name once encoded). Within a schema version names, topic layouts and data fields only ever get
+// added; renaming or removing one, or changing its type, bumps the version. Indexers can filter on
+// the version topic and check get_event_schema_version() before relying on a layout.
+//
+// name       subject      data
+// fee        collector    FeeEvent
+// spend      recipient    SpendEvent
+// deposit    depositor    DepositEvent
+// donate     donor        DonationEvent
+// match      donor        DonationEvent (the matched amount)
+// deficit    -            DeficitEvent
+// recovered  -            DeficitEvent
+// unbacked   token        UnbackedEvent
+// alertfail  subscriber   AlertFailEvent
+// anomaly    caller       AnomalyEvent
+// alarm      -            AlarmEvent
+// emergency  owner        EmergencyEvent
+// rotated    old address  RotatedEvent
+// slash      operator     SlashEvent
+// reconfig   owner        ConfigUpdate
+
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, BytesN, Env, IntoVal, Symbol, Val};
+
+use crate::{GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient};
+#[cfg(feature = "emergency")]
+use crate::Direction;
+
+
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+#[cfg(feature = "fee")]
+pub(crate) const FEE: Symbol = symbol_short!("fee");
+#[cfg(feature = "treasury")]
+pub(crate) const SPEND: Symbol = symbol_short!("spend");
+#[cfg(feature = "treasury")]
+pub(crate) const DEPOSIT: Symbol = symbol_short!("deposit");
+#[cfg(feature = "donation")]
+pub(crate) const DONATE: Symbol = symbol_short!("donate");
+#[cfg(feature = "matching")]
+pub(crate) const MATCH: Symbol = symbol_short!("match");
+#[cfg(feature = "deficit")]
+pub(crate) const DEFICIT: Symbol = symbol_short!("deficit");
+#[cfg(feature = "deficit")]
+pub(crate) const RECOVERED: Symbol = symbol_short!("recovered");
+#[cfg(feature = "solvency")]
+pub(crate) const UNBACKED: Symbol = symbol_short!("unbacked");
+#[cfg(feature = "alerts")]
+pub(crate) const ALERT_FAIL: Symbol = symbol_short!("alertfail");
+#[cfg(feature = "anomaly")]
+pub(crate) const ANOMALY: Symbol = symbol_short!("anomaly");
+#[cfg(feature = "breaker")]
+pub(crate) const ALARM: Symbol = symbol_short!("alarm");
+#[cfg(feature = "emergency")]
+pub(crate) const EMERGENCY: Symbol = symbol_short!("emergency");
+#[cfg(feature = "rotation")]
+pub(crate) const ROTATED: Symbol = symbol_short!("rotated");
+#[cfg(feature = "stake")]
+pub(crate) const SLASH: Symbol = symbol_short!("slash");
+#[cfg(feature = "reconfigure")]
+pub(crate) const RECONFIG: Symbol = symbol_short!("reconfig");
+
+
+#[cfg(feature = "fee")]
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct FeeEvent {
+    pub amount: i128,
+}
+// FeeEvent is a fee routed to the collector
+
+
+#[cfg(feature = "treasury")]
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct SpendEvent {
+    pub payment_id: u64,
+    pub amount: i128,
+}
+// SpendEvent is a logged payment, payment_id is its id in the payment log
+
+
+#[cfg(feature = "treasury")]
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct DepositEvent {
+    pub token: Address,
+    pub amount: i128,
+}
+// DepositEvent is a deposit of a token into the contract
+
+
+#[cfg(any(feature = "donation", feature = "matching"))]
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct DonationEvent {
+    pub donation_id: u64,
+    pub amount: i128,
+}
+// DonationEvent is a donation, or the amount a matching program added to it
+
+
+#[cfg(feature = "deficit")]
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct DeficitEvent {
+    pub current: i128,
+}
+// DeficitEvent is current after it went below zero (deficit) or back to zero or above (recovered)
+
+
+#[cfg(feature = "solvency")]
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct UnbackedEvent {
+    pub shortfall: i128,
+}
+// UnbackedEvent is a token whose balance falls short of the accounting
+
+
+#[cfg(feature = "alerts")]
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct AlertFailEvent {
+    pub threshold: i128,
+}
+// AlertFailEvent is an alert subscriber that failed to take the alert for a threshold
+
+
+#[cfg(feature = "anomaly")]
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct AnomalyEvent {
+    pub size: i128,
+    pub mean: i128,
+}
+// AnomalyEvent is an adjustment far above the usual size, with the mean size before it
+
+
+#[cfg(feature = "breaker")]
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct AlarmEvent {
+    pub drained: i128,
+    pub limit: i128,
+}
+// AlarmEvent is the circuit breaker pausing the contract, drained is what was decreased within its window
+
+
+#[cfg(feature = "emergency")]
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct EmergencyEvent {
+    pub adjustment_id: u64,
+    pub direction: Direction,
+    pub amount: i128,
+    pub justification_hash: BytesN<32>,
+}
+// EmergencyEvent is an emergency adjustment by the owner
+
+
+#[cfg(feature = "rotation")]
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct RotatedEvent {
+    pub new: Address,
+}
+// RotatedEvent is an operator key rotated to a new address
+
+
+#[cfg(feature = "stake")]
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct SlashEvent {
+    pub amount: i128,
+    pub evidence: BytesN<32>,
+}
+// SlashEvent is a slashed operator bond
+
+
+// Publishes an event about subject.
+pub(crate) fn publish<D: IntoVal<Env, Val>>(env: &Env, name: Symbol, subject: &Address, data: D) {
+    env.events().publish((name, EVENT_SCHEMA_VERSION, subject.clone()), data);
+}
+
+// Publishes an event about the budget as a whole.
+pub(crate) fn publish_global<D: IntoVal<Env, Val>>(env: &Env, name: Symbol, data: D) {
+    env.events().publish((name, EVENT_SCHEMA_VERSION), data);
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    // Get the version of the event schema the contract emits
+    pub fn get_event_schema_version(_env: Env) -> u32 {
+        EVENT_SCHEMA_VERSION
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Events};
+    use soroban_sdk::{symbol_short, vec, IntoVal};
+
+    #[test]
+    fn test_event_schema() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &100, &-1000, &10000);
+        assert_eq!(client.get_event_schema_version(), EVENT_SCHEMA_VERSION);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_deficit_mode(&owner, &true);
+
+        // Versioned topics, data as a struct
+        client.decrease_budget(&operator, &300);
+        let expected = vec![
+            &env,
+            (
+                contract_id.clone(),
+                (symbol_short!("deficit"), EVENT_SCHEMA_VERSION).into_val(&env),
+                DeficitEvent { current: -200 }.into_val(&env),
+            ),
+        ];
+        assert_eq!(env.events().all(), expected);
+    }
+}
@@ -0,0 +1,146 @@
+// Diagnostics for adjustments.
+// check_adjustment stops at the first check an adjustment fails, so fixing that one can just reveal
+// the next. explain_adjustment runs every check a direct adjustment goes through on its own and
+// reports all the ones that would fail, rate limit included, without changing anything. Checks that
+// only make sense for an operator (scope, tier) are still run for other addresses.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env, Vec};
+
+use crate::{
+    cap, check_granularity, check_operator, decreased_value, floor, increased_value, read_budget, require_not_paused,
+    BudgetError, Direction, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient,
+};
+#[cfg(feature = "bands")]
+use crate::bands;
+#[cfg(feature = "category")]
+use crate::category;
+#[cfg(feature = "cosign")]
+use crate::cosign;
+#[cfg(feature = "policy")]
+use crate::policy;
+#[cfg(feature = "ratelimit")]
+use crate::ratelimit;
+#[cfg(feature = "removal")]
+use crate::removal;
+#[cfg(feature = "tiers")]
+use crate::tiers;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Explanation {
+    pub errors: Vec<u32>,
+    pub attempted: i128,
+    pub limit: i128,
+}
+// Explanation lists why an adjustment would fail:
+// errors are the codes of every failing check in the order they run, each code once (empty means it would succeed)
+// attempted and limit are the value the adjustment would reach and the limit it is held to, as in LimitCheck
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    // Explain every reason an adjustment by caller would fail right now
+    pub fn explain_adjustment(env: Env, caller: Address, amount: i128, direction: Direction) -> Explanation {
+        let increase = direction == Direction::Increase;
+        let mut errors = Vec::new(&env);
+        let mut record = |result: Result<(), BudgetError>| {
+            if let Err(error) = result {
+                if !errors.contains(error as u32) {
+                    errors.push_back(error as u32);
+                }
+            }
+        };
+
+        record(check_operator(&env, &caller));
+        #[cfg(feature = "category")]
+        record(category::check_scope(&env, &caller, None));
+        #[cfg(feature = "tiers")]
+        record(tiers::check_direct(&env, &caller));
+        #[cfg(feature = "bands")]
+        record(bands::check(&env, amount));
+        #[cfg(feature = "cosign")]
+        if !increase {
+            record(cosign::check(&env));
+        }
+        record(require_not_paused(&env));
+        record(check_granularity(&env, amount));
+        #[cfg(feature = "tiers")]
+        record(tiers::check_size(&env, &caller, amount));
+        #[cfg(feature = "removal")]
+        if !increase {
+            record(removal::check_not_leaving(&env, &caller));
+        }
+        #[cfg(feature = "policy")]
+        record(policy::check(&env, amount, increase));
+
+        let budget = read_budget(&env);
+        let value = if increase {
+            increased_value(&env, &budget, amount).map(|value| (value, cap(&env, &budget)))
+        } else {
+            decreased_value(&env, &budget, amount).map(|value| (value, floor(&env, &budget)))
+        };
+        let (attempted, limit) = match value {
+            Ok(value) => value,
+            Err(violation) => {
+                record(Err(violation.error));
+                (violation.attempted, violation.limit)
+            }
+        };
+        #[cfg(feature = "ratelimit")]
+        record(ratelimit::peek(&env, &caller));
+        Explanation { errors, attempted, limit }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::vec;
+
+    #[test]
+    fn test_explain_adjustment() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let alice = Address::generate(&env);
+        let mallory = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &alice);
+        client.accept_operator(&alice);
+
+        let explanation = client.explain_adjustment(&alice, &500, &Direction::Increase);
+        assert_eq!(explanation, Explanation { errors: vec![&env], attempted: 1500, limit: 10000 });
+
+        // Every failing check is reported, not just the first
+        client.pause(&owner);
+        client.set_granularity(&owner, &7);
+        let explanation = client.explain_adjustment(&mallory, &10000, &Direction::Increase);
+        let expected = vec![
+            &env,
+            BudgetError::NotOperator as u32,
+            BudgetError::Paused as u32,
+            BudgetError::InvalidGranularity as u32,
+            BudgetError::ExceedsMax as u32,
+        ];
+        assert_eq!(explanation.errors, expected);
+        assert_eq!((explanation.attempted, explanation.limit), (11000, 10000));
+
+        // The rate limit is reported without using up the ledger's allowance
+        client.unpause(&owner);
+        client.set_granularity(&owner, &0);
+        client.set_rate_limit(&owner, &0, &1);
+        client.decrease_budget(&alice, &100);
+        let explanation = client.explain_adjustment(&alice, &100, &Direction::Decrease);
+        assert_eq!(explanation.errors, vec![&env, BudgetError::RateLimited as u32]);
+        assert_eq!(client.explain_adjustment(&owner, &100, &Direction::Decrease).errors, vec![&env, BudgetError::NotOperator as u32]);
+        assert_eq!(client.get_budget().current, 900);
+    }
+}
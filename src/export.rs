@@ -0,0 +1,208 @@
+// State export and import.
+// When a contract cannot be upgraded in place, its state is carried over to a fresh deployment in
+// chunks small enough for one transaction each. The owner reads chunk 0, 1, 2... with export_state
+// until it returns End, and hands every chunk in the same order to import_state on the new
+// deployment, initialized with any budget and owned by whoever will own it from then on.
+// Chunk 0 is the core state: the budget and limits, the main settings and the position of the
+// history log with its Merkle tree, so the history root is the same on both sides. The operators
+// and the receipts still held in the log follow in pages. Importing is only possible into an
+// instance that has not been used yet and ends with the End chunk; the settings of the feature
+// modules (policies, payees, categories...) are not carried over and are set up again.
+
+use soroban_sdk::{contractimpl, contracttype, Address, BytesN, Env, Vec};
+
+use crate::{
+    epoch_length, granularity, history, is_listed, max_operators, read_admin, read_budget, read_operators, require_owner,
+    set_listed, BudgetError, BudgetState, DataKey, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient, Receipt,
+};
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct CoreState {
+    pub budget: BudgetState,
+    pub epoch_length: u32,
+    pub granularity: i128,
+    pub max_operators: u32,
+    pub admin: Option<Address>,
+    pub adjustment_count: u64,
+    pub history_start: u64,
+    pub merkle_branch: Vec<BytesN<32>>,
+}
+// CoreState is chunk 0 of an export:
+// adjustment_count, history_start and merkle_branch are the position and tree of the history log
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub enum StateChunk {
+    Core(CoreState),
+    Operators(Vec<Address>),
+    History(Vec<Receipt>),
+    End,
+}
+// StateChunk is one chunk of an export:
+// Core is always chunk 0
+// Operators is a page of the operators list, in list order
+// History is a page of the receipts held in the log, oldest first
+// End follows the last chunk
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum ExportKey {
+    ImportOpen,
+}
+// ImportOpen is set from the imported Core chunk until the End chunk
+
+
+// Operators or receipts per chunk.
+const CHUNK_SIZE: u32 = 20;
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Read one chunk of the contract state for import_state on another deployment (owner only)
+    pub fn export_state(env: Env, caller: Address, chunk: u32) -> Result<StateChunk, BudgetError> {
+        require_owner(&env, &caller)?;
+        if chunk == 0 {
+            return Ok(StateChunk::Core(CoreState {
+                budget: read_budget(&env),
+                epoch_length: epoch_length(&env),
+                granularity: granularity(&env),
+                max_operators: max_operators(&env),
+                admin: read_admin(&env),
+                adjustment_count: env.storage().persistent().get(&DataKey::AdjustmentCount).unwrap_or(0),
+                history_start: history::start(&env),
+                merkle_branch: history::branch(&env),
+            }));
+        }
+
+        // Then the operator pages, then the history pages
+        let operators = read_operators(&env);
+        let operator_pages = operators.len().div_ceil(CHUNK_SIZE);
+        let page = chunk - 1;
+        if page < operator_pages {
+            let start = page * CHUNK_SIZE;
+            return Ok(StateChunk::Operators(operators.slice(start..(start + CHUNK_SIZE).min(operators.len()))));
+        }
+
+        let start = history::start(&env) + u64::from(page - operator_pages) * u64::from(CHUNK_SIZE);
+        let size: u64 = env.storage().persistent().get(&DataKey::AdjustmentCount).unwrap_or(0);
+        if start >= size {
+            return Ok(StateChunk::End);
+        }
+        let mut receipts = Vec::new(&env);
+        for id in start..(start + u64::from(CHUNK_SIZE)).min(size) {
+            if let Some(receipt) = history::entry(&env, id) {
+                receipts.push_back(receipt);
+            }
+        }
+        Ok(StateChunk::History(receipts))
+    }
+
+    /// Apply the next chunk exported from another deployment, in export order (owner only)
+    pub fn import_state(env: Env, caller: Address, chunk: StateChunk) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        let open = env.storage().persistent().has(&ExportKey::ImportOpen);
+        match chunk {
+            StateChunk::Core(core) => {
+                // Only into an instance nothing has happened in yet
+                let used: u64 = env.storage().persistent().get(&DataKey::AdjustmentCount).unwrap_or(0);
+                if open || used > 0 || !read_operators(&env).is_empty() {
+                    return Err(BudgetError::AlreadyRegistered);
+                }
+                let budget = core.budget;
+                if budget.min > budget.current || budget.current > budget.max {
+                    return Err(BudgetError::InvalidLimits);
+                }
+                history::restore(&env, core.adjustment_count, core.history_start, &core.merkle_branch)?;
+                env.storage().instance().set(&DataKey::Budget, &budget);
+                env.storage().persistent().set(&DataKey::EpochLength, &core.epoch_length);
+                env.storage().instance().set(&DataKey::Granularity, &core.granularity);
+                env.storage().persistent().set(&DataKey::MaxOperators, &core.max_operators);
+                if let Some(admin) = core.admin {
+                    env.storage().persistent().set(&DataKey::Admin, &admin);
+                }
+                env.storage().persistent().set(&ExportKey::ImportOpen, &true);
+            }
+            _ if !open => return Err(BudgetError::NoPendingAction),
+            StateChunk::Operators(page) => {
+                let mut operators = read_operators(&env);
+                for operator in page.iter() {
+                    if !is_listed(&env, &operator) {
+                        set_listed(&env, &operator, true);
+                        operators.push_back(operator);
+                    }
+                }
+                env.storage().persistent().set(&DataKey::Operators, &operators);
+            }
+            StateChunk::History(receipts) => {
+                for receipt in receipts.iter() {
+                    history::restore_entry(&env, &receipt)?;
+                }
+            }
+            StateChunk::End => env.storage().persistent().remove(&ExportKey::ImportOpen),
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+
+    #[test]
+    fn test_export_import_state() {
+        let env = Env::default();
+        let old_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let new_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let old = GovernanceBudgetAllocatorClient::new(&env, &old_id);
+        let new = GovernanceBudgetAllocatorClient::new(&env, &new_id);
+
+        let owner = Address::generate(&env);
+        old.initialize(&owner, &1000, &0, &10000);
+        new.initialize(&owner, &0, &0, &0);
+
+        env.mock_all_auths();
+        let mut operators = Vec::new(&env);
+        for _ in 0..25 {
+            let operator = Address::generate(&env);
+            old.add_operator(&owner, &operator);
+            old.accept_operator(&operator);
+            operators.push_back(operator);
+        }
+        let operator = operators.get_unchecked(0);
+        for _ in 0..30 {
+            old.increase_budget(&operator, &10);
+        }
+        old.set_granularity(&owner, &10);
+
+        // Core, two operator pages, two history pages, End
+        let mut chunk = 0;
+        loop {
+            let exported = old.export_state(&owner, &chunk);
+            new.import_state(&owner, &exported);
+            if exported == StateChunk::End {
+                break;
+            }
+            chunk += 1;
+        }
+        assert_eq!(chunk, 5);
+
+        assert_eq!(new.get_budget(), old.get_budget());
+        assert_eq!(new.get_operators(), operators);
+        assert!(new.is_operator(&operator));
+        assert_eq!(new.get_granularity(), 10);
+        assert_eq!(new.get_history_root(), old.get_history_root());
+        assert_eq!(new.get_history_entry(&29), old.get_history_entry(&29));
+        assert_eq!(new.state_digest(), old.state_digest());
+
+        // Closed once End is imported; adjustments carry on with the next id
+        let result = new.try_import_state(&owner, &StateChunk::Operators(Vec::new(&env)));
+        assert_eq!(result, Err(Ok(BudgetError::NoPendingAction)));
+        assert_eq!(new.increase_budget(&operator, &10).id, 30);
+    }
+}
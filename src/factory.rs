@@ -0,0 +1,223 @@
+// Factory contract for deploying team allocators.
+// AllocatorFactory lives in this crate next to the allocator. Its admin uploads the allocator
+// wasm once and stores the hash here; deploy_allocator then deploys a fresh instance with a salt
+// derived from the team Symbol, initializes it and records it in an index keyed by that team.
+// Instances deployed elsewhere can be added to the index with register_allocator.
+// The factory's entrypoint names do not overlap with the allocator's, so both contracts can be
+// built from the same wasm. Every allocator instance therefore also exposes factory_init, which
+// refuses to run on a contract that is already set up as an allocator; deploy_allocator sets the
+// allocator up in the same call that deploys it.
+
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, xdr::ToXdr, Address, BytesN, Env, Symbol, Vec,
+};
+
+use crate::{DataKey, GovernanceBudgetAllocatorClient};
+
+
+#[contract]
+pub struct AllocatorFactory;
+
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum FactoryError {
+    NotInitialized = 1,
+    AlreadyInitialized = 2,
+    NotFactoryAdmin = 3,
+    TeamExists = 4,
+}
+// FactoryError is separate from BudgetError because the factory is a separate contract.
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum FactoryKey {
+    FactoryAdmin,
+    AllocatorWasm,
+    Allocator(Symbol),
+    Teams,
+}
+// FactoryAdmin stores the address allowed to deploy and register allocators
+// AllocatorWasm stores the hash of the uploaded allocator wasm
+// Allocator stores the allocator address of a team
+// Teams stores every team in the index, in registration order
+
+
+fn require_admin(env: &Env, caller: &Address) -> Result<(), FactoryError> {
+    caller.require_auth();
+    let admin: Address = env
+        .storage()
+        .persistent()
+        .get(&FactoryKey::FactoryAdmin)
+        .ok_or(FactoryError::NotInitialized)?;
+    if *caller != admin {
+        return Err(FactoryError::NotFactoryAdmin);
+    }
+    Ok(())
+}
+
+fn check_team_free(env: &Env, team: &Symbol) -> Result<(), FactoryError> {
+    if env.storage().persistent().has(&FactoryKey::Allocator(team.clone())) {
+        return Err(FactoryError::TeamExists);
+    }
+    Ok(())
+}
+
+fn add_to_index(env: &Env, team: Symbol, allocator: &Address) -> Result<(), FactoryError> {
+    check_team_free(env, &team)?;
+    env.storage().persistent().set(&FactoryKey::Allocator(team.clone()), allocator);
+
+    let mut teams: Vec<Symbol> = env.storage().persistent().get(&FactoryKey::Teams).unwrap_or(Vec::new(env));
+    teams.push_back(team);
+    env.storage().persistent().set(&FactoryKey::Teams, &teams);
+    Ok(())
+}
+
+
+#[contractimpl]
+impl AllocatorFactory {
+    /// Set the factory admin and the allocator wasm hash (once, never on an allocator)
+    pub fn factory_init(env: Env, admin: Address, wasm_hash: BytesN<32>) -> Result<(), FactoryError> {
+        let is_allocator = env.storage().instance().has(&DataKey::Owner);
+        if is_allocator || env.storage().persistent().has(&FactoryKey::FactoryAdmin) {
+            return Err(FactoryError::AlreadyInitialized);
+        }
+        env.storage().persistent().set(&FactoryKey::FactoryAdmin, &admin);
+        env.storage().persistent().set(&FactoryKey::AllocatorWasm, &wasm_hash);
+        Ok(())
+    }
+
+    /// Point future deployments at a new allocator wasm (factory admin only)
+    pub fn set_allocator_wasm(env: Env, caller: Address, wasm_hash: BytesN<32>) -> Result<(), FactoryError> {
+        require_admin(&env, &caller)?;
+        env.storage().persistent().set(&FactoryKey::AllocatorWasm, &wasm_hash);
+        Ok(())
+    }
+
+    /// Deploy and initialize an allocator for a team (factory admin only)
+    pub fn deploy_allocator(
+        env: Env,
+        caller: Address,
+        team: Symbol,
+        owner: Address,
+        initial: i128,
+        min: i128,
+        max: i128,
+    ) -> Result<Address, FactoryError> {
+        require_admin(&env, &caller)?;
+        check_team_free(&env, &team)?;
+        let wasm_hash: BytesN<32> = env.storage().persistent().get(&FactoryKey::AllocatorWasm).unwrap();
+
+        // One deterministic address per team
+        let salt: BytesN<32> = env.crypto().sha256(&team.clone().to_xdr(&env)).into();
+        let allocator = env.deployer().with_current_contract(salt).deploy(wasm_hash);
+
+        GovernanceBudgetAllocatorClient::new(&env, &allocator).initialize(&owner, &initial, &min, &max);
+        add_to_index(&env, team, &allocator)?;
+        Ok(allocator)
+    }
+
+    /// Add an allocator deployed outside the factory to the index (factory admin only)
+    pub fn register_allocator(env: Env, caller: Address, team: Symbol, allocator: Address) -> Result<(), FactoryError> {
+        require_admin(&env, &caller)?;
+        add_to_index(&env, team, &allocator)
+    }
+
+    // Get the allocator of a team
+    pub fn get_allocator(env: Env, team: Symbol) -> Option<Address> {
+        env.storage().persistent().get(&FactoryKey::Allocator(team))
+    }
+
+    // Get every team in the index
+    pub fn get_teams(env: Env) -> Vec<Symbol> {
+        env.storage().persistent().get(&FactoryKey::Teams).unwrap_or(Vec::new(&env))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::xdr::{ContractDataDurability, LedgerKey, LedgerKeyContractData, ScAddress, ScVal, ToXdr};
+    use soroban_sdk::{symbol_short, Bytes, BytesN, Symbol};
+    use std::rc::Rc;
+
+    // The allocator wasm is not built for unit tests, so an empty wasm stands in for it: the native
+    // allocator is registered at the address the factory will deploy the team to, and its instance
+    // entry is dropped again so the deployment can create it. Returns the stand-in's wasm hash.
+    fn stand_in_allocator(env: &Env, factory_id: &Address, team: &Symbol) -> BytesN<32> {
+        let salt: BytesN<32> = env.crypto().sha256(&team.clone().to_xdr(env)).into();
+        let address = env.as_contract(factory_id, || env.deployer().with_current_contract(salt).deployed_address());
+        env.register_contract(&address, GovernanceBudgetAllocator);
+        let instance = Rc::new(LedgerKey::ContractData(LedgerKeyContractData {
+            contract: ScAddress::from(&address),
+            key: ScVal::LedgerKeyContractInstance,
+            durability: ContractDataDurability::Persistent,
+        }));
+        let budget = env.host().budget_cloned();
+        env.host().with_mut_storage(|storage| storage.del(&instance, &budget)).unwrap();
+        env.deployer().upload_contract_wasm(Bytes::new(env))
+    }
+
+    #[test]
+    fn test_factory_index() {
+        let env = Env::default();
+        let factory_id = env.register_contract(None, AllocatorFactory);
+        let factory = AllocatorFactoryClient::new(&env, &factory_id);
+        let allocator_id = env.register_contract(None, GovernanceBudgetAllocator);
+
+        let admin = Address::generate(&env);
+        let other = Address::generate(&env);
+
+        factory.factory_init(&admin, &BytesN::from_array(&env, &[0; 32]));
+        assert_eq!(factory.try_factory_init(&admin, &BytesN::from_array(&env, &[0; 32])), Err(Ok(FactoryError::AlreadyInitialized)));
+
+        env.mock_all_auths();
+        let team = symbol_short!("infra");
+        assert_eq!(factory.try_register_allocator(&other, &team, &allocator_id), Err(Ok(FactoryError::NotFactoryAdmin)));
+
+        factory.register_allocator(&admin, &team, &allocator_id);
+        assert_eq!(factory.get_allocator(&team), Some(allocator_id.clone()));
+        assert_eq!(factory.get_allocator(&symbol_short!("ops")), None);
+        assert_eq!(factory.get_teams().len(), 1);
+
+        // A team maps to exactly one allocator
+        assert_eq!(factory.try_register_allocator(&admin, &team, &allocator_id), Err(Ok(FactoryError::TeamExists)));
+    }
+
+    #[test]
+    fn test_deploy_allocator() {
+        let env = Env::default();
+        let factory_id = env.register_contract(None, AllocatorFactory);
+        let factory = AllocatorFactoryClient::new(&env, &factory_id);
+
+        let admin = Address::generate(&env);
+        let owner = Address::generate(&env);
+        let team = symbol_short!("infra");
+
+        let wasm_hash = stand_in_allocator(&env, &factory_id, &team);
+        factory.factory_init(&admin, &wasm_hash);
+
+        env.mock_all_auths();
+        let allocator_id = factory.deploy_allocator(&admin, &team, &owner, &1000, &0, &10000);
+        assert_eq!(factory.get_allocator(&team), Some(allocator_id.clone()));
+
+        let allocator = GovernanceBudgetAllocatorClient::new(&env, &allocator_id);
+        assert_eq!(allocator.get_owner(), owner);
+        assert_eq!(allocator.get_budget(), BudgetState { current: 1000, min: 0, max: 10000 });
+
+        // Nobody can claim the deployed allocator as a factory (both are built from the same wasm)
+        let claim = env.as_contract(&allocator_id, || {
+            AllocatorFactory::factory_init(env.clone(), owner.clone(), wasm_hash.clone())
+        });
+        assert_eq!(claim, Err(FactoryError::AlreadyInitialized));
+
+        // A second deployment for the team is refused before deploying
+        assert_eq!(factory.try_deploy_allocator(&admin, &team, &owner, &0, &0, &0), Err(Ok(FactoryError::TeamExists)));
+    }
+}
@@ -0,0 +1,218 @@
+// Parent/child budget federation.
+// A parent allocator hands part of its own budget to child allocator contracts. The amount is
+// taken out of the parent's current value and the child's max is set to the total allocation
+// through a cross-contract call, so a child can never hold more than its parent gave it.
+// After every adjustment a child reports its current value back to the parent, which rejects
+// reports above the allocation and keeps them for org-wide rollups.
+// A child only accepts adjustments once its parent has allocated to it.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env, Vec};
+
+use crate::{
+    check_granularity, commit_adjustment, floor, read_budget, require_owner, write_budget, BudgetError, GovernanceBudgetAllocator,
+    GovernanceBudgetAllocatorClient,
+};
+
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[contracttype]
+pub struct ChildAllocation {
+    pub allocation: i128,
+    pub reported: i128,
+}
+// ChildAllocation is what the parent tracks per child:
+// allocation is the total the parent handed over (the child's max)
+// reported is the child's current value as of its last report
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum FederationKey {
+    Parent,
+    ParentAllocation,
+    Child(Address),
+    Children,
+}
+// Parent stores the parent allocator of a child
+// ParentAllocation stores the allocation the child received from its parent
+// Child stores the ChildAllocation of a child (on the parent)
+// Children stores the list of children (on the parent)
+
+
+fn read_child(env: &Env, child: &Address) -> Option<ChildAllocation> {
+    env.storage().persistent().get(&FederationKey::Child(child.clone()))
+}
+
+// Rejects a max above the allocation received from the parent.
+pub(crate) fn check_max(env: &Env, max: i128) -> Result<(), BudgetError> {
+    let allocation: Option<i128> = env.storage().persistent().get(&FederationKey::ParentAllocation);
+    match allocation {
+        Some(allocation) if max > allocation => Err(BudgetError::ExceedsMax),
+        _ => Ok(()),
+    }
+}
+
+// Called after every adjustment; a child reports its new current value to its parent.
+pub(crate) fn report(env: &Env, current: i128) {
+    let parent: Option<Address> = env.storage().persistent().get(&FederationKey::Parent);
+    if let Some(parent) = parent {
+        GovernanceBudgetAllocatorClient::new(env, &parent).report_child_budget(&env.current_contract_address(), &current);
+    }
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Make this allocator a child of another allocator (owner only)
+    pub fn set_parent(env: Env, caller: Address, parent: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().set(&FederationKey::Parent, &parent);
+        Ok(())
+    }
+
+    /// Move part of this budget to a child allocator and raise the child's max (owner only)
+    pub fn allocate_to_child(env: Env, caller: Address, child: Address, amount: i128) -> Result<i128, BudgetError> {
+        require_owner(&env, &caller)?;
+        if amount <= 0 {
+            return Err(BudgetError::InvalidConfig);
+        }
+        check_granularity(&env, amount)?;
+
+        // The allocation leaves this budget like any other decrease
+        let mut budget = read_budget(&env);
+        let new_value = budget.current.checked_sub(amount).ok_or(BudgetError::Underflow)?;
+        if new_value < floor(&env, &budget) {
+            return Err(BudgetError::BelowMin);
+        }
+        commit_adjustment(&env, &caller, &mut budget, new_value);
+
+        let mut entry = match read_child(&env, &child) {
+            Some(entry) => entry,
+            None => {
+                let mut children = Self::get_children(env.clone());
+                children.push_back(child.clone());
+                env.storage().persistent().set(&FederationKey::Children, &children);
+                ChildAllocation::default()
+            }
+        };
+        entry.allocation = entry.allocation.checked_add(amount).ok_or(BudgetError::Overflow)?;
+
+        let client = GovernanceBudgetAllocatorClient::new(&env, &child);
+        client.sync_allocation(&env.current_contract_address(), &entry.allocation);
+        entry.reported = client.get_budget().current;
+        env.storage().persistent().set(&FederationKey::Child(child), &entry);
+
+        Ok(new_value)
+    }
+
+    /// Apply the allocation set by the parent as this allocator's max (parent contract only)
+    pub fn sync_allocation(env: Env, parent: Address, allocation: i128) -> Result<(), BudgetError> {
+        parent.require_auth();
+        let expected: Option<Address> = env.storage().persistent().get(&FederationKey::Parent);
+        if expected != Some(parent) {
+            return Err(BudgetError::NotRegistered);
+        }
+
+        let mut budget = read_budget(&env);
+        if budget.current > allocation {
+            return Err(BudgetError::ExceedsMax);
+        }
+        if budget.min > allocation {
+            return Err(BudgetError::InvalidLimits);
+        }
+        budget.max = allocation;
+        write_budget(&env, &budget);
+        env.storage().persistent().set(&FederationKey::ParentAllocation, &allocation);
+        Ok(())
+    }
+
+    /// Record a child's current value (child contract only)
+    pub fn report_child_budget(env: Env, child: Address, current: i128) -> Result<(), BudgetError> {
+        child.require_auth();
+        let mut entry = read_child(&env, &child).ok_or(BudgetError::NotRegistered)?;
+        if current > entry.allocation {
+            return Err(BudgetError::ExceedsMax);
+        }
+        entry.reported = current;
+        env.storage().persistent().set(&FederationKey::Child(child), &entry);
+        Ok(())
+    }
+
+    // Get the parent allocator, if this is a child
+    pub fn get_parent(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&FederationKey::Parent)
+    }
+
+    // Get the allocation received from the parent, if any
+    pub fn get_parent_allocation(env: Env) -> Option<i128> {
+        env.storage().persistent().get(&FederationKey::ParentAllocation)
+    }
+
+    // Get what this allocator tracks for a child
+    pub fn get_child(env: Env, child: Address) -> Option<ChildAllocation> {
+        read_child(&env, &child)
+    }
+
+    // Get the children this allocator allocated to
+    pub fn get_children(env: Env) -> Vec<Address> {
+        env.storage().persistent().get(&FederationKey::Children).unwrap_or(Vec::new(&env))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+
+    #[test]
+    fn test_child_bounded_by_allocation() {
+        let env = Env::default();
+        let parent_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let parent = GovernanceBudgetAllocatorClient::new(&env, &parent_id);
+        let child_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let child = GovernanceBudgetAllocatorClient::new(&env, &child_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        parent.initialize(&owner, &10000, &0, &10000);
+        child.initialize(&owner, &0, &0, &0);
+
+        env.mock_all_auths();
+        child.add_operator(&owner, &operator);
+        child.accept_operator(&operator);
+        child.set_parent(&owner, &parent_id);
+
+        assert_eq!(parent.allocate_to_child(&owner, &child_id, &3000), 7000);
+        assert_eq!(child.get_budget().max, 3000);
+        assert_eq!(child.get_parent_allocation(), Some(3000));
+
+        // Child spend is reported back to the parent
+        child.increase_budget(&operator, &2000);
+        child.decrease_budget(&operator, &500);
+        assert_eq!(parent.get_child(&child_id).unwrap(), ChildAllocation { allocation: 3000, reported: 1500 });
+
+        // The child cannot go past the allocation, neither by adjusting nor by raising its own max
+        assert_eq!(child.try_increase_budget(&operator, &2000), Err(Ok(BudgetError::ExceedsMax)));
+        assert_eq!(child.try_set_limits(&owner, &0, &5000), Err(Ok(BudgetError::ExceedsMax)));
+    }
+
+    #[test]
+    fn test_sync_only_from_parent() {
+        let env = Env::default();
+        let child_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let child = GovernanceBudgetAllocatorClient::new(&env, &child_id);
+
+        let owner = Address::generate(&env);
+        let parent = Address::generate(&env);
+        let stranger = Address::generate(&env);
+
+        child.initialize(&owner, &0, &0, &0);
+
+        env.mock_all_auths();
+        child.set_parent(&owner, &parent);
+        assert_eq!(child.try_sync_allocation(&stranger, &1_000_000), Err(Ok(BudgetError::NotRegistered)));
+        assert_eq!(child.get_budget().max, 0);
+    }
+}
@@ -0,0 +1,175 @@
+// Fee on spends and decreases.
+// The owner can configure a fee in basis points that is skimmed from every decrease, however it
+// is made, and routed to a fee collector. The fee is part of the decreased amount, not added on top
+// of it: a spend of 1000 with a 1% fee decreases the budget by 1000, pays the recipient 990 and the
+// collector 10. Every committed decrease accrues its fee per collector for off-chain settlement;
+// disbursals that move tokens then settle it right away by transferring it to the collector.
+
+use soroban_sdk::{contractimpl, contracttype, token, Address, Env};
+
+use crate::{events, require_owner, BudgetError, FeeEvent, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient};
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct FeeConfig {
+    pub collector: Address,
+    pub bps: u32,
+}
+// FeeConfig is the configured fee: bps is the share of each disbursal in basis points (10000 = 100%)
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum FeeKey {
+    Fee,
+    FeesAccrued(Address),
+}
+// Fee stores the FeeConfig (missing means no fee)
+// FeesAccrued stores the accounting-only fees owed to a collector
+
+
+const MAX_BPS: u32 = 10_000;
+
+// The fee taken from a decrease of the given amount, with the config it was computed under.
+pub(crate) fn fee_on(env: &Env, amount: i128) -> Option<(FeeConfig, i128)> {
+    let config: FeeConfig = env.storage().persistent().get(&FeeKey::Fee)?;
+    // amount * bps / MAX_BPS without overflowing for large amounts
+    let (bps, max) = (config.bps as i128, MAX_BPS as i128);
+    let fee = amount / max * bps + amount % max * bps / max;
+    if fee == 0 {
+        return None;
+    }
+    Some((config, fee))
+}
+
+fn accrue(env: &Env, collector: &Address, fee: i128) {
+    let key = FeeKey::FeesAccrued(collector.clone());
+    let accrued: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+    env.storage().persistent().set(&key, &(accrued + fee));
+}
+
+// Called for every committed decrease: accrues its fee to the collector.
+pub(crate) fn on_decrease(env: &Env, amount: i128) {
+    if let Some((config, fee)) = fee_on(env, amount) {
+        accrue(env, &config.collector, fee);
+        events::publish(env, events::FEE, &config.collector, FeeEvent { amount: fee });
+    }
+}
+
+// Settles the fee a decrease of amount accrued by transferring it to the collector in token, or
+// leaves it accrued when no token is given. Returns the fee so the caller can pay out the rest.
+pub(crate) fn settle(env: &Env, amount: i128, token: Option<&Address>) -> i128 {
+    let Some((config, fee)) = fee_on(env, amount) else {
+        return 0;
+    };
+    if let Some(token) = token {
+        accrue(env, &config.collector, -fee);
+        token::Client::new(env, token).transfer(&env.current_contract_address(), &config.collector, &fee);
+    }
+    fee
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Take a fee in basis points from every spend and decrease and route it to a collector (owner only)
+    pub fn set_fee(env: Env, caller: Address, collector: Address, bps: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        if bps == 0 || bps > MAX_BPS {
+            return Err(BudgetError::InvalidConfig);
+        }
+        env.storage().persistent().set(&FeeKey::Fee, &FeeConfig { collector, bps });
+        Ok(())
+    }
+
+    /// Stop taking fees (owner only)
+    pub fn clear_fee(env: Env, caller: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().remove(&FeeKey::Fee);
+        Ok(())
+    }
+
+    // Get the fee configuration, if any
+    pub fn get_fee(env: Env) -> Option<FeeConfig> {
+        env.storage().persistent().get(&FeeKey::Fee)
+    }
+
+    // Get the accounting-only fees accrued to a collector
+    pub fn get_fees_accrued(env: Env, collector: Address) -> i128 {
+        env.storage().persistent().get(&FeeKey::FeesAccrued(collector)).unwrap_or(0)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{token, String};
+
+    #[test]
+    fn test_fee_on_spend() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let recipient = Address::generate(&env);
+        let collector = Address::generate(&env);
+
+        client.initialize(&owner, &2000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        let sac = env.register_stellar_asset_contract_v2(owner.clone());
+        let backing = token::Client::new(&env, &sac.address());
+        token::StellarAssetClient::new(&env, &sac.address()).mint(&contract_id, &2000);
+        client.set_token(&owner, &sac.address());
+        assert_eq!(client.try_set_fee(&owner, &collector, &10001), Err(Ok(BudgetError::InvalidConfig)));
+        client.set_fee(&owner, &collector, &100);
+
+        client.spend(&operator, &recipient, &1000, &String::from_str(&env, ""));
+        assert_eq!(client.get_budget().current, 1000);
+        assert_eq!(backing.balance(&recipient), 990);
+        assert_eq!(backing.balance(&collector), 10);
+        assert_eq!(client.get_payment(&0).unwrap().amount, 990);
+
+        // Plain decreases only accrue the fee
+        assert_eq!(client.get_fees_accrued(&collector), 0);
+        client.decrease_budget(&operator, &500);
+        assert_eq!(client.get_fees_accrued(&collector), 5);
+        assert_eq!(backing.balance(&collector), 10);
+    }
+
+    #[test]
+    fn test_fee_on_every_decrease() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let collector = Address::generate(&env);
+
+        client.initialize(&owner, &i128::MAX, &0, &i128::MAX);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_fee(&owner, &collector, &100);
+
+        // Queued and reserved decreases pay the fee like direct ones
+        client.queue_adjustment(&operator, &1000, &false);
+        assert_eq!(client.execute_ready(&1), 1);
+        let id = client.reserve(&operator, &2000, &soroban_sdk::symbol_short!("audit"));
+        client.consume_reservation(&operator, &id);
+        assert_eq!(client.get_fees_accrued(&collector), 30);
+
+        // Amounts near i128::MAX do not overflow
+        client.decrease_budget(&operator, &(i128::MAX - 3000));
+        assert_eq!(client.get_fees_accrued(&collector), 30 + (i128::MAX - 3000) / 100);
+    }
+}
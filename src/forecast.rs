@@ -0,0 +1,73 @@
+// Budget forecast.
+// Scheduled limits, decay and refill are all applied lazily when the budget is read. project_budget
+// applies them, in the same order, to a copy of the stored budget as if it were first read at a
+// given ledger, with the vesting schedule (the max unlocking over time) capping refills as it will
+// then. Nothing is stored. Adjustments, payments and settings changed in the meantime are not
+// foreseen, so the result is what the standing policies alone will make of the budget.
+
+use soroban_sdk::{contractimpl, Env};
+
+use crate::{stored_budget, BudgetState, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient};
+#[cfg(feature = "decay")]
+use crate::decay;
+#[cfg(feature = "refill")]
+use crate::refill;
+#[cfg(feature = "schedule")]
+use crate::schedule;
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    // Get the budget expected at a ledger from the scheduled limits, decay, refill and vesting alone
+    pub fn project_budget(env: Env, at_ledger: u32) -> BudgetState {
+        // The past is no different from now: whatever is due has not been applied yet
+        let ledger = at_ledger.max(env.ledger().sequence());
+        let mut budget = stored_budget(&env);
+        #[cfg(feature = "schedule")]
+        schedule::project(&env, &mut budget, ledger);
+        #[cfg(feature = "decay")]
+        decay::project(&env, &mut budget, ledger);
+        #[cfg(feature = "refill")]
+        refill::project(&env, &mut budget, ledger);
+        budget
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    #[test]
+    fn test_project_budget() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+        assert_eq!(client.project_budget(&500), BudgetState { current: 1000, min: 0, max: 10000 });
+
+        env.mock_all_auths();
+        client.set_epoch_length(&owner, &100);
+        client.set_decay(&owner, &300);
+        client.set_refill(&owner, &500);
+        client.schedule_limits(&owner, &250, &0, &800);
+
+        // Within the epoch nothing is due
+        assert_eq!(client.project_budget(&99).current, 1000);
+        // Decayed by one epoch, above the refill target
+        assert_eq!(client.project_budget(&150).current, 700);
+        // Clamped into the scheduled limits, then decayed by two epochs and refilled
+        assert_eq!(client.project_budget(&250), BudgetState { current: 500, min: 0, max: 800 });
+
+        // The projection matches what reading the budget then applies, and stores nothing
+        assert_eq!(client.get_scheduled_limits().len(), 1);
+        let projected = client.project_budget(&420);
+        env.ledger().with_mut(|l| l.sequence_number = 420);
+        assert_eq!(client.get_budget(), projected);
+        assert_eq!(client.project_budget(&0), projected);
+    }
+}
@@ -0,0 +1,576 @@
+// Adjustment history and its Merkle commitment.
+// Every committed adjustment is kept in the history log as its Receipt, keyed by adjustment id.
+// The receipts are also appended as leaves sha256(xdr(receipt)) of an append-only Merkle tree of
+// fixed depth, of which only the right-hand branch is stored. get_history_root returns the root
+// over all adjustments so far, and verify_history_leaf checks an inclusion proof against it, so
+// an archived adjustment can be proven to have existed even once its log entry has been pruned.
+// Leaf i sits at index i (the adjustment id); missing leaves to the right are zero hashes.
+// To bound storage the owner can prune old log entries with prune_history. Entries younger than
+// the retention window are always kept; aggregates (stats, epoch reports, the root) are not touched.
+// The owner can also give the log a fixed capacity. Each new adjustment then drops the oldest entry
+// once the log is full, and a smaller capacity drops the oldest entries beyond it at once by moving
+// the start of the log. Dropped entries are only unreachable at first: every new adjustment deletes
+// a few of them from storage, so changing the capacity never touches more than a handful of entries
+// in one call. Ids keep counting up and get_history_entry answers None for a dropped id.
+// Next to the log, every address that adjusted the budget, or had a deputy adjust it on its
+// behalf, keeps an index of its adjustment ids, so get_history_by_operator pages through one
+// operator's entries, under its current and any rotated away key, without scanning the whole log.
+// The index only holds ids and is never pruned; entries no longer in the log are skipped. Receipts are in ledger time order, so get_history_by_ledger_range
+// finds the first entry of a time range by binary search over the held part of the log.
+
+use soroban_sdk::{contractimpl, contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env, Vec};
+
+use crate::{require_owner, BudgetError, DataKey, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient, Receipt};
+#[cfg(feature = "rotation")]
+use crate::rotation;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct HistoryRoot {
+    pub root: BytesN<32>,
+    pub size: u64,
+}
+// HistoryRoot is the Merkle root over the first size adjustments.
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum HistoryKey {
+    HistoryEntry(u64),
+    MerkleBranch,
+    HistoryRetention,
+    HistoryStart,
+    HistoryCapacity,
+    HistoryCleared,
+    OperatorEntryCount(Address),
+    OperatorEntry(Address, u32),
+}
+// HistoryEntry stores the Receipt of an adjustment by id
+// MerkleBranch stores the right-hand branch of the history tree, one node per level
+// HistoryRetention stores how many ledgers entries are kept at least
+// HistoryStart stores the id of the oldest entry not pruned or dropped yet
+// HistoryCapacity stores how many entries the log holds (missing or 0 means no limit)
+// HistoryCleared stores the id below which every dropped entry has been deleted from storage
+// OperatorEntryCount stores how many adjustments an address made
+// OperatorEntry stores the id of an address's nth adjustment
+
+
+// Enough levels for 2^32 adjustments.
+const DEPTH: u32 = 32;
+
+// Every entry of a page is a storage read, so pages are capped.
+const MAX_PAGE: u32 = 50;
+
+// Dropped entries deleted per adjustment, more than the one a full log drops so a backlog shrinks.
+const CLEAR_PER_RECORD: u64 = 2;
+
+fn hash_pair(env: &Env, left: &BytesN<32>, right: &BytesN<32>) -> BytesN<32> {
+    let mut data: Bytes = left.clone().into();
+    data.append(&right.clone().into());
+    env.crypto().sha256(&data).into()
+}
+
+pub(crate) fn leaf(env: &Env, receipt: &Receipt) -> BytesN<32> {
+    env.crypto().sha256(&receipt.clone().to_xdr(env)).into()
+}
+
+fn retention(env: &Env) -> u32 {
+    env.storage().persistent().get(&HistoryKey::HistoryRetention).unwrap_or(0)
+}
+
+pub(crate) fn start(env: &Env) -> u64 {
+    env.storage().persistent().get(&HistoryKey::HistoryStart).unwrap_or(0)
+}
+
+fn log_capacity(env: &Env) -> u64 {
+    env.storage().persistent().get::<_, u32>(&HistoryKey::HistoryCapacity).unwrap_or(0).into()
+}
+
+fn cleared(env: &Env) -> u64 {
+    env.storage().persistent().get(&HistoryKey::HistoryCleared).unwrap_or(0)
+}
+
+// Deletes up to CLEAR_PER_RECORD entries that were dropped from the log but are still stored.
+fn clear_dropped(env: &Env) {
+    let cleared = cleared(env);
+    let until = start(env).min(cleared + CLEAR_PER_RECORD);
+    if until > cleared {
+        for id in cleared..until {
+            env.storage().persistent().remove(&HistoryKey::HistoryEntry(id));
+        }
+        env.storage().persistent().set(&HistoryKey::HistoryCleared, &until);
+    }
+}
+
+// The log entry of an adjustment, if it is still held.
+pub(crate) fn entry(env: &Env, id: u64) -> Option<Receipt> {
+    if id < start(env) || id >= size(env) {
+        return None;
+    }
+    env.storage().persistent().get(&HistoryKey::HistoryEntry(id))
+}
+
+fn size(env: &Env) -> u64 {
+    env.storage().persistent().get(&DataKey::AdjustmentCount).unwrap_or(0)
+}
+
+fn operator_entries(env: &Env, address: &Address) -> u32 {
+    env.storage().persistent().get(&HistoryKey::OperatorEntryCount(address.clone())).unwrap_or(0)
+}
+
+fn index_entry_under(env: &Env, address: &Address, id: u64) {
+    let count = operator_entries(env, address);
+    let storage = env.storage().persistent();
+    storage.set(&HistoryKey::OperatorEntry(address.clone(), count), &id);
+    storage.set(&HistoryKey::OperatorEntryCount(address.clone()), &(count + 1));
+}
+
+// A deputy's adjustment is indexed under the deputy and the operator it acted for.
+fn index_entry(env: &Env, receipt: &Receipt) {
+    index_entry_under(env, &receipt.caller, receipt.id);
+    if let Some(principal) = &receipt.on_behalf_of {
+        index_entry_under(env, principal, receipt.id);
+    }
+}
+
+pub(crate) fn branch(env: &Env) -> Vec<BytesN<32>> {
+    env.storage().persistent().get(&HistoryKey::MerkleBranch).unwrap_or_else(|| {
+        let mut branch = Vec::new(env);
+        for _ in 0..DEPTH {
+            branch.push_back(BytesN::from_array(env, &[0; 32]));
+        }
+        branch
+    })
+}
+
+// Called for every committed adjustment, after the adjustment count was bumped past its id.
+pub(crate) fn record(env: &Env, receipt: &Receipt) {
+    // A full log drops its oldest entry
+    let capacity = log_capacity(env);
+    if capacity > 0 && receipt.id >= start(env) + capacity {
+        env.storage().persistent().set(&HistoryKey::HistoryStart, &(receipt.id + 1 - capacity));
+    }
+    env.storage().persistent().set(&HistoryKey::HistoryEntry(receipt.id), receipt);
+    clear_dropped(env);
+    index_entry(env, receipt);
+
+    // Standard incremental Merkle tree append
+    let mut branch = branch(env);
+    let mut node = leaf(env, receipt);
+    let mut index = receipt.id + 1;
+    for level in 0..DEPTH {
+        if index & 1 == 1 {
+            branch.set(level, node);
+            break;
+        }
+        node = hash_pair(env, &branch.get_unchecked(level), &node);
+        index >>= 1;
+    }
+    env.storage().persistent().set(&HistoryKey::MerkleBranch, &branch);
+}
+
+// Takes over the log position and tree of another deployment (see export.rs), without entries.
+pub(crate) fn restore(env: &Env, size: u64, start: u64, branch: &Vec<BytesN<32>>) -> Result<(), BudgetError> {
+    if branch.len() != DEPTH || start > size {
+        return Err(BudgetError::InvalidConfig);
+    }
+    env.storage().persistent().set(&DataKey::AdjustmentCount, &size);
+    env.storage().persistent().set(&HistoryKey::HistoryStart, &start);
+    env.storage().persistent().set(&HistoryKey::HistoryCleared, &start);
+    env.storage().persistent().set(&HistoryKey::MerkleBranch, branch);
+    Ok(())
+}
+
+// Puts back an entry of the restored log; it is already part of the tree.
+pub(crate) fn restore_entry(env: &Env, receipt: &Receipt) -> Result<(), BudgetError> {
+    if receipt.id < start(env) || receipt.id >= size(env) {
+        return Err(BudgetError::AdjustmentNotFound);
+    }
+    env.storage().persistent().set(&HistoryKey::HistoryEntry(receipt.id), receipt);
+    index_entry(env, receipt);
+    Ok(())
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Set how many ledgers log entries are kept before they may be pruned (owner only)
+    pub fn set_history_retention(env: Env, caller: Address, ledgers: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().set(&HistoryKey::HistoryRetention, &ledgers);
+        Ok(())
+    }
+
+    /// Delete log entries with an id below before_seq that are outside the retention window (owner only), returns how many
+    pub fn prune_history(env: Env, caller: Address, before_seq: u64) -> Result<u64, BudgetError> {
+        require_owner(&env, &caller)?;
+        let now = env.ledger().sequence();
+        let retention = retention(&env);
+        let first = start(&env);
+        let mut id = first;
+        let end = before_seq.min(size(&env));
+        while id < end {
+            if let Some(entry) = entry(&env, id) {
+                // Entries are in ledger order, so everything after a retained one is retained too
+                if entry.ledger.saturating_add(retention) > now {
+                    break;
+                }
+                env.storage().persistent().remove(&HistoryKey::HistoryEntry(id));
+            }
+            id += 1;
+        }
+        let pruned = id - first;
+        env.storage().persistent().set(&HistoryKey::HistoryStart, &id);
+        if cleared(&env) == first {
+            env.storage().persistent().set(&HistoryKey::HistoryCleared, &id);
+        }
+        Ok(pruned)
+    }
+
+    /// Limit the log to the latest capacity entries, 0 for no limit (owner only)
+    pub fn set_history_capacity(env: Env, caller: Address, capacity: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        // Entries beyond the new capacity are dropped by moving the start, new adjustments delete them
+        if capacity > 0 {
+            let kept_from = size(&env).saturating_sub(capacity.into());
+            if kept_from > start(&env) {
+                env.storage().persistent().set(&HistoryKey::HistoryStart, &kept_from);
+            }
+        }
+        env.storage().persistent().set(&HistoryKey::HistoryCapacity, &capacity);
+        Ok(())
+    }
+
+    // Get how many entries the log holds (0 means no limit)
+    pub fn get_history_capacity(env: Env) -> u32 {
+        env.storage().persistent().get(&HistoryKey::HistoryCapacity).unwrap_or(0)
+    }
+
+    // Get the retention window in ledgers
+    pub fn get_history_retention(env: Env) -> u32 {
+        retention(&env)
+    }
+
+    // Get the id of the oldest log entry that has not been pruned
+    pub fn get_history_start(env: Env) -> u64 {
+        start(&env)
+    }
+
+    // Get the receipt of an adjustment, if it is still in the log
+    pub fn get_history_entry(env: Env, id: u64) -> Option<Receipt> {
+        entry(&env, id)
+    }
+
+    // Get up to limit (at most 50) entries of an operator's adjustments, from its start-th on, oldest first
+    pub fn get_history_by_operator(env: Env, address: Address, start: u32, limit: u32) -> Vec<Receipt> {
+        // Adjustments made under earlier keys of a rotated operator come first
+        let mut addresses = Vec::new(&env);
+        #[cfg(feature = "rotation")]
+        for prior in rotation::previous(&env, &address).iter() {
+            addresses.push_front(prior);
+        }
+        addresses.push_back(address);
+
+        let mut entries = Vec::new(&env);
+        let mut skip = start;
+        let mut remaining = limit.min(MAX_PAGE);
+        for address in addresses.iter() {
+            let count = operator_entries(&env, &address);
+            let from = skip.min(count);
+            skip -= from;
+            for position in from..count.min(from.saturating_add(remaining)) {
+                let id: u64 = env.storage().persistent().get(&HistoryKey::OperatorEntry(address.clone(), position)).unwrap();
+                if let Some(receipt) = entry(&env, id) {
+                    entries.push_back(receipt);
+                }
+                remaining -= 1;
+            }
+        }
+        entries
+    }
+
+    // Get up to limit (at most 50) entries made between two ledger timestamps in seconds (both included), oldest first
+    pub fn get_history_by_ledger_range(env: Env, from_timestamp: u64, to_timestamp: u64, limit: u32) -> Vec<Receipt> {
+        // First held entry at or after from_timestamp; every id between start and size is held
+        let (mut low, mut high) = (start(&env), size(&env));
+        while low < high {
+            let middle = low + (high - low) / 2;
+            match entry(&env, middle) {
+                Some(receipt) if receipt.timestamp < from_timestamp => low = middle + 1,
+                _ => high = middle,
+            }
+        }
+
+        let mut entries = Vec::new(&env);
+        let mut id = low;
+        while entries.len() < limit.min(MAX_PAGE) {
+            match entry(&env, id) {
+                Some(receipt) if receipt.timestamp <= to_timestamp => entries.push_back(receipt),
+                _ => break,
+            }
+            id += 1;
+        }
+        entries
+    }
+
+    // Get the Merkle root over every adjustment so far
+    pub fn get_history_root(env: Env) -> HistoryRoot {
+        let branch = branch(&env);
+        let size = size(&env);
+        let mut zero = BytesN::from_array(&env, &[0; 32]);
+        let mut node = zero.clone();
+        let mut index = size;
+        for level in 0..DEPTH {
+            node = if index & 1 == 1 {
+                hash_pair(&env, &branch.get_unchecked(level), &node)
+            } else {
+                hash_pair(&env, &node, &zero)
+            };
+            zero = hash_pair(&env, &zero, &zero);
+            index >>= 1;
+        }
+        HistoryRoot { root: node, size }
+    }
+
+    // Check that a receipt is part of the history, given its sibling hashes from the leaf up
+    pub fn verify_history_leaf(env: Env, receipt: Receipt, proof: Vec<BytesN<32>>) -> bool {
+        if proof.len() != DEPTH || receipt.id >= size(&env) {
+            return false;
+        }
+        let mut node = leaf(&env, &receipt);
+        for level in 0..DEPTH {
+            let sibling = proof.get_unchecked(level);
+            node = if (receipt.id >> level) & 1 == 1 {
+                hash_pair(&env, &sibling, &node)
+            } else {
+                hash_pair(&env, &node, &sibling)
+            };
+        }
+        node == Self::get_history_root(env.clone()).root
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::{xdr::ToXdr, Bytes, BytesN, Vec};
+
+    fn hash_pair(env: &Env, left: &BytesN<32>, right: &BytesN<32>) -> BytesN<32> {
+        let mut data: Bytes = left.clone().into();
+        data.append(&right.clone().into());
+        env.crypto().sha256(&data).into()
+    }
+
+    #[test]
+    fn test_history_inclusion_proof() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        let receipts = [
+            client.increase_budget(&operator, &100),
+            client.decrease_budget(&operator, &50),
+            client.increase_budget(&operator, &10),
+        ];
+        assert_eq!(client.get_history_entry(&1), Some(receipts[1].clone()));
+        assert_eq!(client.get_history_root().size, 3);
+
+        // Proof for leaf 1: leaf 0, then hash(leaf 2, zero), then the empty subtrees above
+        let leaves: Vec<BytesN<32>> = Vec::from_array(
+            &env,
+            [0, 1, 2].map(|i| env.crypto().sha256(&receipts[i].clone().to_xdr(&env)).into()),
+        );
+        let mut zero = BytesN::from_array(&env, &[0; 32]);
+        let mut proof = Vec::new(&env);
+        proof.push_back(leaves.get_unchecked(0));
+        proof.push_back(hash_pair(&env, &leaves.get_unchecked(2), &zero));
+        for level in 0..32 {
+            if level >= 2 {
+                proof.push_back(zero.clone());
+            }
+            zero = hash_pair(&env, &zero, &zero);
+        }
+        assert!(client.verify_history_leaf(&receipts[1], &proof));
+
+        let mut forged = receipts[1].clone();
+        forged.new += 1;
+        assert!(!client.verify_history_leaf(&forged, &proof));
+    }
+
+    #[test]
+    fn test_prune_respects_retention() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_history_retention(&owner, &100);
+
+        client.increase_budget(&operator, &100);
+        client.increase_budget(&operator, &100);
+        env.ledger().with_mut(|l| l.sequence_number = 50);
+        client.increase_budget(&operator, &100);
+        let root = client.get_history_root();
+
+        // Only the first two are older than the retention window
+        env.ledger().with_mut(|l| l.sequence_number = 120);
+        assert_eq!(client.prune_history(&owner, &3), 2);
+        assert_eq!(client.get_history_entry(&1), None);
+        assert!(client.get_history_entry(&2).is_some());
+        assert_eq!(client.get_history_start(), 2);
+
+        // Aggregates survive pruning
+        assert_eq!(client.get_history_root(), root);
+        assert_eq!(client.get_operator_stats(&operator).op_count, 3);
+    }
+
+    #[test]
+    fn test_history_ring_buffer() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        for _ in 0..3 {
+            client.increase_budget(&operator, &10);
+        }
+
+        // Shrinking keeps the newest entries
+        client.set_history_capacity(&owner, &2);
+        assert_eq!(client.get_history_start(), 1);
+        assert_eq!(client.get_history_entry(&0), None);
+        assert_eq!(client.get_history_entry(&2).unwrap().new, 1030);
+
+        // New adjustments drop the oldest entry, ids keep counting up
+        let receipt = client.increase_budget(&operator, &10);
+        assert_eq!(receipt.id, 3);
+        assert_eq!(client.get_history_entry(&3), Some(receipt));
+        assert_eq!(client.get_history_entry(&1), None);
+        assert_eq!(client.get_history_start(), 2);
+        assert_eq!(client.get_history_root().size, 4);
+
+        // A smaller capacity drops entries at once, new adjustments delete them a few at a time
+        let stored = |id: u64| {
+            env.as_contract(&contract_id, || env.storage().persistent().has(&history::HistoryKey::HistoryEntry(id)))
+        };
+        assert!(!stored(0) && !stored(1));
+        client.set_history_capacity(&owner, &0);
+        for _ in 0..5 {
+            client.increase_budget(&operator, &10);
+        }
+        client.set_history_capacity(&owner, &1);
+        assert_eq!(client.get_history_start(), 8);
+        assert_eq!(client.get_history_entry(&7), None);
+        assert!(stored(7));
+        client.increase_budget(&operator, &10);
+        assert!(!stored(2) && !stored(3) && stored(4));
+
+        // Growing again keeps what is left
+        client.set_history_capacity(&owner, &0);
+        client.increase_budget(&operator, &10);
+        assert!(client.get_history_entry(&9).is_some());
+        assert!(client.get_history_entry(&10).is_some());
+    }
+
+    #[test]
+    fn test_history_by_operator() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let alice = Address::generate(&env);
+        let bob = Address::generate(&env);
+        let alice_new = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        for operator in [&alice, &bob] {
+            client.add_operator(&owner, operator);
+            client.accept_operator(operator);
+        }
+        client.increase_budget(&alice, &10);
+        client.increase_budget(&bob, &20);
+        client.increase_budget(&alice, &30);
+        client.rotate_operator_key(&alice, &alice_new);
+        client.increase_budget(&alice_new, &40);
+        client.increase_budget(&bob, &50);
+
+        // Entries under the rotated away key come first
+        let ids = |entries: Vec<Receipt>| entries.iter().map(|receipt| receipt.id).collect::<std::vec::Vec<_>>();
+        assert_eq!(ids(client.get_history_by_operator(&alice_new, &0, &10)), [0, 2, 3]);
+        assert_eq!(ids(client.get_history_by_operator(&alice_new, &1, &1)), [2]);
+        assert_eq!(ids(client.get_history_by_operator(&alice_new, &2, &10)), [3]);
+        assert_eq!(ids(client.get_history_by_operator(&bob, &0, &10)), [1, 4]);
+        assert_eq!(client.get_history_by_operator(&bob, &5, &10).len(), 0);
+
+        // Pruned entries are skipped
+        client.prune_history(&owner, &1);
+        assert_eq!(ids(client.get_history_by_operator(&alice_new, &0, &10)), [2, 3]);
+
+        // A deputy's adjustments show up for the deputy and for the operator it acted for
+        let deputy = Address::generate(&env);
+        client.delegate_operator(&bob, &deputy, &100);
+        client.increase_budget(&deputy, &60);
+        assert_eq!(ids(client.get_history_by_operator(&bob, &0, &10)), [1, 4, 5]);
+        assert_eq!(ids(client.get_history_by_operator(&deputy, &0, &10)), [5]);
+    }
+
+    #[test]
+    fn test_history_by_ledger_range() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        for (ledger, timestamp) in [(10, 1000), (20, 2000), (20, 2000), (30, 3000), (40, 4000)] {
+            env.ledger().with_mut(|l| {
+                l.sequence_number = ledger;
+                l.timestamp = timestamp;
+            });
+            client.increase_budget(&operator, &10);
+        }
+
+        let ids = |entries: Vec<Receipt>| entries.iter().map(|receipt| receipt.id).collect::<std::vec::Vec<_>>();
+        assert_eq!(ids(client.get_history_by_ledger_range(&1500, &3000, &10)), [1, 2, 3]);
+        assert_eq!(ids(client.get_history_by_ledger_range(&2000, &2000, &10)), [1, 2]);
+        assert_eq!(ids(client.get_history_by_ledger_range(&0, &10000, &2)), [0, 1]);
+        assert_eq!(client.get_history_by_ledger_range(&4001, &10000, &10).len(), 0);
+        assert_eq!(client.get_history_by_ledger_range(&3000, &2000, &10).len(), 0);
+    }
+}
@@ -0,0 +1,109 @@
+// Hook/callback registry for budget changes.
+// The owner registers contracts implementing BudgetHook. After every successful budget change
+// each registered hook is called with the old value, the new value and who made the change.
+// A failing hook fails the whole change, so only register contracts you trust to accept it.
+
+use soroban_sdk::{contractclient, contractimpl, contracttype, Address, Env, Vec};
+
+use crate::{require_owner, BudgetError, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient};
+
+
+// Interface downstream contracts implement to be notified of budget changes.
+#[contractclient(name = "BudgetHookClient")]
+pub trait BudgetHook {
+    fn on_budget_changed(env: Env, old: i128, new: i128, caller: Address);
+}
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum HookKey {
+    Hooks,
+}
+// Hooks stores the list of registered hook contracts
+
+
+// Every hook costs a cross-contract call on each adjustment, so the list stays small.
+const MAX_HOOKS: u32 = 5;
+
+fn read_hooks(env: &Env) -> Vec<Address> {
+    env.storage().persistent().get(&HookKey::Hooks).unwrap_or(Vec::new(env))
+}
+
+// Called after every successful budget change.
+pub(crate) fn notify(env: &Env, old: i128, new: i128, caller: &Address) {
+    for hook in read_hooks(env).iter() {
+        BudgetHookClient::new(env, &hook).on_budget_changed(&old, &new, caller);
+    }
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Register a hook contract (owner only)
+    pub fn add_hook(env: Env, caller: Address, hook: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+
+        let mut hooks = read_hooks(&env);
+        if hooks.contains(&hook) {
+            return Err(BudgetError::AlreadyRegistered);
+        }
+        if hooks.len() >= MAX_HOOKS {
+            return Err(BudgetError::InvalidConfig);
+        }
+
+        hooks.push_back(hook);
+        env.storage().persistent().set(&HookKey::Hooks, &hooks);
+        Ok(())
+    }
+
+    /// Unregister a hook contract (owner only)
+    pub fn remove_hook(env: Env, caller: Address, hook: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+
+        let mut hooks = read_hooks(&env);
+        let index = hooks.first_index_of(&hook).ok_or(BudgetError::NotRegistered)?;
+        hooks.remove(index);
+        env.storage().persistent().set(&HookKey::Hooks, &hooks);
+        Ok(())
+    }
+
+    // Get the registered hook contracts
+    pub fn get_hooks(env: Env) -> Vec<Address> {
+        read_hooks(&env)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+
+    #[test]
+    fn test_hook_notified() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+        let hook = mocks::register_hook(&env);
+        let hook_id = hook.address.clone();
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.add_hook(&owner, &hook_id);
+        assert_eq!(client.try_add_hook(&owner, &hook_id), Err(Ok(BudgetError::AlreadyRegistered)));
+
+        client.increase_budget(&operator, &500);
+
+        assert_eq!(hook.last_change(), Some((1000, 1500, operator)));
+
+        client.remove_hook(&owner, &hook_id);
+        assert_eq!(client.get_hooks().len(), 0);
+    }
+}
@@ -0,0 +1,90 @@
+// Idempotency keys for retried adjustments.
+// Wallets retry submissions they did not see confirmed. The keyed adjustment entrypoints take an
+// op_key chosen by the caller (e.g. a hash of the wallet's request id); the first successful call
+// with a key is applied and its receipt stored, and every later call by the same caller with the
+// same key returns that receipt without applying anything again. Failed calls store nothing, so
+// they can be retried with the same key. Apart from that they behave like increase_budget and
+// decrease_budget.
+
+use soroban_sdk::{contractimpl, contracttype, Address, BytesN, Env};
+
+use crate::{BudgetAllocator, BudgetError, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient, Receipt};
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum IdempotencyKey {
+    OpKey(Address, BytesN<32>),
+}
+// OpKey stores the Receipt of the adjustment a caller made with an op_key
+
+
+fn once(
+    env: &Env,
+    caller: &Address,
+    op_key: BytesN<32>,
+    apply: impl FnOnce() -> Result<Receipt, BudgetError>,
+) -> Result<Receipt, BudgetError> {
+    let key = IdempotencyKey::OpKey(caller.clone(), op_key);
+    if let Some(receipt) = env.storage().persistent().get(&key) {
+        return Ok(receipt);
+    }
+    let receipt = apply()?;
+    env.storage().persistent().set(&key, &receipt);
+    Ok(receipt)
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Increase the budget at most once per op_key (operators only)
+    pub fn increase_budget_with_key(env: Env, caller: Address, amount: i128, op_key: BytesN<32>) -> Result<Receipt, BudgetError> {
+        once(&env, &caller, op_key, || Self::increase_budget(env.clone(), caller.clone(), amount))
+    }
+
+    /// Decrease the budget at most once per op_key (operators only)
+    pub fn decrease_budget_with_key(env: Env, caller: Address, amount: i128, op_key: BytesN<32>) -> Result<Receipt, BudgetError> {
+        once(&env, &caller, op_key, || Self::decrease_budget(env.clone(), caller.clone(), amount))
+    }
+
+    // Get the receipt of the adjustment a caller made with an op_key, if any
+    pub fn get_keyed_receipt(env: Env, caller: Address, op_key: BytesN<32>) -> Option<Receipt> {
+        env.storage().persistent().get(&IdempotencyKey::OpKey(caller, op_key))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::BytesN;
+
+    #[test]
+    fn test_retry_returns_original_receipt() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+
+        let key = BytesN::from_array(&env, &[1; 32]);
+        let first = client.decrease_budget_with_key(&operator, &300, &key);
+        let retry = client.decrease_budget_with_key(&operator, &300, &key);
+        assert_eq!(first, retry);
+        assert_eq!(client.get_budget().current, 700);
+        assert_eq!(client.get_adjustment_count(), 1);
+
+        // A failed attempt does not use up its key
+        let other = BytesN::from_array(&env, &[2; 32]);
+        assert_eq!(client.try_decrease_budget_with_key(&operator, &5000, &other), Err(Ok(BudgetError::BelowMin)));
+        assert_eq!(client.decrease_budget_with_key(&operator, &200, &other).new, 500);
+    }
+}
@@ -0,0 +1,118 @@
+// Property tests over random operation sequences.
+// Unit tests exercise one feature at a time; these apply random sequences of operator changes and
+// budget adjustments, made by operators and non-operators alike, and check after every step that:
+// current stays within min and max, a call by a non-operator or non-owner never changes anything,
+// and the history log adds up to the budget (every receipt continues where the previous one ended
+// and the receipts together account for the whole change since initialize).
+
+extern crate std;
+
+use proptest::prelude::*;
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{Address, Env, Vec};
+
+use crate::{GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient};
+
+
+// Actors 0..OPERATOR_POOL can be made operators, the last one never is; the owner is separate.
+const OPERATOR_POOL: usize = 3;
+const ACTORS: usize = OPERATOR_POOL + 1;
+
+const INITIAL: i128 = 1000;
+const MIN: i128 = 200;
+const MAX: i128 = 5000;
+
+#[derive(Clone, Debug)]
+enum Op {
+    AddOperator(usize, bool),
+    RemoveOperator(usize, bool),
+    Increase(usize, i128),
+    Decrease(usize, i128),
+}
+// The bool says whether the owner makes the operator change; otherwise another actor tries to
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        (0..OPERATOR_POOL, any::<bool>()).prop_map(|(actor, by_owner)| Op::AddOperator(actor, by_owner)),
+        (0..OPERATOR_POOL, any::<bool>()).prop_map(|(actor, by_owner)| Op::RemoveOperator(actor, by_owner)),
+        (0..ACTORS, -50i128..2500).prop_map(|(actor, amount)| Op::Increase(actor, amount)),
+        (0..ACTORS, -50i128..2500).prop_map(|(actor, amount)| Op::Decrease(actor, amount)),
+    ]
+}
+
+fn run(ops: std::vec::Vec<Op>) {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+    let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+    let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let mut actors = Vec::new(&env);
+    for _ in 0..ACTORS {
+        actors.push_back(Address::generate(&env));
+    }
+    client.initialize(&owner, &INITIAL, &MIN, &MAX);
+
+    for op in ops {
+        let before = client.get_budget();
+        let operators_before = client.get_operators();
+        let count_before = client.get_adjustment_count();
+
+        match op {
+            Op::AddOperator(actor, by_owner) => {
+                let actor = actors.get_unchecked(actor as u32);
+                let caller = if by_owner { owner.clone() } else { actor.clone() };
+                let added = client.try_add_operator(&caller, &actor).is_ok() && client.try_accept_operator(&actor).is_ok();
+                assert!(by_owner || !added);
+            }
+            Op::RemoveOperator(actor, by_owner) => {
+                let actor = actors.get_unchecked(actor as u32);
+                let caller = if by_owner { owner.clone() } else { actor.clone() };
+                let removed = client.try_remove_operator(&caller, &actor).is_ok();
+                assert!(by_owner || !removed);
+            }
+            Op::Increase(actor, amount) | Op::Decrease(actor, amount) => {
+                let actor = actors.get_unchecked(actor as u32);
+                let result = match op {
+                    Op::Increase(..) => client.try_increase_budget(&actor, &amount),
+                    _ => client.try_decrease_budget(&actor, &amount),
+                };
+                if !operators_before.contains(&actor) {
+                    assert!(result.is_err());
+                }
+            }
+        }
+
+        // Limits always hold and are never touched by these calls
+        let after = client.get_budget();
+        assert!(after.min <= after.current && after.current <= after.max);
+        assert_eq!((after.min, after.max), (MIN, MAX));
+
+        // A failed or non-adjusting call leaves the budget as it was
+        if client.get_adjustment_count() == count_before {
+            assert_eq!(after, before);
+        }
+    }
+
+    // The receipts chain up from the initial value to the current one
+    let mut value = INITIAL;
+    for id in 0..client.get_adjustment_count() {
+        let receipt = client.get_history_entry(&id).unwrap();
+        assert_eq!(receipt.id, id);
+        assert_eq!(receipt.previous, value);
+        assert_ne!(receipt.caller, actors.get_unchecked(OPERATOR_POOL as u32));
+        value = receipt.new;
+    }
+    assert_eq!(value, client.get_budget().current);
+}
+
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn test_random_sequences_keep_invariants(ops in prop::collection::vec(op(), 1..40)) {
+        run(ops);
+    }
+}
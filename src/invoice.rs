@@ -0,0 +1,156 @@
+// Claim-code invoices.
+// An operator can approve a payment before knowing who will receive it: create_invoice stores the
+// sha256 hash of a secret claim code together with the amount and an expiry ledger. Whoever is
+// given the code calls redeem_invoice with it and the address to pay before the expiry; only then
+// is the budget decreased, on behalf of the operator that created the invoice (which therefore
+// still has to be an operator, possibly under a rotated key), and in token-backed mode the amount
+// transferred.
+// The code is revealed in the redeeming transaction, so it must reach the recipient privately and
+// a new code has to be used for every invoice.
+
+use soroban_sdk::{contractimpl, contracttype, token, Address, Bytes, BytesN, Env, String};
+
+use crate::{
+    apply_decrease, exempt_from_veto, require_operator, rotation, treasury, BudgetError, GovernanceBudgetAllocator,
+    GovernanceBudgetAllocatorClient,
+};
+#[cfg(feature = "payee")]
+use crate::payee;
+#[cfg(feature = "policy")]
+use crate::policy;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Invoice {
+    pub caller: Address,
+    pub amount: i128,
+    pub expiry: u32,
+}
+// Invoice stores an open invoice:
+// caller is the operator that created it
+// expiry is the last ledger it can be redeemed in
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum InvoiceKey {
+    Invoice(BytesN<32>),
+}
+// Invoice stores an open Invoice by the hash of its claim code, until it is redeemed or cancelled
+
+
+fn read_invoice(env: &Env, code_hash: &BytesN<32>) -> Result<Invoice, BudgetError> {
+    env.storage().persistent().get(&InvoiceKey::Invoice(code_hash.clone())).ok_or(BudgetError::NotRegistered)
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Open an invoice payable to whoever presents the claim code hashing to code_hash (operators only)
+    pub fn create_invoice(env: Env, caller: Address, code_hash: BytesN<32>, amount: i128, expiry: u32) -> Result<(), BudgetError> {
+        require_operator(&env, &caller)?;
+        if amount <= 0 {
+            return Err(BudgetError::InvalidAmount);
+        }
+        if expiry < env.ledger().sequence() {
+            return Err(BudgetError::InvalidConfig);
+        }
+        let key = InvoiceKey::Invoice(code_hash);
+        if env.storage().persistent().has(&key) {
+            return Err(BudgetError::AlreadyRegistered);
+        }
+        env.storage().persistent().set(&key, &Invoice { caller, amount, expiry });
+        Ok(())
+    }
+
+    /// Redeem an invoice with its claim code and pay it to recipient (anyone holding the code), returns the amount
+    pub fn redeem_invoice(env: Env, preimage: Bytes, recipient: Address) -> Result<i128, BudgetError> {
+        let code_hash: BytesN<32> = env.crypto().sha256(&preimage).into();
+        let invoice = read_invoice(&env, &code_hash)?;
+        if env.ledger().sequence() > invoice.expiry {
+            return Err(BudgetError::ActionExpired);
+        }
+        env.storage().persistent().remove(&InvoiceKey::Invoice(code_hash));
+
+        #[cfg(feature = "payee")]
+        payee::charge(&env, &recipient, invoice.amount)?;
+        #[cfg(feature = "policy")]
+        policy::check_recipient(&env, &recipient, invoice.amount)?;
+        let caller = rotation::current(&env, &invoice.caller);
+        let receipt = apply_decrease(&env, &caller, invoice.amount)?;
+
+        if let Ok(token) = treasury::backing_token(&env) {
+            exempt_from_veto(&env, &receipt);
+            let net = treasury::net_of_fee(&env, invoice.amount, Some(&token));
+            token::Client::new(&env, &token).transfer(&env.current_contract_address(), &recipient, &net);
+            treasury::log_payment(&env, &caller, &recipient, net, String::from_str(&env, "invoice"));
+            return Ok(net);
+        }
+        Ok(invoice.amount)
+    }
+
+    /// Cancel an open invoice (its creator only)
+    pub fn cancel_invoice(env: Env, caller: Address, code_hash: BytesN<32>) -> Result<(), BudgetError> {
+        caller.require_auth();
+        let invoice = read_invoice(&env, &code_hash)?;
+        if !rotation::is_same(&env, &invoice.caller, &caller) {
+            return Err(BudgetError::NotOperator);
+        }
+        env.storage().persistent().remove(&InvoiceKey::Invoice(code_hash));
+        Ok(())
+    }
+
+    // Get an open invoice by the hash of its claim code
+    pub fn get_invoice(env: Env, code_hash: BytesN<32>) -> Option<Invoice> {
+        env.storage().persistent().get(&InvoiceKey::Invoice(code_hash))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::{token, Bytes, BytesN};
+
+    #[test]
+    fn test_invoice_redeem() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let recipient = Address::generate(&env);
+        let sac = env.register_stellar_asset_contract_v2(owner.clone());
+        let backing = token::Client::new(&env, &sac.address());
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        token::StellarAssetClient::new(&env, &sac.address()).mint(&contract_id, &1000);
+        client.set_token(&owner, &sac.address());
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+
+        let code = Bytes::from_array(&env, b"claim-code-1");
+        let code_hash: BytesN<32> = env.crypto().sha256(&code).into();
+        client.create_invoice(&operator, &code_hash, &300, &50);
+        assert_eq!(client.get_budget().current, 1000);
+
+        // A wrong code finds nothing; the right one pays once
+        let wrong = Bytes::from_array(&env, b"guess");
+        assert_eq!(client.try_redeem_invoice(&wrong, &recipient), Err(Ok(BudgetError::NotRegistered)));
+        assert_eq!(client.redeem_invoice(&code, &recipient), 300);
+        assert_eq!(backing.balance(&recipient), 300);
+        assert_eq!(client.get_budget().current, 700);
+        assert_eq!(client.try_redeem_invoice(&code, &recipient), Err(Ok(BudgetError::NotRegistered)));
+
+        // Expired invoices cannot be redeemed
+        let late = Bytes::from_array(&env, b"claim-code-2");
+        client.create_invoice(&operator, &env.crypto().sha256(&late).into(), &100, &50);
+        env.ledger().with_mut(|l| l.sequence_number = 51);
+        assert_eq!(client.try_redeem_invoice(&late, &recipient), Err(Ok(BudgetError::ActionExpired)));
+    }
+}
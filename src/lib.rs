@@ -1,5 +1,7 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, contracterror, Address, Env, Vec};
+// Helpers shared by several feature modules go unused in builds with only some of the features.
+#![cfg_attr(not(feature = "full"), allow(dead_code, unused_imports, unused_mut, unused_variables))]
+use soroban_sdk::{contract, contractimpl, contractmeta, contracttype, Address, Env, String, Symbol, Vec};
 
 // #![no_std] means the contract does not use Rust’s standard library, which is required for Soroban.
 // soroban_sdk provides types and macros needed for writing a smart contract, accessing storage, handling authentication, and working with addresses and vectors.
@@ -9,16 +11,15 @@ use soroban_sdk::{contract, contractimpl, contracttype, contracterror, Address,
 
 #[derive(Clone, Debug, PartialEq)]
 #[contracttype]
-pub struct BudgetState {
-    pub current: i128,
-    pub min: i128,
-    pub max: i128,
+pub struct BudgetMeta {
+    pub last_modified_ledger: u32,
+    pub last_modified_by: Option<Address>,
+    pub last_op_id: Option<u64>,
 }
-// BudgetState stores the budget data:
-// current is the current budget value
-// min is the lower limit
-// max is the upper limit
-// This struct represents the main financial state stored on-chain.
+// BudgetMeta tells how fresh the budget is:
+// last_modified_ledger is the ledger of the last change of the budget
+// last_modified_by and last_op_id are the caller and id of the last change when it was an adjustment,
+// None when it was a limit change or an automatic change (schedule, decay, refill, veto)
 
 
 
@@ -30,38 +31,49 @@ pub enum DataKey {
     Owner,
     Operators,
     Budget,
+    AdjustmentCount,
+    EpochLength,
+    Paused,
+    Decommissioned,
+    Nonce(Address),
+    Admin,
+    Granularity,
+    MaxOperators,
+    BudgetMeta,
+    Nominated(Address),
+    OperatorLock,
+    OperatorUntil(Address),
+    OwnerOperator,
 }
-// DataKey defines keys used for persistent storage:
+// DataKey defines keys used for contract storage. Owner, Paused, Budget and Granularity are needed
+// on almost every call and live in instance storage; the other keys are persistent:
 // Owner stores the owner address
 // Operators stores the list of operators
 // Budget stores the BudgetState
+// AdjustmentCount stores how many adjustments have been applied (used as the next adjustment id)
+// EpochLength stores the epoch length in ledgers (missing or 0 means epochs are disabled)
+// Paused stores whether budget adjustments are currently blocked
+// Decommissioned is set once the contract has been retired and never cleared
+// Nonce stores the next nonce expected in a signed operation of an address
+// Admin stores the optional admin, who may manage operators but nothing else
+// Granularity stores the step every adjustment amount must be a multiple of (missing or 0 means any amount)
+// MaxOperators stores the maximum size of the operators list (missing or 0 means no cap)
+// BudgetMeta stores the BudgetMeta of the last budget change
+// Nominated marks an address nominated as operator that has not accepted yet
+// OperatorLock stores the last ledger the operators list is locked for (missing means unlocked)
+// OperatorUntil stores the last ledger an operator is listed for (u32::MAX unless its removal is scheduled)
+// OwnerOperator stores whether the owner may adjust the budget without being listed (missing means no)
+// Feature modules keep their own key enums; variant names must stay unique across all of them
+// because a unit variant is stored as just its name.
 
 
 
 
 
-#[contracterror]
-#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
-#[repr(u32)]
-pub enum BudgetError {
-    NotOwner = 1,
-    NotOperator = 2,
-    AlreadyOperator = 3,
-    NotOperatorFound = 4,
-    Overflow = 5,
-    Underflow = 6,
-    ExceedsMax = 7,
-    BelowMin = 8,
-    InvalidLimits = 9,
-}
-
-// BudgetError defines all failure cases:
-// Access control errors
-// Arithmetic errors
-// Budget limit violations
-// Invalid initialization
-// Each error has a numeric value so failures are deterministic and testable.
-
+// Metadata embedded in the wasm, readable without calling the contract (the SDK adds the rustc
+// and SDK versions it was built with). get_version returns the same at runtime.
+contractmeta!(key = "version", val = "0.1.0");
+contractmeta!(key = "interface", val = "1");
 
 
 #[contract]
@@ -69,19 +81,782 @@ pub struct GovernanceBudgetAllocator;
 // This declares the contract type.
 // All callable contract functions are implemented for this struct.
 
+mod admin;
+#[cfg(feature = "alerts")]
+mod alerts;
+#[cfg(feature = "anomaly")]
+mod anomaly;
+#[cfg(feature = "authorizer")]
+mod authorizer;
+#[cfg(feature = "badge")]
+mod badge;
+#[cfg(feature = "bands")]
+mod bands;
+#[cfg(feature = "blacklist")]
+mod blacklist;
+#[cfg(feature = "breaker")]
+mod breaker;
+#[cfg(feature = "category")]
+mod category;
+#[cfg(feature = "consumer")]
+mod consumer;
+#[cfg(feature = "cosign")]
+mod cosign;
+#[cfg(feature = "decay")]
+mod decay;
+#[cfg(feature = "deficit")]
+mod deficit;
+#[cfg(feature = "delegation")]
+mod delegation;
+#[cfg(feature = "deposit")]
+mod deposit;
+#[cfg(feature = "deputy")]
+mod deputy;
+#[cfg(feature = "digest")]
+mod digest;
+#[cfg(feature = "donation")]
+mod donation;
+#[cfg(feature = "emergency")]
+mod emergency;
+#[cfg(feature = "escrow")]
+mod escrow;
+mod events;
+#[cfg(feature = "explain")]
+mod explain;
+#[cfg(feature = "export")]
+mod export;
+#[cfg(feature = "factory")]
+mod factory;
+#[cfg(feature = "federation")]
+mod federation;
+#[cfg(feature = "fee")]
+mod fee;
+#[cfg(feature = "forecast")]
+mod forecast;
+#[cfg(feature = "history")]
+mod history;
+#[cfg(feature = "holding")]
+mod holding;
+#[cfg(feature = "hooks")]
+mod hooks;
+#[cfg(feature = "idempotency")]
+mod idempotency;
+#[cfg(feature = "invoice")]
+mod invoice;
+#[cfg(feature = "matching")]
+mod matching;
+#[cfg(feature = "migration")]
+mod migration;
+#[cfg(feature = "optimistic")]
+mod optimistic;
+#[cfg(feature = "oracle")]
+mod oracle;
+#[cfg(feature = "payee")]
+mod payee;
+#[cfg(feature = "policy")]
+mod policy;
+#[cfg(feature = "proposal")]
+mod proposal;
+#[cfg(feature = "queue")]
+mod queue;
+#[cfg(feature = "ratelimit")]
+mod ratelimit;
+#[cfg(feature = "reconfigure")]
+mod reconfigure;
+#[cfg(feature = "recovery")]
+mod recovery;
+#[cfg(feature = "refill")]
+mod refill;
+#[cfg(feature = "removal")]
+mod removal;
+#[cfg(feature = "report")]
+mod report;
+#[cfg(feature = "reserve")]
+mod reserve;
+#[cfg(feature = "roles")]
+mod roles;
+#[cfg(feature = "rotation")]
+mod rotation;
+#[cfg(feature = "schedule")]
+mod schedule;
+#[cfg(feature = "sealed")]
+mod sealed;
+#[cfg(feature = "signed")]
+mod signed;
+#[cfg(feature = "snapshot")]
+mod snapshot;
+#[cfg(feature = "solvency")]
+mod solvency;
+#[cfg(feature = "stake")]
+mod stake;
+#[cfg(feature = "stats")]
+mod stats;
+#[cfg(feature = "tags")]
+mod tags;
+#[cfg(feature = "tiers")]
+mod tiers;
+#[cfg(feature = "token_vote")]
+mod token_vote;
+#[cfg(feature = "transfer")]
+mod transfer;
+#[cfg(feature = "treasury")]
+mod treasury;
+#[cfg(feature = "vault")]
+mod vault;
+#[cfg(feature = "vesting")]
+mod vesting;
+#[cfg(feature = "veto")]
+mod veto;
+#[cfg(feature = "voting")]
+mod voting;
+#[cfg(feature = "watchdog")]
+mod watchdog;
+
+#[cfg(any(test, feature = "testutils"))]
+pub mod mocks;
+#[cfg(any(test, feature = "testutils"))]
+pub mod testutils;
+#[cfg(test)]
+mod bench;
+#[cfg(test)]
+mod invariants;
+#[cfg(test)]
+mod model;
+
+pub use governance_budget_interface::{
+    BudgetAllocator, BudgetAllocatorClient, BudgetError, BudgetState, BudgetTreasury, BudgetTreasuryClient,
+    ContractVersion, LimitCheck, Receipt, INTERFACE_VERSION,
+};
+pub use admin::{AdminAction, PendingAction};
+#[cfg(feature = "alerts")]
+pub use alerts::{AlertSubscriber, AlertSubscriberClient, BudgetAlert};
+#[cfg(feature = "anomaly")]
+pub use anomaly::SizeStats;
+#[cfg(feature = "authorizer")]
+pub use authorizer::{Authorizer, AuthorizerClient, AuthorizerConfig, AuthorizerMode};
+#[cfg(feature = "badge")]
+pub use badge::{BadgeNft, BadgeNftClient};
+#[cfg(feature = "bands")]
+pub use bands::{Approval, ApprovalBand, BandRequest};
+#[cfg(feature = "breaker")]
+pub use breaker::BreakerConfig;
+#[cfg(feature = "category")]
+pub use category::Category;
+#[cfg(feature = "cosign")]
+pub use cosign::PendingDecrease;
+#[cfg(feature = "decay")]
+pub use decay::DecayPolicy;
+#[cfg(feature = "deposit")]
+pub use deposit::Deposit;
+#[cfg(feature = "deputy")]
+pub use deputy::Deputy;
+#[cfg(feature = "donation")]
+pub use donation::Donation;
+#[cfg(feature = "emergency")]
+pub use emergency::Direction;
+#[cfg(feature = "escrow")]
+pub use escrow::{Payout, PayoutStatus};
+pub use events::EVENT_SCHEMA_VERSION;
+#[cfg(feature = "breaker")]
+pub use events::AlarmEvent;
+#[cfg(feature = "alerts")]
+pub use events::AlertFailEvent;
+#[cfg(feature = "anomaly")]
+pub use events::AnomalyEvent;
+#[cfg(feature = "deficit")]
+pub use events::DeficitEvent;
+#[cfg(feature = "treasury")]
+pub use events::DepositEvent;
+#[cfg(any(feature = "donation", feature = "matching"))]
+pub use events::DonationEvent;
+#[cfg(feature = "emergency")]
+pub use events::EmergencyEvent;
+#[cfg(feature = "fee")]
+pub use events::FeeEvent;
+#[cfg(feature = "rotation")]
+pub use events::RotatedEvent;
+#[cfg(feature = "stake")]
+pub use events::SlashEvent;
+#[cfg(feature = "treasury")]
+pub use events::SpendEvent;
+#[cfg(feature = "solvency")]
+pub use events::UnbackedEvent;
+#[cfg(feature = "explain")]
+pub use explain::Explanation;
+#[cfg(feature = "export")]
+pub use export::{CoreState, StateChunk};
+#[cfg(feature = "factory")]
+pub use factory::{AllocatorFactory, AllocatorFactoryClient, FactoryError};
+#[cfg(feature = "federation")]
+pub use federation::ChildAllocation;
+#[cfg(feature = "fee")]
+pub use fee::FeeConfig;
+#[cfg(feature = "history")]
+pub use history::HistoryRoot;
+#[cfg(feature = "holding")]
+pub use holding::HoldingRequirement;
+#[cfg(feature = "hooks")]
+pub use hooks::{BudgetHook, BudgetHookClient};
+#[cfg(feature = "invoice")]
+pub use invoice::Invoice;
+#[cfg(feature = "matching")]
+pub use matching::MatchingProgram;
+#[cfg(feature = "optimistic")]
+pub use optimistic::{Challenge, OptimisticConfig};
+#[cfg(feature = "oracle")]
+pub use oracle::{Asset, OracleConfig, PriceData, PriceOracle, PriceOracleClient};
+#[cfg(feature = "payee")]
+pub use payee::Payee;
+#[cfg(feature = "policy")]
+pub use policy::PolicyRule;
+#[cfg(feature = "proposal")]
+pub use proposal::{Proposal, ProposalAction, ProposalMetadata, ProposalStatus};
+#[cfg(feature = "queue")]
+pub use queue::QueuedAdjustment;
+#[cfg(feature = "ratelimit")]
+pub use ratelimit::RateLimit;
+#[cfg(feature = "reconfigure")]
+pub use reconfigure::ConfigUpdate;
+#[cfg(feature = "recovery")]
+pub use recovery::{GuardianConfig, Recovery};
+#[cfg(feature = "refill")]
+pub use refill::RefillPolicy;
+#[cfg(feature = "report")]
+pub use report::EpochReport;
+#[cfg(feature = "reserve")]
+pub use reserve::Reservation;
+#[cfg(feature = "roles")]
+pub use roles::RoleInfo;
+#[cfg(feature = "schedule")]
+pub use schedule::ScheduledLimits;
+#[cfg(feature = "sealed")]
+pub use sealed::SealedVoting;
+#[cfg(feature = "signed")]
+pub use signed::SignedAdjustment;
+#[cfg(feature = "snapshot")]
+pub use snapshot::Checkpoint;
+#[cfg(feature = "solvency")]
+pub use solvency::BackingReport;
+#[cfg(feature = "stats")]
+pub use stats::OperatorStats;
+#[cfg(feature = "tags")]
+pub use tags::TagTotal;
+#[cfg(feature = "tiers")]
+pub use tiers::{Tier, TierPolicy};
+#[cfg(feature = "token_vote")]
+pub use token_vote::{TokenTally, TokenVotingConfig, VotesToken, VotesTokenClient};
+#[cfg(feature = "treasury")]
+pub use treasury::Payment;
+#[cfg(feature = "vault")]
+pub use vault::{YieldVault, YieldVaultClient};
+#[cfg(feature = "vesting")]
+pub use vesting::Vesting;
+#[cfg(feature = "veto")]
+pub use veto::ProvisionalAdjustment;
+#[cfg(feature = "voting")]
+pub use voting::Tally;
+#[cfg(feature = "watchdog")]
+pub use watchdog::Watchdog;
+
+
+
+// Shared storage helpers used by the entrypoints below and by the feature modules.
+
+pub(crate) fn read_owner(env: &Env) -> Address {
+    env.storage().instance().get(&DataKey::Owner).unwrap()
+}
+
+pub(crate) fn write_owner(env: &Env, owner: &Address) {
+    env.storage().instance().set(&DataKey::Owner, owner);
+}
+
+// Reads the operators list after applying any removal whose grace period has ended.
+pub(crate) fn read_operators(env: &Env) -> Vec<Address> {
+    #[cfg(feature = "removal")]
+    removal::apply_due(env);
+    env.storage().persistent().get(&DataKey::Operators).unwrap()
+}
+
+// Checks membership with a single read of the operator's own key instead of scanning the list.
+pub(crate) fn is_listed(env: &Env, address: &Address) -> bool {
+    let until: Option<u32> = env.storage().persistent().get(&DataKey::OperatorUntil(address.clone()));
+    until.is_some_and(|until| env.ledger().sequence() <= until)
+}
+
+// Sets or clears the membership key; every change to the operators list has to go with one.
+pub(crate) fn set_listed(env: &Env, operator: &Address, listed: bool) {
+    let key = DataKey::OperatorUntil(operator.clone());
+    if listed {
+        env.storage().persistent().set(&key, &u32::MAX);
+    } else {
+        env.storage().persistent().remove(&key);
+    }
+}
+
+// Reads the budget as stored, without activating scheduled limit changes.
+pub(crate) fn stored_budget(env: &Env) -> BudgetState {
+    env.storage().instance().get(&DataKey::Budget).unwrap()
+}
+
+// Reads the budget after activating any scheduled limit change, decay and refill that is due.
+pub(crate) fn read_budget(env: &Env) -> BudgetState {
+    #[cfg(feature = "schedule")]
+    schedule::apply_due(env);
+    #[cfg(feature = "decay")]
+    decay::apply_due(env);
+    #[cfg(feature = "refill")]
+    refill::apply_due(env);
+    stored_budget(env)
+}
+
+// Every budget write goes through here so the epoch checkpoint sees the state being replaced.
+pub(crate) fn write_budget(env: &Env, budget: &BudgetState) {
+    #[cfg(feature = "snapshot")]
+    snapshot::on_budget_change(env);
+    #[cfg(any(feature = "deficit", feature = "report", feature = "alerts"))]
+    let previous = stored_budget(env).current;
+    #[cfg(feature = "deficit")]
+    deficit::on_change(env, previous, budget.current);
+    #[cfg(feature = "report")]
+    report::on_change(env, previous, budget.current);
+    env.storage().instance().set(&DataKey::Budget, budget);
+    #[cfg(feature = "alerts")]
+    alerts::on_change(env, previous, budget.current);
+    write_meta(env, None, None);
+}
+
+pub(crate) fn write_meta(env: &Env, by: Option<Address>, op_id: Option<u64>) {
+    let meta = BudgetMeta { last_modified_ledger: env.ledger().sequence(), last_modified_by: by, last_op_id: op_id };
+    env.storage().persistent().set(&DataKey::BudgetMeta, &meta);
+}
+
+pub(crate) fn epoch_length(env: &Env) -> u32 {
+    env.storage().persistent().get(&DataKey::EpochLength).unwrap_or(0)
+}
+
+// Epochs are consecutive blocks of EpochLength ledgers; None when epochs are disabled.
+pub(crate) fn current_epoch(env: &Env) -> Option<u32> {
+    epoch_at(env, env.ledger().sequence())
+}
+
+// The epoch a ledger falls in; None when epochs are disabled.
+pub(crate) fn epoch_at(env: &Env, ledger: u32) -> Option<u32> {
+    let length = epoch_length(env);
+    if length == 0 {
+        return None;
+    }
+    Some(ledger / length)
+}
+
+pub(crate) fn granularity(env: &Env) -> i128 {
+    env.storage().instance().get(&DataKey::Granularity).unwrap_or(0)
+}
+
+// Rejects amounts that are not a multiple of the configured granularity.
+pub(crate) fn check_granularity(env: &Env, amount: i128) -> Result<(), BudgetError> {
+    let step = granularity(env);
+    if step > 0 && amount % step != 0 {
+        return Err(BudgetError::InvalidGranularity);
+    }
+    Ok(())
+}
+
+// The lowest value a decrease may take current to: min (zero unless deficit mode is on) plus
+// whatever is reserved on top of it.
+pub(crate) fn floor(env: &Env, budget: &BudgetState) -> i128 {
+    #[cfg(feature = "deficit")]
+    let floor = deficit::floor(env, budget);
+    #[cfg(not(feature = "deficit"))]
+    let floor = budget.min.max(0);
+    #[cfg(feature = "reserve")]
+    let floor = floor.checked_add(reserve::reserved(env)).unwrap_or(i128::MAX);
+    floor
+}
+
+// The highest value an increase may take current to: max, or the part of it vested so far.
+pub(crate) fn cap(env: &Env, budget: &BudgetState) -> i128 {
+    #[cfg(feature = "vesting")]
+    { vesting::cap(env, budget) }
+    #[cfg(not(feature = "vesting"))]
+    { budget.max }
+}
+
+// The operator whose rights an address uses: its principal while it is an active deputy, else itself.
+pub(crate) fn acting_for(env: &Env, address: &Address) -> Address {
+    #[cfg(feature = "deputy")]
+    { deputy::acting_for(env, address) }
+    #[cfg(not(feature = "deputy"))]
+    { address.clone() }
+}
+
+pub(crate) fn max_operators(env: &Env) -> u32 {
+    env.storage().persistent().get(&DataKey::MaxOperators).unwrap_or(0)
+}
+
+// Checks that an address may join the operators list.
+pub(crate) fn check_can_join(env: &Env, operators: &Vec<Address>, operator: &Address) -> Result<(), BudgetError> {
+    // Blacklisted addresses can never become operators
+    #[cfg(feature = "blacklist")]
+    blacklist::check(env, operator)?;
+    check_eligible(env, operator)?;
+
+    // Prevents duplicate entries
+    if operators.contains(operator) {
+        return Err(BudgetError::AlreadyOperator);
+    }
+
+    // Enforce the operator cap, if any
+    let cap = max_operators(env);
+    if cap > 0 && operators.len() >= cap {
+        return Err(BudgetError::TooManyOperators);
+    }
+    Ok(())
+}
+
+// Fails while the operators list is locked against membership changes.
+pub(crate) fn check_operators_unlocked(env: &Env) -> Result<(), BudgetError> {
+    let until: u32 = env.storage().persistent().get(&DataKey::OperatorLock).unwrap_or(0);
+    if env.storage().persistent().has(&DataKey::OperatorLock) && env.ledger().sequence() <= until {
+        return Err(BudgetError::OperatorsLocked);
+    }
+    Ok(())
+}
+
+pub(crate) fn is_paused(env: &Env) -> bool {
+    env.storage().instance().get(&DataKey::Paused).unwrap_or(false)
+}
+
+pub(crate) fn set_paused(env: &Env, paused: bool) {
+    env.storage().instance().set(&DataKey::Paused, &paused);
+}
+
+pub(crate) fn require_not_paused(env: &Env) -> Result<(), BudgetError> {
+    if is_paused(env) {
+        return Err(BudgetError::Paused);
+    }
+    Ok(())
+}
+
+pub(crate) fn is_decommissioned(env: &Env) -> bool {
+    env.storage().persistent().has(&DataKey::Decommissioned)
+}
+
+// Authenticates the caller and checks that it is the owner.
+// Fails once the contract is decommissioned, which disables every owner-gated mutation.
+pub(crate) fn require_owner(env: &Env, caller: &Address) -> Result<(), BudgetError> {
+    if is_decommissioned(env) {
+        return Err(BudgetError::Decommissioned);
+    }
+    caller.require_auth();
+    // The watchdog fallback holds owner powers while the owner misses its heartbeat
+    if *caller != read_owner(env) && !is_acting_owner(env, caller) {
+        return Err(BudgetError::NotOwner);
+    }
+    Ok(())
+}
+
+// Whether an address holds the owner's powers in its place (see watchdog.rs).
+fn is_acting_owner(env: &Env, address: &Address) -> bool {
+    #[cfg(feature = "watchdog")]
+    { watchdog::is_acting_owner(env, address) }
+    #[cfg(not(feature = "watchdog"))]
+    { false }
+}
+
+pub(crate) fn read_admin(env: &Env) -> Option<Address> {
+    env.storage().persistent().get(&DataKey::Admin)
+}
+
+// Authenticates the caller and checks that it is the owner or the admin.
+// Only operator and payee management accept the admin; everything else stays owner-only.
+pub(crate) fn require_operator_manager(env: &Env, caller: &Address) -> Result<(), BudgetError> {
+    if is_decommissioned(env) {
+        return Err(BudgetError::Decommissioned);
+    }
+    caller.require_auth();
+    if *caller == read_owner(env) || Some(caller.clone()) == read_admin(env) || is_acting_owner(env, caller) {
+        return Ok(());
+    }
+    Err(BudgetError::NotOwner)
+}
+
+pub(crate) fn owner_operates(env: &Env) -> bool {
+    env.storage().persistent().get(&DataKey::OwnerOperator).unwrap_or(false)
+}
+
+// Checks that the address is in the operators list (or the owner acting as one), without authenticating it.
+pub(crate) fn check_operator(env: &Env, address: &Address) -> Result<(), BudgetError> {
+    if is_decommissioned(env) {
+        return Err(BudgetError::Decommissioned);
+    }
+    // An external authorizer adds to the operators list or replaces it
+    #[cfg(feature = "authorizer")]
+    {
+        let authorizer = authorizer::config(env);
+        if let Some(config) = &authorizer {
+            if authorizer::approves(env, config, address) {
+                return Ok(());
+            }
+        }
+        if authorizer.map(|config| config.mode) == Some(AuthorizerMode::Replace) {
+            return Err(BudgetError::NotOperator);
+        }
+    }
+    if is_listed(env, address) {
+        return check_eligible(env, address);
+    }
+    if owner_operates(env) && *address == read_owner(env) {
+        return Ok(());
+    }
+    // An active deputy holds the rights of the operator it stands in for
+    #[cfg(feature = "deputy")]
+    if let Some(principal) = deputy::principal_of(env, address) {
+        return check_eligible(env, &principal);
+    }
+    Err(BudgetError::NotOperator)
+}
+
+// Checks the requirements an operator has to keep meeting to act.
+pub(crate) fn check_eligible(env: &Env, operator: &Address) -> Result<(), BudgetError> {
+    #[cfg(feature = "badge")]
+    badge::check(env, operator)?;
+    #[cfg(feature = "holding")]
+    holding::check(env, operator)?;
+    #[cfg(feature = "stake")]
+    stake::check(env, operator)?;
+    Ok(())
+}
+
+// Authenticates the caller and checks that it is in the operators list.
+pub(crate) fn require_operator(env: &Env, caller: &Address) -> Result<(), BudgetError> {
+    caller.require_auth();
+    check_operator(env, caller)
+}
+
+// Stores the new current value and assigns the adjustment its id.
+// Every successful increase/decrease goes through here so per-adjustment features hook in one place.
+pub(crate) fn commit_adjustment(env: &Env, caller: &Address, budget: &mut BudgetState, new_value: i128) -> Receipt {
+    let previous = budget.current;
+    budget.current = new_value;
+    write_budget(env, budget);
+
+    let id: u64 = env.storage().persistent().get(&DataKey::AdjustmentCount).unwrap_or(0);
+    env.storage().persistent().set(&DataKey::AdjustmentCount, &(id + 1));
+
+    #[cfg(feature = "veto")]
+    veto::record(env, id, caller, previous, new_value);
+    #[cfg(feature = "stats")]
+    stats::record(env, caller, previous, new_value);
+    #[cfg(feature = "anomaly")]
+    anomaly::record(env, caller, previous, new_value);
+    #[cfg(feature = "policy")]
+    policy::record(env, previous, new_value);
+    #[cfg(feature = "breaker")]
+    if new_value < previous {
+        breaker::on_decrease(env, previous - new_value, budget.max);
+    }
+    #[cfg(feature = "hooks")]
+    hooks::notify(env, previous, new_value, caller);
+    #[cfg(feature = "federation")]
+    federation::report(env, new_value);
+
+    #[cfg(feature = "deputy")]
+    let on_behalf_of = deputy::principal_of(env, caller);
+    #[cfg(not(feature = "deputy"))]
+    let on_behalf_of = None;
+    let receipt = Receipt {
+        id,
+        previous,
+        new: new_value,
+        ledger: env.ledger().sequence(),
+        timestamp: env.ledger().timestamp(),
+        caller: caller.clone(),
+        on_behalf_of,
+    };
+    #[cfg(feature = "history")]
+    history::record(env, &receipt);
+    write_meta(env, Some(caller.clone()), Some(id));
+    receipt
+}
+
+// Keeps an adjustment that moved tokens from being vetoed (see veto.rs).
+pub(crate) fn exempt_from_veto(env: &Env, receipt: &Receipt) {
+    #[cfg(feature = "veto")]
+    veto::exempt(env, receipt.id);
+}
+
+// A limit an adjustment would violate, with the value it attempted and the limit it ran into.
+pub(crate) struct Violation {
+    pub error: BudgetError,
+    pub attempted: i128,
+    pub limit: i128,
+}
+
+// Rejects negative amounts, and zero amounts as no-ops that would only write an identical state.
+fn check_amount(amount: i128) -> Result<(), Violation> {
+    if amount == 0 {
+        return Err(Violation { error: BudgetError::NoOp, attempted: amount, limit: 0 });
+    }
+    if amount < 0 {
+        return Err(Violation { error: BudgetError::InvalidAmount, attempted: amount, limit: 0 });
+    }
+    Ok(())
+}
+
+// The current value after increasing by amount, or the limit the increase violates.
+pub(crate) fn increased_value(env: &Env, budget: &BudgetState, amount: i128) -> Result<i128, Violation> {
+    check_amount(amount)?;
+    
+    // Safe addition with overflow check
+    let new_value = budget.current.checked_add(amount)
+        .ok_or(Violation { error: BudgetError::Overflow, attempted: amount, limit: i128::MAX.saturating_sub(budget.current) })?;
+    
+    // Check max limit (only the unlocked part when max is vesting)
+    let cap = cap(env, budget);
+    if new_value > cap {
+        return Err(Violation { error: BudgetError::ExceedsMax, attempted: new_value, limit: cap });
+    }
+    Ok(new_value)
+}
+
+// The current value after decreasing by amount, or the limit the decrease violates.
+pub(crate) fn decreased_value(env: &Env, budget: &BudgetState, amount: i128) -> Result<i128, Violation> {
+    check_amount(amount)?;
+    
+    // Safe subtraction with underflow check
+    let new_value = budget.current.checked_sub(amount)
+        .ok_or(Violation { error: BudgetError::Underflow, attempted: amount, limit: budget.current.saturating_sub(i128::MIN) })?;
+    
+    // Check min limit (zero unless deficit mode is on, and never into reservations)
+    let floor = floor(env, budget);
+    if new_value < floor {
+        return Err(Violation { error: BudgetError::BelowMin, attempted: new_value, limit: floor });
+    }
+    Ok(new_value)
+}
+
+// Applies an increase for an operator whose authorization was already checked by the entrypoint.
+pub(crate) fn apply_increase(env: &Env, caller: &Address, amount: i128) -> Result<Receipt, BudgetError> {
+    apply_increase_in(env, caller, None, amount)
+}
+
+// Same as apply_increase, on behalf of a category when one is given.
+pub(crate) fn apply_increase_in(env: &Env, caller: &Address, category: Option<&Symbol>, amount: i128) -> Result<Receipt, BudgetError> {
+    // Check if caller is operator
+    check_operator(env, caller)?;
+    #[cfg(feature = "category")]
+    category::check_scope(env, caller, category)?;
+    #[cfg(feature = "tiers")]
+    tiers::check_direct(env, caller)?;
+    increase_for(env, caller, amount)
+}
+
+// Applies an increase for an operator whose right to adjust was already checked.
+pub(crate) fn increase_for(env: &Env, caller: &Address, amount: i128) -> Result<Receipt, BudgetError> {
+    #[cfg(feature = "bands")]
+    bands::check(env, amount)?;
+    increase_approved(env, caller, amount)
+}
+
+// Same as increase_for, for an amount whose band's approvals were already given (see bands.rs).
+pub(crate) fn increase_approved(env: &Env, caller: &Address, amount: i128) -> Result<Receipt, BudgetError> {
+    require_not_paused(env)?;
+    check_granularity(env, amount)?;
+    #[cfg(feature = "tiers")]
+    tiers::check_size(env, caller, amount)?;
+    #[cfg(feature = "policy")]
+    policy::check(env, amount, true)?;
+    
+    // Get current budget
+    let mut budget = read_budget(env);
+    
+    // Checked addition within the max limit
+    let new_value = increased_value(env, &budget, amount).map_err(|violation| violation.error)?;
+    
+    // Counted last, so an adjustment that fails any other check uses up no rate-limit slot
+    #[cfg(feature = "ratelimit")]
+    ratelimit::check(env, caller)?;
+    
+    // Update state
+    Ok(commit_adjustment(env, caller, &mut budget, new_value))
+}
+
+// Applies a decrease for an operator whose authorization was already checked by the entrypoint.
+pub(crate) fn apply_decrease(env: &Env, caller: &Address, amount: i128) -> Result<Receipt, BudgetError> {
+    apply_decrease_in(env, caller, None, amount)
+}
+
+// Same as apply_decrease, on behalf of a category when one is given.
+pub(crate) fn apply_decrease_in(env: &Env, caller: &Address, category: Option<&Symbol>, amount: i128) -> Result<Receipt, BudgetError> {
+    // Check if caller is operator
+    check_operator(env, caller)?;
+    #[cfg(feature = "category")]
+    category::check_scope(env, caller, category)?;
+    #[cfg(feature = "tiers")]
+    tiers::check_direct(env, caller)?;
+    decrease_for(env, caller, amount)
+}
+
+// Applies a decrease for a caller whose right to decrease was already checked (operator or consumer).
+pub(crate) fn decrease_for(env: &Env, caller: &Address, amount: i128) -> Result<Receipt, BudgetError> {
+    #[cfg(feature = "bands")]
+    bands::check(env, amount)?;
+    #[cfg(feature = "cosign")]
+    cosign::check(env)?;
+    decrease_approved(env, caller, amount)
+}
+
+// Same as decrease_for, for an amount whose band's approvals (or co-signature) were already given.
+pub(crate) fn decrease_approved(env: &Env, caller: &Address, amount: i128) -> Result<Receipt, BudgetError> {
+    require_not_paused(env)?;
+    check_granularity(env, amount)?;
+    #[cfg(feature = "tiers")]
+    tiers::check_size(env, caller, amount)?;
+    #[cfg(feature = "removal")]
+    removal::check_not_leaving(env, caller)?;
+    #[cfg(feature = "policy")]
+    policy::check(env, amount, false)?;
+    
+    // Get current budget
+    let mut budget = read_budget(env);
+    
+    // Checked subtraction within the min limit
+    let new_value = decreased_value(env, &budget, amount).map_err(|violation| violation.error)?;
+    
+    // Counted last, so an adjustment that fails any other check uses up no rate-limit slot
+    #[cfg(feature = "ratelimit")]
+    ratelimit::check(env, caller)?;
+    
+    // Update state
+    let receipt = commit_adjustment(env, caller, &mut budget, new_value);
+    #[cfg(feature = "fee")]
+    fee::on_decrease(env, amount);
+    Ok(receipt)
+}
+
 
 
 #[contractimpl]
 impl GovernanceBudgetAllocator {
     /// Initialize the contract with owner, initial budget, and limits
-    pub fn initialize(env: Env, owner: Address, initial: i128, min: i128, max: i128) {
+    pub fn initialize(env: Env, owner: Address, initial: i128, min: i128, max: i128) -> Result<(), BudgetError> {
+        // A decommissioned contract can never be set up again
+        if is_decommissioned(&env) {
+            return Err(BudgetError::Decommissioned);
+        }
+
+        // Only the first call sets the contract up, later ones would replace a live owner and budget
+        if env.storage().instance().has(&DataKey::Owner) {
+            return Err(BudgetError::AlreadyInitialized);
+        }
+        
         // Validate limits: min <= initial <= max
         if min > initial || initial > max {
-            panic!("Invalid limits: min must be <= initial <= max");
+            return Err(BudgetError::InvalidLimits);
         }
         
         // Store owner
-        env.storage().persistent().set(&DataKey::Owner, &owner);
+        write_owner(&env, &owner);
         
         // Initialize empty operators list
         let operators: Vec<Address> = Vec::new(&env);
@@ -93,7 +868,10 @@ impl GovernanceBudgetAllocator {
             min,
             max,
         };
-        env.storage().persistent().set(&DataKey::Budget, &budget);
+        env.storage().instance().set(&DataKey::Budget, &budget);
+        write_meta(&env, None, None);
+        
+        Ok(())
     }
 //     Creates the initial budget state and stores it.
 //      The contract is now fully initialized.
@@ -101,52 +879,80 @@ impl GovernanceBudgetAllocator {
 
 
 
-//     This function adds a new operator.
+//     This function nominates a new operator; it becomes one when it calls accept_operator.
 //.     The caller must authenticate.
     pub fn add_operator(env: Env, caller: Address, operator: Address) -> Result<(), BudgetError> {
-        caller.require_auth();
-        
-        // Verify caller is owner
-//       Checks that the caller is the owner.
+        // Verify caller is owner or admin
+//       Checks that the caller is the owner or the admin.
 //       If not, returns a NotOwner error.
-        let owner: Address = env.storage().persistent().get(&DataKey::Owner).unwrap();
-        if caller != owner {
-            return Err(BudgetError::NotOwner);
-        }
+        require_operator_manager(&env, &caller)?;
+        check_operators_unlocked(&env)?;
         
-        // Get operators list
-        // Loads the current list of operators from storage.
-        let mut operators: Vec<Address> = env.storage().persistent().get(&DataKey::Operators).unwrap();
+        // Fail early on nominees that could never accept
+        check_can_join(&env, &read_operators(&env), &operator)?;
         
+        // The nominee only becomes an operator once it accepts
+        env.storage().persistent().set(&DataKey::Nominated(operator), &true);
         
-        // Checks whether the address is already an operator.
-        // Prevents duplicate entries.
-        for op in operators.iter() {
-            if op == operator {
-                return Err(BudgetError::AlreadyOperator);
-            }
+        Ok(())
+    }
+    
+    /// Accept a nomination and become an operator (nominee only)
+    pub fn accept_operator(env: Env, operator: Address) -> Result<(), BudgetError> {
+        operator.require_auth();
+        if is_decommissioned(&env) {
+            return Err(BudgetError::Decommissioned);
         }
+        let nomination = DataKey::Nominated(operator.clone());
+        if !env.storage().persistent().has(&nomination) {
+            return Err(BudgetError::NoPendingAction);
+        }
+        check_operators_unlocked(&env)?;
+        
+        // The operators list may have changed since the nomination
+        let mut operators = read_operators(&env);
+        check_can_join(&env, &operators, &operator)?;
         
         // Add operator
+        env.storage().persistent().remove(&nomination);
+        set_listed(&env, &operator, true);
         operators.push_back(operator);
         env.storage().persistent().set(&DataKey::Operators, &operators);
         
         Ok(())
     }
     
+    /// Lock the operators list against additions and removals up to and including a ledger (owner only)
+    pub fn lock_operators(env: Env, caller: Address, until_ledger: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        // A lock can be extended but never shortened
+        let current: u32 = env.storage().persistent().get(&DataKey::OperatorLock).unwrap_or(0);
+        if until_ledger < env.ledger().sequence() || until_ledger <= current {
+            return Err(BudgetError::InvalidConfig);
+        }
+        env.storage().persistent().set(&DataKey::OperatorLock, &until_ledger);
+        Ok(())
+    }
+    
+    // Get the last ledger the operators list is locked for, if it was ever locked
+    pub fn get_operator_lock(env: Env) -> Option<u32> {
+        env.storage().persistent().get(&DataKey::OperatorLock)
+    }
+    
+    // Check if an address was nominated as operator and has not accepted yet
+    pub fn is_nominated(env: Env, address: Address) -> bool {
+        env.storage().persistent().has(&DataKey::Nominated(address))
+    }
+    
 //     Removes an operator.
 //    The caller must authenticate.
     pub fn remove_operator(env: Env, caller: Address, operator: Address) -> Result<(), BudgetError> {
-        caller.require_auth();
-        
-        // Verify caller is owner
-        let owner: Address = env.storage().persistent().get(&DataKey::Owner).unwrap();
-        if caller != owner {
-            return Err(BudgetError::NotOwner);
-        }
+        // Verify caller is owner or admin
+        require_operator_manager(&env, &caller)?;
+        check_operators_unlocked(&env)?;
         
         // Get operators list
-        let operators: Vec<Address> = env.storage().persistent().get(&DataKey::Operators).unwrap();
+        let operators = read_operators(&env);
         
         // Find and remove operator
         
@@ -164,112 +970,235 @@ impl GovernanceBudgetAllocator {
             return Err(BudgetError::NotOperatorFound);
         }
         
+        // With a grace period the operator only leaves once it has passed
+        #[cfg(feature = "removal")]
+        if removal::grace(&env) > 0 {
+            return removal::schedule(&env, operator);
+        }
+        
         env.storage().persistent().set(&DataKey::Operators, &new_operators);
+        set_listed(&env, &operator, false);
         
         Ok(())
     }
 
-    // Increase the budget (operators only)
+    // Get when and by whom the budget was last changed
+    pub fn get_budget_meta(env: Env) -> BudgetMeta {
+        read_budget(&env);
+        env.storage().persistent().get(&DataKey::BudgetMeta).unwrap()
+    }
     
-    pub fn increase_budget(env: Env, caller: Address, amount: i128) -> Result<i128, BudgetError> {
-        caller.require_auth();
-        
-        // Check if caller is operator
-        let operators: Vec<Address> = env.storage().persistent().get(&DataKey::Operators).unwrap();
-        let mut is_operator = false;
-        for op in operators.iter() {
-            if op == caller {
-                is_operator = true;
-                break;
-            }
-        }
-        
-        if !is_operator {
-            return Err(BudgetError::NotOperator);
+    // Get current/max in basis points (0 when max is not positive)
+    pub fn get_utilization_bps(env: Env) -> i128 {
+        let budget = read_budget(&env);
+        if budget.max <= 0 {
+            return 0;
         }
-        
-        // Get current budget
-        let mut budget: BudgetState = env.storage().persistent().get(&DataKey::Budget).unwrap();
-        
-        // Safe addition with overflow check
-        let new_value = budget.current.checked_add(amount)
-            .ok_or(BudgetError::Overflow)?;
-        
-        // Check max limit
-        if new_value > budget.max {
-            return Err(BudgetError::ExceedsMax);
+        // current <= max, so if current * 10000 overflows then max / 10000 is non-zero
+        match budget.current.checked_mul(10_000) {
+            Some(scaled) => scaled / budget.max,
+            None => budget.current / (budget.max / 10_000),
         }
-        
-        // Update state
-        budget.current = new_value;
-        env.storage().persistent().set(&DataKey::Budget, &budget);
-        
-        Ok(new_value)
     }
     
-    // Decrease the budget (operators only)
-   
-    pub fn decrease_budget(env: Env, caller: Address, amount: i128) -> Result<i128, BudgetError> {
-        caller.require_auth();
-        
-        // Check if caller is operator
-        let operators: Vec<Address> = env.storage().persistent().get(&DataKey::Operators).unwrap();
-        let mut is_operator = false;
-        for op in operators.iter() {
-            if op == caller {
-                is_operator = true;
-                break;
-            }
-        }
-        
-        if !is_operator {
-            return Err(BudgetError::NotOperator);
-        }
-        
-        // Get current budget
-        let mut budget: BudgetState = env.storage().persistent().get(&DataKey::Budget).unwrap();
-        
-        // Safe subtraction with underflow check
-        let new_value = budget.current.checked_sub(amount)
-            .ok_or(BudgetError::Underflow)?;
-        
-        // Check min limit
-        if new_value < budget.min {
-            return Err(BudgetError::BelowMin);
+    // Get how much the budget can still be increased before reaching max (or the vested part of it)
+    pub fn get_headroom(env: Env) -> i128 {
+        let budget = read_budget(&env);
+        cap(&env, &budget).saturating_sub(budget.current).max(0)
+    }
+    
+    // Get how much the budget can still be decreased before reaching min
+    pub fn get_floor_distance(env: Env) -> i128 {
+        let budget = read_budget(&env);
+        budget.current.saturating_sub(budget.min)
+    }
+    
+    // Get the number of operators
+    pub fn get_operator_count(env: Env) -> u32 {
+        read_operators(&env).len()
+    }
+    
+    // Get up to limit operators starting at index start (empty past the end)
+    pub fn get_operators_page(env: Env, start: u32, limit: u32) -> Vec<Address> {
+        let operators = read_operators(&env);
+        let start = start.min(operators.len());
+        let end = start.saturating_add(limit).min(operators.len());
+        operators.slice(start..end)
+    }
+    
+    /// Set the epoch length in ledgers (owner only, 0 disables epochs)
+    pub fn set_epoch_length(env: Env, caller: Address, ledgers: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().set(&DataKey::EpochLength, &ledgers);
+        Ok(())
+    }
+    
+    // Get the epoch length in ledgers (0 means disabled)
+    pub fn get_epoch_length(env: Env) -> u32 {
+        epoch_length(&env)
+    }
+    
+    /// Require every adjustment to be a multiple of granularity (owner only, 0 allows any amount)
+    pub fn set_granularity(env: Env, caller: Address, granularity: i128) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        if granularity < 0 {
+            return Err(BudgetError::InvalidConfig);
         }
-        
-        // Update state
-        budget.current = new_value;
-        env.storage().persistent().set(&DataKey::Budget, &budget);
-        
-        Ok(new_value)
+        env.storage().instance().set(&DataKey::Granularity, &granularity);
+        Ok(())
     }
     
+    // Get the adjustment granularity (0 means any amount)
+    pub fn get_granularity(env: Env) -> i128 {
+        granularity(&env)
+    }
     
-    // Get current budget state
-    pub fn get_budget(env: Env) -> BudgetState {
-        env.storage().persistent().get(&DataKey::Budget).unwrap()
+    /// Cap the number of operators (owner only, 0 removes the cap)
+    pub fn set_max_operators(env: Env, caller: Address, max: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().set(&DataKey::MaxOperators, &max);
+        Ok(())
     }
     
-    // Get contract owner address
-    pub fn get_owner(env: Env) -> Address {
-        env.storage().persistent().get(&DataKey::Owner).unwrap()
+    // Get the operator cap (0 means no cap)
+    pub fn get_max_operators(env: Env) -> u32 {
+        max_operators(&env)
     }
     
-    // Get list of authorized operators
-    pub fn get_operators(env: Env) -> Vec<Address> {
-        env.storage().persistent().get(&DataKey::Operators).unwrap()
+    /// Allow or stop the owner adjusting the budget without being an operator itself (owner only)
+    pub fn set_owner_operator(env: Env, caller: Address, enabled: bool) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().set(&DataKey::OwnerOperator, &enabled);
+        Ok(())
     }
     
-    // Check if an address is an operator
-    pub fn is_operator(env: Env, address: Address) -> bool {
-        let operators: Vec<Address> = env.storage().persistent().get(&DataKey::Operators).unwrap();
-        for op in operators.iter() {
-            if op == address {
-                return true;
+    // Check if the owner may adjust the budget without being an operator
+    pub fn is_owner_operator(env: Env) -> bool {
+        owner_operates(&env)
+    }
+    
+    /// Assign the admin role, replacing any previous admin (owner only)
+    pub fn set_admin(env: Env, caller: Address, admin: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().set(&DataKey::Admin, &admin);
+        Ok(())
+    }
+    
+    /// Remove the admin role (owner only)
+    pub fn remove_admin(env: Env, caller: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().remove(&DataKey::Admin);
+        Ok(())
+    }
+    
+    // Get the admin, if one is assigned
+    pub fn get_admin(env: Env) -> Option<Address> {
+        read_admin(&env)
+    }
+    
+    /// Block all budget adjustments (owner only)
+    pub fn pause(env: Env, caller: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        set_paused(&env, true);
+        Ok(())
+    }
+    
+    /// Allow budget adjustments again (owner only)
+    pub fn unpause(env: Env, caller: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        set_paused(&env, false);
+        Ok(())
+    }
+    
+    // Check if budget adjustments are paused
+    pub fn is_paused(env: Env) -> bool {
+        is_paused(&env)
+    }
+    
+    // Check if the contract has been decommissioned
+    pub fn is_decommissioned(env: Env) -> bool {
+        is_decommissioned(&env)
+    }
+    
+    // Get how many adjustments have been applied (also the id the next one will get)
+    pub fn get_adjustment_count(env: Env) -> u64 {
+        env.storage().persistent().get(&DataKey::AdjustmentCount).unwrap_or(0)
+    }
+}
+
+// The core entrypoints are declared, and documented, by the BudgetAllocator trait of
+// governance_budget_interface, so integrators can use its client without this crate.
+#[contractimpl]
+impl BudgetAllocator for GovernanceBudgetAllocator {
+    fn increase_budget(env: Env, caller: Address, amount: i128) -> Result<Receipt, BudgetError> {
+        caller.require_auth();
+        apply_increase(&env, &caller, amount)
+    }
+
+    fn decrease_budget(env: Env, caller: Address, amount: i128) -> Result<Receipt, BudgetError> {
+        caller.require_auth();
+        apply_decrease(&env, &caller, amount)
+    }
+
+    fn get_budget(env: Env) -> BudgetState {
+        read_budget(&env)
+    }
+
+    fn get_owner(env: Env) -> Address {
+        read_owner(&env)
+    }
+
+    fn get_operators(env: Env) -> Vec<Address> {
+        read_operators(&env)
+    }
+
+    fn check_adjustment(env: Env, caller: Address, amount: i128, increase: bool) -> LimitCheck {
+        let checks = || -> Result<(), BudgetError> {
+            check_operator(&env, &caller)?;
+            #[cfg(feature = "category")]
+            category::check_scope(&env, &caller, None)?;
+            #[cfg(feature = "tiers")]
+            tiers::check_direct(&env, &caller)?;
+            #[cfg(feature = "bands")]
+            bands::check(&env, amount)?;
+            #[cfg(feature = "cosign")]
+            if !increase {
+                cosign::check(&env)?;
             }
+            require_not_paused(&env)?;
+            check_granularity(&env, amount)?;
+            #[cfg(feature = "tiers")]
+            tiers::check_size(&env, &caller, amount)?;
+            #[cfg(feature = "removal")]
+            if !increase {
+                removal::check_not_leaving(&env, &caller)?;
+            }
+            #[cfg(feature = "policy")]
+            policy::check(&env, amount, increase)?;
+            Ok(())
+        };
+        if let Err(error) = checks() {
+            return LimitCheck { error: error as u32, attempted: amount, limit: 0 };
         }
-        false
+        
+        let budget = read_budget(&env);
+        let result = if increase {
+            increased_value(&env, &budget, amount).map(|value| (value, cap(&env, &budget)))
+        } else {
+            decreased_value(&env, &budget, amount).map(|value| (value, floor(&env, &budget)))
+        };
+        match result {
+            Ok((attempted, limit)) => LimitCheck { error: 0, attempted, limit },
+            Err(violation) => LimitCheck { error: violation.error as u32, attempted: violation.attempted, limit: violation.limit },
+        }
+    }
+
+    fn is_operator(env: Env, address: Address) -> bool {
+        is_listed(&env, &address)
+    }
+
+    fn get_version(env: Env) -> ContractVersion {
+        ContractVersion { version: String::from_str(&env, env!("CARGO_PKG_VERSION")), interface: INTERFACE_VERSION }
     }
 }
 
@@ -277,7 +1206,8 @@ impl GovernanceBudgetAllocator {
 // This module contains unit tests for the contract.
 mod test {
     use super::*;
-    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::vec;
 
     #[test]
     fn test_initialize() {
@@ -295,6 +1225,35 @@ mod test {
         assert_eq!(budget.max, 10000);
     }
     
+    #[test]
+    fn test_initialize_invalid_limits() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+        
+        let owner = Address::generate(&env);
+        
+        assert_eq!(client.try_initialize(&owner, &1000, &2000, &10000), Err(Ok(BudgetError::InvalidLimits)));
+        assert_eq!(client.try_initialize(&owner, &1000, &0, &500), Err(Ok(BudgetError::InvalidLimits)));
+    }
+    
+    #[test]
+    fn test_initialize_only_once() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+        
+        let owner = Address::generate(&env);
+        let attacker = Address::generate(&env);
+        
+        client.initialize(&owner, &1000, &0, &10000);
+        
+        // Nobody can take over a live contract by setting it up again
+        assert_eq!(client.try_initialize(&attacker, &0, &0, &1), Err(Ok(BudgetError::AlreadyInitialized)));
+        assert_eq!(client.get_owner(), owner);
+        assert_eq!(client.get_budget().max, 10000);
+    }
+    
     #[test]
     fn test_add_operator() {
         let env = Env::default();
@@ -307,9 +1266,157 @@ mod test {
         client.initialize(&owner, &1000, &0, &10000);
         
         env.mock_all_auths();
+        assert_eq!(client.try_accept_operator(&operator), Err(Ok(BudgetError::NoPendingAction)));
         client.add_operator(&owner, &operator);
         
+        // Nominated, but not an operator until it accepts
+        assert!(client.is_nominated(&operator));
+        assert!(!client.is_operator(&operator));
+        client.accept_operator(&operator);
+        
+        assert!(client.is_operator(&operator));
+        assert!(!client.is_nominated(&operator));
+    }
+    
+    #[test]
+    fn test_lock_operators() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+        
+        let owner = Address::generate(&env);
+        let first = Address::generate(&env);
+        let second = Address::generate(&env);
+        
+        client.initialize(&owner, &1000, &0, &10000);
+        
+        env.mock_all_auths();
+        client.add_operator(&owner, &first);
+        client.accept_operator(&first);
+        client.add_operator(&owner, &second);
+        client.lock_operators(&owner, &100);
+        assert_eq!(client.try_lock_operators(&owner, &50), Err(Ok(BudgetError::InvalidConfig)));
+        
+        // Neither joining nor leaving while locked
+        assert_eq!(client.try_accept_operator(&second), Err(Ok(BudgetError::OperatorsLocked)));
+        assert_eq!(client.try_remove_operator(&owner, &first), Err(Ok(BudgetError::OperatorsLocked)));
+        
+        env.ledger().with_mut(|l| l.sequence_number = 101);
+        client.accept_operator(&second);
+        client.remove_operator(&owner, &first);
+        assert_eq!(client.get_operators(), vec![&env, second]);
+    }
+    
+    #[test]
+    fn test_check_adjustment() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+        
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        
+        client.initialize(&owner, &1000, &0, &10000);
+        
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        
+        assert_eq!(client.check_adjustment(&operator, &500, &true), LimitCheck { error: 0, attempted: 1500, limit: 10000 });
+        
+        // Failures come with the value that was attempted and the limit it hit
+        let check = client.check_adjustment(&operator, &1500, &false);
+        assert_eq!(check, LimitCheck { error: BudgetError::BelowMin as u32, attempted: -500, limit: 0 });
+        assert_eq!(client.try_decrease_budget(&operator, &1500), Err(Ok(BudgetError::BelowMin)));
+        
+        assert_eq!(client.check_adjustment(&owner, &1, &true).error, BudgetError::NotOperator as u32);
+        assert_eq!(client.try_increase_budget(&operator, &-1), Err(Ok(BudgetError::InvalidAmount)));
+        
+        // A zero amount changes nothing, so it is neither applied nor logged
+        assert_eq!(client.try_decrease_budget(&operator, &0), Err(Ok(BudgetError::NoOp)));
+        assert_eq!(client.get_adjustment_count(), 0);
+    }
+    
+    #[test]
+    fn test_max_operators() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+        
+        let owner = Address::generate(&env);
+        let first = Address::generate(&env);
+        let second = Address::generate(&env);
+        
+        client.initialize(&owner, &1000, &0, &10000);
+        
+        env.mock_all_auths();
+        client.set_max_operators(&owner, &1);
+        client.add_operator(&owner, &first);
+        client.accept_operator(&first);
+        assert_eq!(client.try_add_operator(&owner, &second), Err(Ok(BudgetError::TooManyOperators)));
+        
+        // Removing an operator frees a slot
+        client.remove_operator(&owner, &first);
+        client.add_operator(&owner, &second);
+        client.accept_operator(&second);
+        assert_eq!(client.get_operators().len(), 1);
+    }
+    
+    #[test]
+    fn test_operator_pages() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+        
+        let owner = Address::generate(&env);
+        
+        client.initialize(&owner, &1000, &0, &10000);
+        
+        env.mock_all_auths();
+        let mut all = Vec::new(&env);
+        for _ in 0..5 {
+            let operator = Address::generate(&env);
+            client.add_operator(&owner, &operator);
+            client.accept_operator(&operator);
+            all.push_back(operator);
+        }
+        
+        assert_eq!(client.get_operator_count(), 5);
+        assert_eq!(client.get_operators_page(&0, &2), all.slice(0..2));
+        assert_eq!(client.get_operators_page(&4, &2), all.slice(4..5));
+        assert_eq!(client.get_operators_page(&7, &2).len(), 0);
+    }
+    
+    #[test]
+    fn test_admin_manages_operators_only() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+        
+        let owner = Address::generate(&env);
+        let admin = Address::generate(&env);
+        let operator = Address::generate(&env);
+        
+        client.initialize(&owner, &1000, &0, &10000);
+        
+        env.mock_all_auths();
+        client.set_admin(&owner, &admin);
+        assert_eq!(client.get_admin(), Some(admin.clone()));
+        
+        client.add_operator(&admin, &operator);
+        
+        client.accept_operator(&operator);
         assert!(client.is_operator(&operator));
+        client.remove_operator(&admin, &operator);
+        assert!(!client.is_operator(&operator));
+        
+        // Limits and ownership stay with the owner
+        assert_eq!(client.try_set_limits(&admin, &0, &20000), Err(Ok(BudgetError::NotOwner)));
+        let action = AdminAction::TransferOwnership(admin.clone());
+        assert_eq!(client.try_propose_action(&admin, &action), Err(Ok(BudgetError::NotOwner)));
+        
+        client.remove_admin(&owner);
+        assert_eq!(client.try_add_operator(&admin, &operator), Err(Ok(BudgetError::NotOwner)));
     }
     
     #[test]
@@ -325,9 +1432,87 @@ mod test {
         
         env.mock_all_auths();
         client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        
+        let receipt = client.increase_budget(&operator, &500);
+        assert_eq!(receipt.id, 0);
+        assert_eq!(receipt.previous, 1000);
+        assert_eq!(receipt.new, 1500);
+        assert_eq!(receipt.caller, operator);
+    }
+    
+    #[test]
+    fn test_granularity() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+        
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        
+        client.initialize(&owner, &1000, &0, &10000);
+        
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_granularity(&owner, &100);
         
-        let new_value = client.increase_budget(&operator, &500);
-        assert_eq!(new_value, 1500);
+        assert_eq!(client.try_increase_budget(&operator, &150), Err(Ok(BudgetError::InvalidGranularity)));
+        assert_eq!(client.try_decrease_budget(&operator, &1), Err(Ok(BudgetError::InvalidGranularity)));
+        assert_eq!(client.increase_budget(&operator, &300).new, 1300);
+        
+        client.set_granularity(&owner, &0);
+        assert_eq!(client.increase_budget(&operator, &1).new, 1301);
+    }
+    
+    #[test]
+    fn test_budget_meta() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+        
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        
+        client.initialize(&owner, &1000, &0, &10000);
+        assert_eq!(client.get_budget_meta().last_op_id, None);
+        
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        env.ledger().with_mut(|l| l.sequence_number = 7);
+        client.increase_budget(&operator, &500);
+        client.increase_budget(&operator, &500);
+        
+        let meta = client.get_budget_meta();
+        assert_eq!(meta.last_modified_ledger, 7);
+        assert_eq!(meta.last_modified_by, Some(operator));
+        assert_eq!(meta.last_op_id, Some(1));
+        
+        // A limit change is not an adjustment
+        client.set_limits(&owner, &0, &20000);
+        assert_eq!(client.get_budget_meta().last_op_id, None);
+    }
+    
+    #[test]
+    fn test_utilization_and_headroom() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+        
+        let owner = Address::generate(&env);
+        
+        client.initialize(&owner, &2500, &500, &10000);
+        
+        assert_eq!(client.get_utilization_bps(), 2500);
+        assert_eq!(client.get_headroom(), 7500);
+        assert_eq!(client.get_floor_distance(), 2000);
+        
+        // Values too large for a plain multiplication still work
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+        client.initialize(&owner, &(i128::MAX / 2), &0, &i128::MAX);
+        assert_eq!(client.get_utilization_bps(), 5000);
     }
     
     #[test]
@@ -360,8 +1545,72 @@ mod test {
         
         env.mock_all_auths();
         client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
         client.increase_budget(&operator, &10000);
     }
+
+    #[test]
+    fn test_owner_as_operator() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        assert!(!client.is_owner_operator());
+        assert_eq!(client.try_increase_budget(&owner, &100), Err(Ok(BudgetError::NotOperator)));
+
+        client.set_owner_operator(&owner, &true);
+        assert!(client.is_owner_operator());
+        assert_eq!(client.increase_budget(&owner, &100).caller, owner);
+
+        // Only while the flag is on, and it is not an operator listing
+        assert!(!client.is_operator(&owner));
+        client.set_owner_operator(&owner, &false);
+        assert_eq!(client.try_decrease_budget(&owner, &100), Err(Ok(BudgetError::NotOperator)));
+    }
+
+    #[test]
+    fn test_interface_clients() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+        let allocator = BudgetAllocatorClient::new(&env, &contract_id);
+        let treasury = BudgetTreasuryClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+
+        // The interface clients reach the same entrypoints
+        assert!(allocator.is_operator(&operator));
+        assert_eq!(allocator.increase_budget(&operator, &500).new, 1500);
+        assert_eq!(allocator.try_decrease_budget(&operator, &2000), Err(Ok(BudgetError::BelowMin)));
+        assert_eq!(allocator.check_adjustment(&operator, &9000, &true).error, BudgetError::ExceedsMax as u32);
+        assert_eq!(allocator.get_owner(), owner);
+        assert_eq!(treasury.get_token(), None);
+    }
+
+    #[test]
+    fn test_get_version() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = BudgetAllocatorClient::new(&env, &contract_id);
+
+        // Available before initialize, and in step with the crate version and the embedded metadata
+        let version = client.get_version();
+        assert_eq!(version.version, String::from_str(&env, env!("CARGO_PKG_VERSION")));
+        assert_eq!(version.version, String::from_str(&env, "0.1.0"));
+        assert_eq!(version.interface, INTERFACE_VERSION);
+    }
 }
 // Initialization stores correct values
 // Owner can add operators
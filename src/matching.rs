@@ -0,0 +1,187 @@
+// Matching funds for donations.
+// The owner can run a matching program: every donation is matched at ratio_bps (10000 = 1:1) on
+// top of the donation, up to a per-donor cap and a global cap over the whole program. Matches are
+// paid in the backing token, either pulled from a sponsor through the allowance it granted this
+// contract, or without a sponsor from the contract's own balance that the accounting does not
+// account for yet (e.g. a matching pool sent to the contract). A match never takes the budget
+// beyond max, and it is cut down to what the source can actually pay instead of failing the donation.
+// Each match raises the budget and emits a "match" event about the donor with the donation id and amount.
+
+use soroban_sdk::{contractimpl, contracttype, token, Address, Env};
+
+use crate::{
+    commit_adjustment, events, exempt_from_veto, read_budget, require_owner, treasury, BudgetError, DonationEvent,
+    GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient,
+};
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct MatchingProgram {
+    pub sponsor: Option<Address>,
+    pub ratio_bps: u32,
+    pub per_donor_cap: i128,
+    pub global_cap: i128,
+}
+// MatchingProgram configures matching:
+// sponsor pays the matches through its allowance, None pays them from the contract's unaccounted balance
+// per_donor_cap and global_cap bound the matched amounts per donor and in total
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum MatchingKey {
+    Matching,
+    Matched(Address),
+    MatchedTotal,
+}
+// Matching stores the MatchingProgram (missing means no matching)
+// Matched stores the amount ever matched for a donor (kept across programs)
+// MatchedTotal stores the amount matched in the current program
+
+
+const BPS: i128 = 10_000;
+
+fn matched(env: &Env, donor: &Address) -> i128 {
+    env.storage().persistent().get(&MatchingKey::Matched(donor.clone())).unwrap_or(0)
+}
+
+fn matched_total(env: &Env) -> i128 {
+    env.storage().persistent().get(&MatchingKey::MatchedTotal).unwrap_or(0)
+}
+
+// What the source of the matches can pay right now.
+fn available(env: &Env, program: &MatchingProgram, token: &Address) -> i128 {
+    let client = token::Client::new(env, token);
+    let this = env.current_contract_address();
+    match &program.sponsor {
+        Some(sponsor) => client.allowance(sponsor, &this).min(client.balance(sponsor)),
+        None => client.balance(&this) - treasury::accounted(env, token),
+    }
+}
+
+// Called after a donation was credited; matches it if a program is running.
+pub(crate) fn on_donation(env: &Env, donor: &Address, id: u64, amount: i128) {
+    let program: MatchingProgram = match env.storage().persistent().get(&MatchingKey::Matching) {
+        Some(program) => program,
+        None => return,
+    };
+    let token = match treasury::backing_token(env) {
+        Ok(token) => token,
+        Err(_) => return,
+    };
+
+    let mut budget = read_budget(env);
+    let donor_matched = matched(env, donor);
+    let total_matched = matched_total(env);
+    let amount = (amount.saturating_mul(program.ratio_bps as i128) / BPS)
+        .min(program.per_donor_cap - donor_matched)
+        .min(program.global_cap - total_matched)
+        .min(budget.max - budget.current)
+        .min(available(env, &program, &token));
+    if amount <= 0 {
+        return;
+    }
+
+    let this = env.current_contract_address();
+    if let Some(sponsor) = &program.sponsor {
+        token::Client::new(env, &token).transfer_from(&this, sponsor, &this, &amount);
+    }
+    let caller = program.sponsor.unwrap_or(this);
+    let new_value = budget.current + amount;
+    let receipt = commit_adjustment(env, &caller, &mut budget, new_value);
+    exempt_from_veto(env, &receipt);
+
+    env.storage().persistent().set(&MatchingKey::Matched(donor.clone()), &(donor_matched + amount));
+    env.storage().persistent().set(&MatchingKey::MatchedTotal, &(total_matched + amount));
+    events::publish(env, events::MATCH, donor, DonationEvent { donation_id: id, amount });
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Start a matching program for donations (owner only)
+    pub fn set_matching(
+        env: Env,
+        caller: Address,
+        sponsor: Option<Address>,
+        ratio_bps: u32,
+        per_donor_cap: i128,
+        global_cap: i128,
+    ) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        if ratio_bps == 0 || per_donor_cap <= 0 || global_cap <= 0 {
+            return Err(BudgetError::InvalidConfig);
+        }
+        let program = MatchingProgram { sponsor, ratio_bps, per_donor_cap, global_cap };
+        env.storage().persistent().set(&MatchingKey::Matching, &program);
+        env.storage().persistent().set(&MatchingKey::MatchedTotal, &0i128);
+        Ok(())
+    }
+
+    /// End the matching program (owner only)
+    pub fn clear_matching(env: Env, caller: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().remove(&MatchingKey::Matching);
+        Ok(())
+    }
+
+    // Get the matching program, if any
+    pub fn get_matching(env: Env) -> Option<MatchingProgram> {
+        env.storage().persistent().get(&MatchingKey::Matching)
+    }
+
+    // Get the amount matched for a donor
+    pub fn get_matched(env: Env, donor: Address) -> i128 {
+        matched(&env, &donor)
+    }
+
+    // Get the amount matched in total
+    pub fn get_matched_total(env: Env) -> i128 {
+        matched_total(&env)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{token, String};
+
+    #[test]
+    fn test_sponsor_matches_up_to_caps() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let sponsor = Address::generate(&env);
+        let alice = Address::generate(&env);
+        let bob = Address::generate(&env);
+
+        client.initialize(&owner, &0, &0, &100000);
+
+        env.mock_all_auths();
+        let sac = env.register_stellar_asset_contract_v2(owner.clone());
+        let minter = token::StellarAssetClient::new(&env, &sac.address());
+        for address in [&sponsor, &alice, &bob] {
+            minter.mint(address, &10000);
+        }
+        client.set_token(&owner, &sac.address());
+        token::Client::new(&env, &sac.address()).approve(&sponsor, &contract_id, &10000, &1000);
+        client.set_matching(&owner, &Some(sponsor.clone()), &10000, &300, &500);
+
+        let memo = String::from_str(&env, "");
+        client.donate(&alice, &200, &memo);
+        client.donate(&alice, &200, &memo);
+        assert_eq!(client.get_matched(&alice), 300);
+
+        // Only 200 of the global cap is left for bob
+        client.donate(&bob, &400, &memo);
+        assert_eq!(client.get_matched(&bob), 200);
+        assert_eq!(client.get_matched_total(), 500);
+        assert_eq!(client.get_budget().current, 1300);
+        assert_eq!(token::Client::new(&env, &sac.address()).balance(&sponsor), 9500);
+    }
+}
@@ -0,0 +1,98 @@
+// Storage layout migration.
+// Owner, Paused, Budget and Granularity used to be persistent entries and now live in instance
+// storage, which is loaded with the contract on every call. Operators also gained a membership key
+// each (OperatorUntil). A contract deployed with the old layout is upgraded in place and then the
+// owner calls migrate_storage_v2 once to move the legacy entries over; until then the entrypoints
+// that read the moved keys fail.
+
+use soroban_sdk::{contractimpl, Address, Env, Vec};
+
+use crate::{
+    is_decommissioned, write_owner, BudgetError, BudgetState, DataKey, GovernanceBudgetAllocator,
+    GovernanceBudgetAllocatorClient,
+};
+#[cfg(feature = "removal")]
+use crate::removal;
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Move the legacy persistent configuration into instance storage (owner only, once)
+    pub fn migrate_storage_v2(env: Env, caller: Address) -> Result<(), BudgetError> {
+        if is_decommissioned(&env) {
+            return Err(BudgetError::Decommissioned);
+        }
+        // The owner is still in its legacy slot, so it is checked here rather than by require_owner
+        let storage = env.storage().persistent();
+        let owner: Address = storage.get(&DataKey::Owner).ok_or(BudgetError::NoPendingAction)?;
+        caller.require_auth();
+        if caller != owner {
+            return Err(BudgetError::NotOwner);
+        }
+
+        write_owner(&env, &owner);
+        storage.remove(&DataKey::Owner);
+        if let Some(budget) = storage.get::<_, BudgetState>(&DataKey::Budget) {
+            env.storage().instance().set(&DataKey::Budget, &budget);
+            storage.remove(&DataKey::Budget);
+        }
+        if let Some(paused) = storage.get::<_, bool>(&DataKey::Paused) {
+            env.storage().instance().set(&DataKey::Paused, &paused);
+            storage.remove(&DataKey::Paused);
+        }
+        if let Some(granularity) = storage.get::<_, i128>(&DataKey::Granularity) {
+            env.storage().instance().set(&DataKey::Granularity, &granularity);
+            storage.remove(&DataKey::Granularity);
+        }
+
+        // Backfill the membership keys, keeping scheduled removals on their effective ledger
+        let operators: Vec<Address> = storage.get(&DataKey::Operators).unwrap_or(Vec::new(&env));
+        for operator in operators.iter() {
+            #[cfg(feature = "removal")]
+            let until = removal::pending(&env, &operator).map_or(u32::MAX, |at| at - 1);
+            #[cfg(not(feature = "removal"))]
+            let until = u32::MAX;
+            storage.set(&DataKey::OperatorUntil(operator), &until);
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+
+    #[test]
+    fn test_migrate_legacy_storage() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        // A contract still on the old layout
+        env.as_contract(&contract_id, || {
+            let storage = env.storage().persistent();
+            storage.set(&DataKey::Owner, &owner);
+            storage.set(&DataKey::Operators, &Vec::from_array(&env, [operator.clone()]));
+            storage.set(&DataKey::Budget, &BudgetState { current: 1000, min: 0, max: 10000 });
+            storage.set(&DataKey::Paused, &false);
+            storage.set(&DataKey::Granularity, &10i128);
+        });
+
+        env.mock_all_auths();
+        assert_eq!(client.try_migrate_storage_v2(&operator), Err(Ok(BudgetError::NotOwner)));
+        client.migrate_storage_v2(&owner);
+
+        assert_eq!(client.get_owner(), owner);
+        assert_eq!(client.get_granularity(), 10);
+        assert!(client.is_operator(&operator));
+        assert_eq!(client.increase_budget(&operator, &100).new, 1100);
+
+        // Nothing left to migrate
+        assert_eq!(client.try_migrate_storage_v2(&owner), Err(Ok(BudgetError::NoPendingAction)));
+    }
+}
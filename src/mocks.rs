@@ -0,0 +1,126 @@
+// Mock contracts for tests.
+// Stand-ins for the contracts the allocator calls out to: a SEP-40 price oracle, a governance
+// token with balance checkpoints, a budget hook that records what it is told, and the Stellar
+// Asset Contract used as backing token. Each has a register_* helper that deploys it into a test
+// Env ready to use, in builds with the feature that calls out to it. Built for this crate's tests
+// and, with the testutils feature, for the tests of contracts composing with the allocator.
+
+use soroban_sdk::{contract, contractimpl, contracttype, symbol_short, token, Address, Env};
+
+#[cfg(feature = "hooks")]
+use crate::BudgetHook;
+#[cfg(feature = "oracle")]
+use crate::{Asset, PriceData, PriceOracle};
+#[cfg(feature = "token_vote")]
+use crate::VotesToken;
+
+
+// Price oracle quoting whatever set_price stored, in the base asset set by register_oracle.
+#[cfg(feature = "oracle")]
+#[contract]
+pub struct MockOracle;
+
+#[cfg(feature = "oracle")]
+#[derive(Clone)]
+#[contracttype]
+pub enum MockOracleKey {
+    Base,
+    Decimals,
+    Price(Asset),
+}
+
+#[cfg(feature = "oracle")]
+#[contractimpl]
+impl MockOracle {
+    pub fn set_base(env: Env, base: Asset, decimals: u32) {
+        env.storage().instance().set(&MockOracleKey::Base, &base);
+        env.storage().instance().set(&MockOracleKey::Decimals, &decimals);
+    }
+
+    pub fn set_price(env: Env, asset: Asset, price: i128, timestamp: u64) {
+        env.storage().persistent().set(&MockOracleKey::Price(asset), &PriceData { price, timestamp });
+    }
+}
+
+#[cfg(feature = "oracle")]
+#[contractimpl]
+impl PriceOracle for MockOracle {
+    fn base(env: Env) -> Asset {
+        env.storage().instance().get(&MockOracleKey::Base).unwrap()
+    }
+
+    fn decimals(env: Env) -> u32 {
+        env.storage().instance().get(&MockOracleKey::Decimals).unwrap()
+    }
+
+    fn lastprice(env: Env, asset: Asset) -> Option<PriceData> {
+        env.storage().persistent().get(&MockOracleKey::Price(asset))
+    }
+}
+
+
+// Governance token that keeps one balance per (holder, ledger).
+#[cfg(feature = "token_vote")]
+#[contract]
+pub struct MockVotesToken;
+
+#[cfg(feature = "token_vote")]
+#[contractimpl]
+impl MockVotesToken {
+    pub fn set_balance(env: Env, id: Address, ledger: u32, amount: i128) {
+        env.storage().persistent().set(&(id, ledger), &amount);
+    }
+}
+
+#[cfg(feature = "token_vote")]
+#[contractimpl]
+impl VotesToken for MockVotesToken {
+    fn balance_at(env: Env, id: Address, ledger: u32) -> i128 {
+        env.storage().persistent().get(&(id, ledger)).unwrap_or(0)
+    }
+}
+
+
+// Budget hook that remembers the last notification it received.
+#[cfg(feature = "hooks")]
+#[contract]
+pub struct MockHook;
+
+#[cfg(feature = "hooks")]
+#[contractimpl]
+impl MockHook {
+    pub fn last_change(env: Env) -> Option<(i128, i128, Address)> {
+        env.storage().instance().get(&symbol_short!("last"))
+    }
+}
+
+#[cfg(feature = "hooks")]
+#[contractimpl]
+impl BudgetHook for MockHook {
+    fn on_budget_changed(env: Env, old: i128, new: i128, caller: Address) {
+        env.storage().instance().set(&symbol_short!("last"), &(old, new, caller));
+    }
+}
+
+
+#[cfg(feature = "oracle")]
+pub fn register_oracle(env: &Env, base: &Asset, decimals: u32) -> MockOracleClient<'static> {
+    let client = MockOracleClient::new(env, &env.register_contract(None, MockOracle));
+    client.set_base(base, &decimals);
+    client
+}
+
+#[cfg(feature = "token_vote")]
+pub fn register_votes_token(env: &Env) -> MockVotesTokenClient<'static> {
+    MockVotesTokenClient::new(env, &env.register_contract(None, MockVotesToken))
+}
+
+#[cfg(feature = "hooks")]
+pub fn register_hook(env: &Env) -> MockHookClient<'static> {
+    MockHookClient::new(env, &env.register_contract(None, MockHook))
+}
+
+// A Stellar Asset Contract administered by admin, with the admin client to mint it.
+pub fn register_token(env: &Env, admin: &Address) -> token::StellarAssetClient<'static> {
+    token::StellarAssetClient::new(env, &env.register_stellar_asset_contract_v2(admin.clone()).address())
+}
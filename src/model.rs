@@ -0,0 +1,212 @@
+// Differential tests against a reference model.
+// Model is a plain in-memory implementation of the core rules (operator nomination and removal,
+// pause, granularity, limits and the order in which adjustments are checked), written from the
+// documented behaviour rather than from the contract code. The same random operation sequences
+// are run against the model and a deployed contract, and every outcome (success or the exact
+// error) and the state after every step have to agree. A disagreement points either at a bug in
+// the contract or at storage and serialization behaviour the model does not share.
+
+extern crate std;
+
+use proptest::prelude::*;
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{Address, Env, Vec};
+use std::collections::BTreeSet;
+
+use crate::{BudgetError, BudgetState, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient};
+
+
+// Actor 0 is the owner; any actor may be nominated and try any call.
+const ACTORS: usize = 4;
+const OWNER: usize = 0;
+
+#[derive(Clone, Debug)]
+enum Op {
+    AddOperator(usize, usize),
+    AcceptOperator(usize),
+    RemoveOperator(usize, usize),
+    Increase(usize, i128),
+    Decrease(usize, i128),
+    Pause(usize, bool),
+    SetGranularity(usize, i128),
+}
+// The first usize is the caller, the second the operator concerned
+
+fn op() -> impl Strategy<Value = Op> {
+    // Mostly the owner for owner calls, so that sequences get past the access checks
+    let actor = 0..ACTORS;
+    let manager = prop_oneof![3 => Just(OWNER), 1 => 0..ACTORS];
+    let amount = prop_oneof![Just(0i128), -20i128..0, 1i128..3000, Just(i128::MAX)];
+    prop_oneof![
+        2 => (manager.clone(), actor.clone()).prop_map(|(caller, operator)| Op::AddOperator(caller, operator)),
+        2 => actor.clone().prop_map(Op::AcceptOperator),
+        1 => (manager.clone(), actor.clone()).prop_map(|(caller, operator)| Op::RemoveOperator(caller, operator)),
+        3 => (actor.clone(), amount.clone()).prop_map(|(caller, amount)| Op::Increase(caller, amount)),
+        3 => (actor, amount).prop_map(|(caller, amount)| Op::Decrease(caller, amount)),
+        1 => (manager.clone(), any::<bool>()).prop_map(|(caller, paused)| Op::Pause(caller, paused)),
+        1 => (manager, prop_oneof![Just(-1i128), Just(0), Just(5), Just(10)])
+            .prop_map(|(caller, granularity)| Op::SetGranularity(caller, granularity)),
+    ]
+}
+
+
+#[derive(Debug)]
+struct Model {
+    current: i128,
+    min: i128,
+    max: i128,
+    operators: std::vec::Vec<usize>,
+    nominated: BTreeSet<usize>,
+    paused: bool,
+    granularity: i128,
+}
+
+impl Model {
+    fn new(current: i128, min: i128, max: i128) -> Self {
+        Model { current, min, max, operators: std::vec![], nominated: BTreeSet::new(), paused: false, granularity: 0 }
+    }
+
+    fn owner_only(caller: usize) -> Result<(), BudgetError> {
+        if caller != OWNER {
+            return Err(BudgetError::NotOwner);
+        }
+        Ok(())
+    }
+
+    // Checks shared by both directions, in the contract's order, up to the limit check.
+    fn check_adjustment(&self, caller: usize, amount: i128) -> Result<(), BudgetError> {
+        if !self.operators.contains(&caller) {
+            return Err(BudgetError::NotOperator);
+        }
+        if self.paused {
+            return Err(BudgetError::Paused);
+        }
+        if self.granularity > 0 && amount % self.granularity != 0 {
+            return Err(BudgetError::InvalidGranularity);
+        }
+        match amount {
+            0 => Err(BudgetError::NoOp),
+            amount if amount < 0 => Err(BudgetError::InvalidAmount),
+            _ => Ok(()),
+        }
+    }
+
+    fn apply(&mut self, op: &Op) -> Result<(), BudgetError> {
+        match *op {
+            Op::AddOperator(caller, operator) => {
+                Self::owner_only(caller)?;
+                if self.operators.contains(&operator) {
+                    return Err(BudgetError::AlreadyOperator);
+                }
+                self.nominated.insert(operator);
+            }
+            Op::AcceptOperator(operator) => {
+                if !self.nominated.contains(&operator) {
+                    return Err(BudgetError::NoPendingAction);
+                }
+                if self.operators.contains(&operator) {
+                    return Err(BudgetError::AlreadyOperator);
+                }
+                self.nominated.remove(&operator);
+                self.operators.push(operator);
+            }
+            Op::RemoveOperator(caller, operator) => {
+                Self::owner_only(caller)?;
+                let index = self.operators.iter().position(|listed| *listed == operator);
+                self.operators.remove(index.ok_or(BudgetError::NotOperatorFound)?);
+            }
+            Op::Increase(caller, amount) => {
+                self.check_adjustment(caller, amount)?;
+                let new = self.current.checked_add(amount).ok_or(BudgetError::Overflow)?;
+                if new > self.max {
+                    return Err(BudgetError::ExceedsMax);
+                }
+                self.current = new;
+            }
+            Op::Decrease(caller, amount) => {
+                self.check_adjustment(caller, amount)?;
+                let new = self.current.checked_sub(amount).ok_or(BudgetError::Underflow)?;
+                if new < self.min.max(0) {
+                    return Err(BudgetError::BelowMin);
+                }
+                self.current = new;
+            }
+            Op::Pause(caller, paused) => {
+                Self::owner_only(caller)?;
+                self.paused = paused;
+            }
+            Op::SetGranularity(caller, granularity) => {
+                Self::owner_only(caller)?;
+                if granularity < 0 {
+                    return Err(BudgetError::InvalidConfig);
+                }
+                self.granularity = granularity;
+            }
+        }
+        Ok(())
+    }
+}
+
+
+// The outcome of a contract call as the model reports it.
+fn outcome<T, C>(result: Result<Result<T, C>, Result<BudgetError, soroban_sdk::InvokeError>>) -> Result<(), BudgetError> {
+    match result {
+        Ok(_) => Ok(()),
+        Err(Ok(error)) => Err(error),
+        Err(Err(error)) => panic!("call failed outside the contract: {error:?}"),
+    }
+}
+
+fn run(ops: std::vec::Vec<Op>) {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+    let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+    let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+    let mut actors = Vec::new(&env);
+    for _ in 0..ACTORS {
+        actors.push_back(Address::generate(&env));
+    }
+    let actor = |index: usize| actors.get_unchecked(index as u32);
+    client.initialize(&actor(OWNER), &1000, &100, &5000);
+    let mut model = Model::new(1000, 100, 5000);
+
+    for op in ops {
+        let expected = model.apply(&op);
+        let actual = match op {
+            Op::AddOperator(caller, operator) => outcome(client.try_add_operator(&actor(caller), &actor(operator))),
+            Op::AcceptOperator(operator) => outcome(client.try_accept_operator(&actor(operator))),
+            Op::RemoveOperator(caller, operator) => outcome(client.try_remove_operator(&actor(caller), &actor(operator))),
+            Op::Increase(caller, amount) => outcome(client.try_increase_budget(&actor(caller), &amount)),
+            Op::Decrease(caller, amount) => outcome(client.try_decrease_budget(&actor(caller), &amount)),
+            Op::Pause(caller, true) => outcome(client.try_pause(&actor(caller))),
+            Op::Pause(caller, false) => outcome(client.try_unpause(&actor(caller))),
+            Op::SetGranularity(caller, granularity) => outcome(client.try_set_granularity(&actor(caller), &granularity)),
+        };
+        assert_eq!(actual, expected, "{op:?} on {model:?}");
+
+        let budget = BudgetState { current: model.current, min: model.min, max: model.max };
+        assert_eq!(client.get_budget(), budget, "after {op:?}");
+        let mut operators = Vec::new(&env);
+        for index in &model.operators {
+            operators.push_back(actor(*index));
+        }
+        assert_eq!(client.get_operators(), operators, "after {op:?}");
+        assert_eq!(client.is_paused(), model.paused);
+        assert_eq!(client.get_granularity(), model.granularity);
+        for index in 0..ACTORS {
+            assert_eq!(client.is_nominated(&actor(index)), model.nominated.contains(&index));
+        }
+    }
+}
+
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn test_contract_matches_model(ops in prop::collection::vec(op(), 1..40)) {
+        run(ops);
+    }
+}
@@ -0,0 +1,221 @@
+// Optimistic execution with challenge bonds.
+// In optimistic mode routine proposals do not need a ratification or a vote: once the optimistic
+// delay has passed since creation anyone can execute them, unless someone challenged them first.
+// A challenger locks a bond in the configured token. The owner then rules on the challenge:
+// upholding it rejects the proposal and refunds the bond; dismissing it forfeits the bond to this
+// contract and lets the proposal execute after all.
+
+use soroban_sdk::{contractimpl, contracttype, token, Address, Env};
+
+use crate::{
+    proposal, require_owner, treasury, BudgetError, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient,
+    ProposalStatus,
+};
+#[cfg(feature = "token_vote")]
+use crate::token_vote;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct OptimisticConfig {
+    pub token: Address,
+    pub bond: i128,
+    pub delay: u32,
+}
+// OptimisticConfig defines optimistic execution:
+// token and bond are the token and amount a challenger has to lock
+// delay is how many ledgers after creation an unchallenged proposal becomes executable
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Challenge {
+    pub challenger: Address,
+    pub token: Address,
+    pub bond: i128,
+}
+// Challenge stores a pending challenge with the bond it locked (token and amount at challenge time).
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum OptimisticKey {
+    Optimistic,
+    Challenge(u64),
+    Dismissed(u64),
+}
+// Optimistic stores the OptimisticConfig (missing means optimistic mode is off)
+// Challenge stores the pending Challenge against a proposal
+// Dismissed marks a proposal whose challenge was dismissed, so it cannot be challenged again
+
+
+fn config(env: &Env) -> Result<OptimisticConfig, BudgetError> {
+    env.storage().persistent().get(&OptimisticKey::Optimistic).ok_or(BudgetError::InvalidConfig)
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Turn on optimistic execution with the given bond and delay (owner only)
+    pub fn set_optimistic(env: Env, caller: Address, token: Address, bond: i128, delay: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        if bond <= 0 || delay == 0 {
+            return Err(BudgetError::InvalidConfig);
+        }
+        let config = OptimisticConfig { token, bond, delay };
+        env.storage().persistent().set(&OptimisticKey::Optimistic, &config);
+        Ok(())
+    }
+
+    /// Turn optimistic execution off (owner only)
+    pub fn clear_optimistic(env: Env, caller: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().remove(&OptimisticKey::Optimistic);
+        Ok(())
+    }
+
+    /// Challenge an open proposal by locking the bond (anyone)
+    pub fn challenge(env: Env, caller: Address, id: u64) -> Result<(), BudgetError> {
+        caller.require_auth();
+        let config = config(&env)?;
+        let proposal = proposal::read_proposal(&env, id)?;
+        proposal::require_open(&env, &proposal)?;
+
+        let key = OptimisticKey::Challenge(id);
+        if env.storage().persistent().has(&key) || env.storage().persistent().has(&OptimisticKey::Dismissed(id)) {
+            return Err(BudgetError::AlreadyRegistered);
+        }
+        token::Client::new(&env, &config.token).transfer(&caller, &env.current_contract_address(), &config.bond);
+        treasury::hold(&env, &config.token, config.bond);
+        let challenge = Challenge { challenger: caller, token: config.token, bond: config.bond };
+        env.storage().persistent().set(&key, &challenge);
+        Ok(())
+    }
+
+    /// Rule on a challenge: uphold rejects the proposal and refunds the bond, dismiss forfeits it (owner only)
+    pub fn resolve_challenge(env: Env, caller: Address, id: u64, uphold: bool) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        let key = OptimisticKey::Challenge(id);
+        let challenge: Challenge = env.storage().persistent().get(&key).ok_or(BudgetError::NotRegistered)?;
+        env.storage().persistent().remove(&key);
+        treasury::release(&env, &challenge.token, challenge.bond);
+
+        if uphold {
+            proposal::close(&env, id, ProposalStatus::Rejected)?;
+            let client = token::Client::new(&env, &challenge.token);
+            client.transfer(&env.current_contract_address(), &challenge.challenger, &challenge.bond);
+        } else {
+            env.storage().persistent().set(&OptimisticKey::Dismissed(id), &true);
+        }
+        Ok(())
+    }
+
+    /// Execute an unchallenged proposal once the optimistic delay has passed (anyone)
+    pub fn execute_optimistic(env: Env, id: u64) -> Result<(), BudgetError> {
+        let config = config(&env)?;
+        let mut proposal = proposal::read_proposal(&env, id)?;
+        proposal::require_open(&env, &proposal)?;
+        if env.storage().persistent().has(&OptimisticKey::Challenge(id)) {
+            return Err(BudgetError::ProposalChallenged);
+        }
+        if env.ledger().sequence() < proposal.created_ledger.saturating_add(config.delay) {
+            return Err(BudgetError::TimelockActive);
+        }
+        #[cfg(feature = "token_vote")]
+        token_vote::gate_proposal(&env, &proposal)?;
+        proposal::execute_unratified(&env, &mut proposal)
+    }
+
+    // Get the optimistic execution configuration, if any
+    pub fn get_optimistic(env: Env) -> Option<OptimisticConfig> {
+        config(&env).ok()
+    }
+
+    // Get the pending challenge against a proposal, if any
+    pub fn get_challenge(env: Env, id: u64) -> Option<Challenge> {
+        env.storage().persistent().get(&OptimisticKey::Challenge(id))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::{symbol_short, token, BytesN, String};
+
+    fn metadata(env: &Env) -> ProposalMetadata {
+        ProposalMetadata {
+            title: symbol_short!("limits"),
+            memo: String::from_str(env, "raise the limits"),
+            recipient: None,
+            content_hash: BytesN::from_array(env, &[1; 32]),
+        }
+    }
+
+    #[test]
+    fn test_unchallenged_proposal_executes() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let sac = env.register_stellar_asset_contract_v2(owner.clone());
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_optimistic(&owner, &sac.address(), &100, &50);
+
+        let id = client.propose_limits(&operator, &0, &20000, &metadata(&env));
+        assert_eq!(client.try_execute_optimistic(&id), Err(Ok(BudgetError::TimelockActive)));
+
+        env.ledger().with_mut(|l| l.sequence_number = 50);
+        client.execute_optimistic(&id);
+        assert_eq!(client.get_budget().max, 20000);
+    }
+
+    #[test]
+    fn test_challenge_rulings() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let challenger = Address::generate(&env);
+        let sac = env.register_stellar_asset_contract_v2(owner.clone());
+        let bond_token = token::Client::new(&env, &sac.address());
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        token::StellarAssetClient::new(&env, &sac.address()).mint(&challenger, &200);
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_optimistic(&owner, &sac.address(), &100, &50);
+
+        // An upheld challenge rejects the proposal and refunds the bond
+        let first = client.propose_limits(&operator, &0, &20000, &metadata(&env));
+        client.challenge(&challenger, &first);
+        assert_eq!(bond_token.balance(&challenger), 100);
+        env.ledger().with_mut(|l| l.sequence_number = 50);
+        assert_eq!(client.try_execute_optimistic(&first), Err(Ok(BudgetError::ProposalChallenged)));
+        client.resolve_challenge(&owner, &first, &true);
+        assert_eq!(bond_token.balance(&challenger), 200);
+        assert_eq!(client.get_proposal_status(&first), ProposalStatus::Rejected);
+
+        // A dismissed challenge forfeits the bond and the proposal goes ahead
+        let second = client.propose_limits(&operator, &0, &15000, &metadata(&env));
+        client.challenge(&challenger, &second);
+        client.resolve_challenge(&owner, &second, &false);
+        assert_eq!(bond_token.balance(&contract_id), 100);
+        assert_eq!(client.try_challenge(&challenger, &second), Err(Ok(BudgetError::AlreadyRegistered)));
+        env.ledger().with_mut(|l| l.sequence_number = 100);
+        client.execute_optimistic(&second);
+        assert_eq!(client.get_budget().max, 15000);
+    }
+}
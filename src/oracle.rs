@@ -0,0 +1,133 @@
+// Budget figures converted through a price oracle.
+// The owner configures a SEP-40 style price oracle, the asset the budget is denominated in and
+// the maximum age of a price. get_budget_in(asset) then returns current/min/max converted into
+// another asset (or currency, e.g. Other("USD")) at the latest prices. Prices are quoted in the
+// oracle's base asset, so converting into anything but the base goes through the cross rate.
+// Prices older than max_age seconds are rejected with StalePrice rather than used.
+
+use soroban_sdk::{contractclient, contractimpl, contracttype, Address, Env, Symbol};
+
+use crate::{
+    read_budget, require_owner, BudgetError, BudgetState, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient,
+};
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub enum Asset {
+    Stellar(Address),
+    Other(Symbol),
+}
+// Asset identifies a priced asset: a Stellar token contract or an off-chain ticker such as USD
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PriceData {
+    pub price: i128,
+    pub timestamp: u64,
+}
+// PriceData is a price in the oracle's base asset, scaled by 10^decimals, with its timestamp in seconds
+
+
+// The subset of the SEP-40 oracle interface used here.
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracle {
+    fn base(env: Env) -> Asset;
+    fn decimals(env: Env) -> u32;
+    fn lastprice(env: Env, asset: Asset) -> Option<PriceData>;
+}
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct OracleConfig {
+    pub oracle: Address,
+    pub budget_asset: Asset,
+    pub max_age: u64,
+}
+// OracleConfig configures conversions:
+// budget_asset is the asset the budget amounts are denominated in
+// max_age is the oldest a price may be, in seconds
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum OracleKey {
+    Oracle,
+}
+// Oracle stores the OracleConfig (missing means conversions are off)
+
+
+// The latest fresh price of an asset in the oracle's base, the base itself being 1.
+fn price(env: &Env, client: &PriceOracleClient, config: &OracleConfig, asset: &Asset) -> Result<i128, BudgetError> {
+    if *asset == client.base() {
+        return Ok(10i128.pow(client.decimals()));
+    }
+    let data = client.lastprice(asset).ok_or(BudgetError::StalePrice)?;
+    if data.price <= 0 || env.ledger().timestamp().saturating_sub(data.timestamp) > config.max_age {
+        return Err(BudgetError::StalePrice);
+    }
+    Ok(data.price)
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Configure the price oracle used for conversions (owner only)
+    pub fn set_oracle(env: Env, caller: Address, oracle: Address, budget_asset: Asset, max_age: u64) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().set(&OracleKey::Oracle, &OracleConfig { oracle, budget_asset, max_age });
+        Ok(())
+    }
+
+    // Get the oracle configuration, if any
+    pub fn get_oracle(env: Env) -> Option<OracleConfig> {
+        env.storage().persistent().get(&OracleKey::Oracle)
+    }
+
+    // Get current, min and max converted into another asset at the latest prices
+    pub fn get_budget_in(env: Env, asset: Asset) -> Result<BudgetState, BudgetError> {
+        let config: OracleConfig = env.storage().persistent().get(&OracleKey::Oracle).ok_or(BudgetError::NotRegistered)?;
+        let client = PriceOracleClient::new(&env, &config.oracle);
+        let from = price(&env, &client, &config, &config.budget_asset)?;
+        let to = price(&env, &client, &config, &asset)?;
+
+        let convert = |amount: i128| amount.checked_mul(from).map(|value| value / to).ok_or(BudgetError::Overflow);
+        let budget = read_budget(&env);
+        Ok(BudgetState { current: convert(budget.current)?, min: convert(budget.min)?, max: convert(budget.max)? })
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::symbol_short;
+
+    #[test]
+    fn test_budget_in_usd() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+        // XLM at 0.10 USD, last updated at timestamp 1000
+        let oracle = mocks::register_oracle(&env, &Asset::Other(symbol_short!("USD")), 7);
+        oracle.set_price(&Asset::Other(symbol_short!("XLM")), &1_000_000, &1000);
+
+        let owner = Address::generate(&env);
+
+        client.initialize(&owner, &5000, &0, &10000);
+
+        env.mock_all_auths();
+        client.set_oracle(&owner, &oracle.address, &Asset::Other(symbol_short!("XLM")), &600);
+        env.ledger().with_mut(|l| l.timestamp = 1500);
+
+        let usd = client.get_budget_in(&Asset::Other(symbol_short!("USD")));
+        assert_eq!(usd, BudgetState { current: 500, min: 0, max: 1000 });
+        assert_eq!(client.try_get_budget_in(&Asset::Other(symbol_short!("EUR"))), Err(Ok(BudgetError::StalePrice)));
+
+        env.ledger().with_mut(|l| l.timestamp = 1601);
+        assert_eq!(client.try_get_budget_in(&Asset::Other(symbol_short!("USD"))), Err(Ok(BudgetError::StalePrice)));
+    }
+}
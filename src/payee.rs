@@ -0,0 +1,151 @@
+// Payee allowlist.
+// The owner or the admin keeps a list of vetted recipients, each with an optional cap on the total
+// it may be paid. Payments to a listed payee count towards its cap everywhere funds leave for a
+// named recipient (spend, spend_batch, create_payout). Once the owner turns the allowlist on,
+// payments to anyone not on it fail with PayeeNotAllowed. With the allowlist off, listed payees
+// are still capped and they are the recipients the RecipientApproval policy rule lets through.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env};
+
+use crate::{
+    require_operator_manager, require_owner, BudgetError, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient,
+};
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Payee {
+    pub cap: i128,
+    pub paid: i128,
+}
+// Payee stores a listed recipient:
+// cap is the most it may be paid in total (0 means no cap)
+// paid is what it was paid since it was listed
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum PayeeKey {
+    PayeeAllowlist,
+    Payee(Address),
+}
+// PayeeAllowlist stores whether payments to unlisted recipients are rejected
+// Payee stores the Payee of a listed recipient
+
+
+fn read_payee(env: &Env, recipient: &Address) -> Option<Payee> {
+    env.storage().persistent().get(&PayeeKey::Payee(recipient.clone()))
+}
+
+pub(crate) fn is_payee(env: &Env, recipient: &Address) -> bool {
+    env.storage().persistent().has(&PayeeKey::Payee(recipient.clone()))
+}
+
+fn enforced(env: &Env) -> bool {
+    env.storage().persistent().get(&PayeeKey::PayeeAllowlist).unwrap_or(false)
+}
+
+// Checks a payment against the allowlist and counts it towards the payee's cap.
+pub(crate) fn charge(env: &Env, recipient: &Address, amount: i128) -> Result<(), BudgetError> {
+    let Some(mut payee) = read_payee(env, recipient) else {
+        if enforced(env) {
+            return Err(BudgetError::PayeeNotAllowed);
+        }
+        return Ok(());
+    };
+    payee.paid = payee.paid.checked_add(amount).ok_or(BudgetError::Overflow)?;
+    if payee.cap > 0 && payee.paid > payee.cap {
+        return Err(BudgetError::ExceedsMax);
+    }
+    env.storage().persistent().set(&PayeeKey::Payee(recipient.clone()), &payee);
+    Ok(())
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// List a payee or change its cap, 0 for no cap (owner or admin)
+    pub fn set_payee(env: Env, caller: Address, payee: Address, cap: i128) -> Result<(), BudgetError> {
+        require_operator_manager(&env, &caller)?;
+        if cap < 0 {
+            return Err(BudgetError::InvalidConfig);
+        }
+        let paid = read_payee(&env, &payee).map_or(0, |listed| listed.paid);
+        env.storage().persistent().set(&PayeeKey::Payee(payee), &Payee { cap, paid });
+        Ok(())
+    }
+
+    /// Remove a payee from the allowlist (owner or admin)
+    pub fn remove_payee(env: Env, caller: Address, payee: Address) -> Result<(), BudgetError> {
+        require_operator_manager(&env, &caller)?;
+        if !is_payee(&env, &payee) {
+            return Err(BudgetError::NotRegistered);
+        }
+        env.storage().persistent().remove(&PayeeKey::Payee(payee));
+        Ok(())
+    }
+
+    /// Reject or accept payments to recipients that are not on the allowlist (owner only)
+    pub fn set_payee_allowlist(env: Env, caller: Address, enabled: bool) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().set(&PayeeKey::PayeeAllowlist, &enabled);
+        Ok(())
+    }
+
+    // Check if payments to unlisted recipients are rejected
+    pub fn is_payee_allowlist_enabled(env: Env) -> bool {
+        enforced(&env)
+    }
+
+    // Get a listed payee, if any
+    pub fn get_payee(env: Env, payee: Address) -> Option<Payee> {
+        read_payee(&env, &payee)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::String;
+
+    #[test]
+    fn test_payee_allowlist() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let admin = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let vendor = Address::generate(&env);
+        let stranger = Address::generate(&env);
+        let backing = mocks::register_token(&env, &owner);
+
+        client.initialize(&owner, &5000, &0, &10000);
+
+        env.mock_all_auths();
+        backing.mint(&contract_id, &5000);
+        client.set_token(&owner, &backing.address);
+        client.set_admin(&owner, &admin);
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_payee(&admin, &vendor, &500);
+        client.set_payee_allowlist(&owner, &true);
+
+        let memo = String::from_str(&env, "invoice");
+        assert_eq!(client.try_spend(&operator, &stranger, &100, &memo), Err(Ok(BudgetError::PayeeNotAllowed)));
+        client.spend(&operator, &vendor, &300, &memo);
+        assert_eq!(client.get_payee(&vendor).unwrap().paid, 300);
+
+        // The cap covers the total paid
+        assert_eq!(client.try_spend(&operator, &vendor, &300, &memo), Err(Ok(BudgetError::ExceedsMax)));
+        client.spend(&operator, &vendor, &200, &memo);
+
+        // Off again: unlisted recipients can be paid
+        client.set_payee_allowlist(&owner, &false);
+        client.spend(&operator, &stranger, &100, &memo);
+        assert_eq!(client.get_budget().current, 4400);
+    }
+}
@@ -0,0 +1,201 @@
+// Spending policy engine.
+// Instead of a separate feature per spending rule, the owner sets a list of PolicyRule values that
+// is evaluated the same way before every adjustment (increase_for/decrease_for) and, for the
+// rules about recipients, before every payment that names one (spend, spend_batch, create_payout).
+// A rule that does not hold fails the call with PolicyViolation, or with ConfirmationRequired when
+// the owner could allow it by approving the spend with approve_spend.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env, Vec};
+
+use crate::{read_budget, require_owner, BudgetError, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient};
+#[cfg(feature = "payee")]
+use crate::payee;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub enum PolicyRule {
+    MaxAmount(i128),
+    SpendLimit(u32, u32),
+    RecipientApproval,
+}
+// PolicyRule is one rule of the spending policy:
+// MaxAmount caps every single adjustment and payment
+// SpendLimit(bps, ledgers) caps the decreases within each period of ledgers to bps of max
+// RecipientApproval makes every payment to a recipient that is not a listed payee need an owner
+// approval for its recipient and amount
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum PolicyKey {
+    Policies,
+    PolicyWindow,
+    SpendApproval(Address),
+}
+// Policies stores the list of PolicyRule (missing means no rules)
+// PolicyWindow stores (period, total decreased in it) for the SpendLimit rule
+// SpendApproval stores the amount the owner approved paying a recipient, used up by one payment
+
+
+// Every rule is evaluated on every adjustment, so the list stays small.
+const MAX_RULES: u32 = 10;
+
+fn read_policies(env: &Env) -> Vec<PolicyRule> {
+    env.storage().persistent().get(&PolicyKey::Policies).unwrap_or(Vec::new(env))
+}
+
+// The period and how much was decreased in it so far, zero once a new period started.
+fn window(env: &Env, ledgers: u32) -> (u32, i128) {
+    let period = env.ledger().sequence() / ledgers.max(1);
+    let (stored, spent): (u32, i128) = env.storage().persistent().get(&PolicyKey::PolicyWindow).unwrap_or((period, 0));
+    if stored == period {
+        (period, spent)
+    } else {
+        (period, 0)
+    }
+}
+
+// Evaluates the rules about the adjustment itself.
+pub(crate) fn check(env: &Env, amount: i128, increase: bool) -> Result<(), BudgetError> {
+    for rule in read_policies(env).iter() {
+        match rule {
+            PolicyRule::MaxAmount(max) if amount > max => return Err(BudgetError::PolicyViolation),
+            PolicyRule::SpendLimit(bps, ledgers) if !increase => {
+                let limit = read_budget(env).max.saturating_mul(bps.into()) / 10_000;
+                if window(env, ledgers).1.saturating_add(amount) > limit {
+                    return Err(BudgetError::PolicyViolation);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// Evaluates the rules about a payment's recipient, using up the owner approval it needs.
+pub(crate) fn check_recipient(env: &Env, recipient: &Address, amount: i128) -> Result<(), BudgetError> {
+    #[cfg(feature = "payee")]
+    if payee::is_payee(env, recipient) {
+        return Ok(());
+    }
+    if !read_policies(env).contains(PolicyRule::RecipientApproval) {
+        return Ok(());
+    }
+    let key = PolicyKey::SpendApproval(recipient.clone());
+    let approved: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+    if approved != amount {
+        return Err(BudgetError::ConfirmationRequired);
+    }
+    env.storage().persistent().remove(&key);
+    Ok(())
+}
+
+// Called for every committed adjustment, counts decreases towards the SpendLimit rule.
+pub(crate) fn record(env: &Env, previous: i128, new: i128) {
+    if new >= previous {
+        return;
+    }
+    for rule in read_policies(env).iter() {
+        if let PolicyRule::SpendLimit(_, ledgers) = rule {
+            let (period, spent) = window(env, ledgers);
+            env.storage().persistent().set(&PolicyKey::PolicyWindow, &(period, spent.saturating_add(previous - new)));
+            return;
+        }
+    }
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Replace the spending policy rules (owner only)
+    pub fn set_policies(env: Env, caller: Address, rules: Vec<PolicyRule>) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        if rules.len() > MAX_RULES {
+            return Err(BudgetError::InvalidConfig);
+        }
+        for rule in rules.iter() {
+            match rule {
+                PolicyRule::MaxAmount(max) if max <= 0 => return Err(BudgetError::InvalidConfig),
+                PolicyRule::SpendLimit(bps, ledgers) if bps > 10_000 || ledgers == 0 => {
+                    return Err(BudgetError::InvalidConfig)
+                }
+                _ => {}
+            }
+        }
+        env.storage().persistent().set(&PolicyKey::Policies, &rules);
+        Ok(())
+    }
+
+    /// Approve one payment of exactly amount to a recipient under the RecipientApproval rule (owner only)
+    pub fn approve_spend(env: Env, caller: Address, recipient: Address, amount: i128) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        if amount <= 0 {
+            return Err(BudgetError::InvalidAmount);
+        }
+        env.storage().persistent().set(&PolicyKey::SpendApproval(recipient), &amount);
+        Ok(())
+    }
+
+    // Get the spending policy rules
+    pub fn get_policies(env: Env) -> Vec<PolicyRule> {
+        read_policies(&env)
+    }
+
+    // Get the payment amount approved for a recipient, if any
+    pub fn get_spend_approval(env: Env, recipient: Address) -> Option<i128> {
+        env.storage().persistent().get(&PolicyKey::SpendApproval(recipient))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::{token, vec, String};
+
+    #[test]
+    fn test_policy_rules() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let recipient = Address::generate(&env);
+        let sac = env.register_stellar_asset_contract_v2(owner.clone());
+
+        client.initialize(&owner, &5000, &0, &10000);
+
+        env.mock_all_auths();
+        token::StellarAssetClient::new(&env, &sac.address()).mint(&contract_id, &5000);
+        client.set_token(&owner, &sac.address());
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+
+        // At most 2000 per adjustment, and 10% of max (1000) decreased per 100 ledgers
+        let rules = vec![
+            &env,
+            PolicyRule::MaxAmount(2000),
+            PolicyRule::SpendLimit(1000, 100),
+            PolicyRule::RecipientApproval,
+        ];
+        client.set_policies(&owner, &rules);
+        assert_eq!(client.try_increase_budget(&operator, &2500), Err(Ok(BudgetError::PolicyViolation)));
+        client.decrease_budget(&operator, &600);
+        assert_eq!(client.try_decrease_budget(&operator, &500), Err(Ok(BudgetError::PolicyViolation)));
+
+        // Payments need an approval matching the amount, which one payment uses up
+        let memo = String::from_str(&env, "grant");
+        assert_eq!(client.try_spend(&operator, &recipient, &400, &memo), Err(Ok(BudgetError::ConfirmationRequired)));
+        client.approve_spend(&owner, &recipient, &400);
+        client.spend(&operator, &recipient, &400, &memo);
+        assert_eq!(client.get_spend_approval(&recipient), None);
+
+        // A new period starts from zero
+        env.ledger().with_mut(|l| l.sequence_number = 100);
+        client.decrease_budget(&operator, &1000);
+        assert_eq!(client.get_budget().current, 3000);
+    }
+}
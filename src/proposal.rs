@@ -0,0 +1,348 @@
+// Operator proposals with owner ratification.
+// Operators see the need for new limits first but must not be able to change them on their own.
+// An operator files a proposal, the owner ratifies it, and only then it is executed. Proposals
+// that are not ratified before their expiry ledger expire and can no longer be executed.
+// The proposer can withdraw an open proposal with cancel_proposal and the owner can turn it down
+// with reject_proposal; both close it for good.
+
+use soroban_sdk::{contractimpl, contracttype, Address, BytesN, Env, String, Symbol};
+
+use crate::{
+    admin::check_limits, read_budget, require_operator, require_owner, rotation, write_budget, BudgetError,
+    GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient,
+};
+#[cfg(feature = "deposit")]
+use crate::deposit;
+#[cfg(feature = "token_vote")]
+use crate::token_vote;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub enum ProposalAction {
+    SetLimits(i128, i128),
+}
+// ProposalAction is what a proposal executes once ratified:
+// SetLimits(min, max) replaces the limits, clamping current into them
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub enum ProposalStatus {
+    Pending,
+    Ratified,
+    Cancelled,
+    Rejected,
+    Expired,
+}
+// Pending proposals wait for the owner; Ratified proposals have been executed.
+// Cancelled proposals were withdrawn by the proposer, Rejected ones turned down by the owner.
+// Expired is never stored: queries report it for pending proposals past their expiry ledger.
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ProposalMetadata {
+    pub title: Symbol,
+    pub memo: String,
+    pub recipient: Option<Address>,
+    pub content_hash: BytesN<32>,
+}
+// ProposalMetadata gives voters context for a proposal:
+// title is a short name and memo a free-form description
+// recipient is who the proposal is meant to benefit, if anyone in particular
+// content_hash is the hash of an off-chain document (e.g. the digest of an IPFS CID)
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Proposal {
+    pub id: u64,
+    pub proposer: Address,
+    pub action: ProposalAction,
+    pub created_ledger: u32,
+    pub expiry_ledger: u32,
+    pub status: ProposalStatus,
+    pub metadata: ProposalMetadata,
+}
+// Proposal stores one proposal:
+// expiry_ledger is the last ledger the proposal can be ratified in
+// metadata is the context supplied by the proposer
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum ProposalKey {
+    ProposalCount,
+    Proposal(u64),
+    ProposalTtl,
+}
+// ProposalCount stores the number of proposals (the next proposal id)
+// Proposal stores a Proposal by id
+// ProposalTtl stores how many ledgers a new proposal stays open
+
+
+// Roughly one day of ledgers.
+const DEFAULT_PROPOSAL_TTL: u32 = 17_280;
+
+fn proposal_ttl(env: &Env) -> u32 {
+    env.storage().persistent().get(&ProposalKey::ProposalTtl).unwrap_or(DEFAULT_PROPOSAL_TTL)
+}
+
+pub(crate) fn read_proposal(env: &Env, id: u64) -> Result<Proposal, BudgetError> {
+    env.storage().persistent().get(&ProposalKey::Proposal(id)).ok_or(BudgetError::ProposalNotFound)
+}
+
+pub(crate) fn write_proposal(env: &Env, proposal: &Proposal) {
+    env.storage().persistent().set(&ProposalKey::Proposal(proposal.id), proposal);
+}
+
+// Stores a new pending proposal and returns its id.
+pub(crate) fn create(env: &Env, proposer: &Address, action: ProposalAction, metadata: ProposalMetadata) -> u64 {
+    let id: u64 = env.storage().persistent().get(&ProposalKey::ProposalCount).unwrap_or(0);
+    let now = env.ledger().sequence();
+    let proposal = Proposal {
+        id,
+        proposer: proposer.clone(),
+        action,
+        created_ledger: now,
+        expiry_ledger: now.saturating_add(proposal_ttl(env)),
+        status: ProposalStatus::Pending,
+        metadata,
+    };
+    write_proposal(env, &proposal);
+    env.storage().persistent().set(&ProposalKey::ProposalCount, &(id + 1));
+    #[cfg(feature = "deposit")]
+    deposit::lock(env, proposer, id);
+    id
+}
+
+// Checks that a proposal can still be acted on.
+pub(crate) fn require_open(env: &Env, proposal: &Proposal) -> Result<(), BudgetError> {
+    if proposal.status != ProposalStatus::Pending {
+        return Err(BudgetError::ProposalClosed);
+    }
+    if env.ledger().sequence() > proposal.expiry_ledger {
+        return Err(BudgetError::ProposalExpired);
+    }
+    Ok(())
+}
+
+// The stored proposal with Expired reported for a pending proposal past its expiry ledger.
+fn with_effective_status(env: &Env, mut proposal: Proposal) -> Proposal {
+    if proposal.status == ProposalStatus::Pending && env.ledger().sequence() > proposal.expiry_ledger {
+        proposal.status = ProposalStatus::Expired;
+    }
+    proposal
+}
+
+// Closes an open proposal without executing it.
+pub(crate) fn close(env: &Env, id: u64, status: ProposalStatus) -> Result<(), BudgetError> {
+    let mut proposal = read_proposal(env, id)?;
+    require_open(env, &proposal)?;
+    proposal.status = status;
+    write_proposal(env, &proposal);
+    #[cfg(feature = "deposit")]
+    deposit::refund(env, &proposal);
+    Ok(())
+}
+
+// Runs the proposal's action and marks it ratified.
+pub(crate) fn execute(env: &Env, proposal: &mut Proposal) -> Result<(), BudgetError> {
+    match proposal.action {
+        ProposalAction::SetLimits(min, max) => {
+            check_limits(env, min, max)?;
+            let mut budget = read_budget(env);
+            budget.min = min;
+            budget.max = max;
+            budget.current = budget.current.clamp(min, max);
+            write_budget(env, &budget);
+        }
+    }
+    proposal.status = ProposalStatus::Ratified;
+    write_proposal(env, proposal);
+    #[cfg(feature = "deposit")]
+    deposit::refund(env, proposal);
+    Ok(())
+}
+
+// Same as execute, for proposals executed without the owner (by vote or optimistically). Those may
+// not clamp current into the new limits: like SetLimits without confirmation, a proposal whose
+// limits exclude current fails with ConfirmationRequired and needs the owner to ratify it.
+pub(crate) fn execute_unratified(env: &Env, proposal: &mut Proposal) -> Result<(), BudgetError> {
+    match proposal.action {
+        ProposalAction::SetLimits(min, max) => {
+            let current = read_budget(env).current;
+            if current < min || current > max {
+                return Err(BudgetError::ConfirmationRequired);
+            }
+        }
+    }
+    execute(env, proposal)
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Propose new limits for the owner to ratify (operators only), returns the proposal id
+    pub fn propose_limits(env: Env, caller: Address, min: i128, max: i128, metadata: ProposalMetadata) -> Result<u64, BudgetError> {
+        require_operator(&env, &caller)?;
+        check_limits(&env, min, max)?;
+        Ok(create(&env, &caller, ProposalAction::SetLimits(min, max), metadata))
+    }
+
+    /// Ratify and execute a pending proposal (owner only)
+    pub fn ratify_proposal(env: Env, caller: Address, id: u64) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        let mut proposal = read_proposal(&env, id)?;
+        require_open(&env, &proposal)?;
+        #[cfg(feature = "token_vote")]
+        token_vote::gate_proposal(&env, &proposal)?;
+        execute(&env, &mut proposal)
+    }
+
+    /// Withdraw an open proposal (proposer only)
+    pub fn cancel_proposal(env: Env, caller: Address, id: u64) -> Result<(), BudgetError> {
+        caller.require_auth();
+        if !rotation::is_same(&env, &read_proposal(&env, id)?.proposer, &caller) {
+            return Err(BudgetError::NotOperator);
+        }
+        close(&env, id, ProposalStatus::Cancelled)
+    }
+
+    /// Turn down an open proposal (owner only)
+    pub fn reject_proposal(env: Env, caller: Address, id: u64) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        close(&env, id, ProposalStatus::Rejected)
+    }
+
+    /// Set how many ledgers new proposals stay open (owner only)
+    pub fn set_proposal_ttl(env: Env, caller: Address, ledgers: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        if ledgers == 0 {
+            return Err(BudgetError::InvalidConfig);
+        }
+        env.storage().persistent().set(&ProposalKey::ProposalTtl, &ledgers);
+        Ok(())
+    }
+
+    // Get a proposal by id with its metadata (status Expired once it can no longer be ratified)
+    pub fn get_proposal(env: Env, id: u64) -> Option<Proposal> {
+        read_proposal(&env, id).ok().map(|proposal| with_effective_status(&env, proposal))
+    }
+
+    // Get the status of a proposal, including Expired
+    pub fn get_proposal_status(env: Env, id: u64) -> Result<ProposalStatus, BudgetError> {
+        Ok(with_effective_status(&env, read_proposal(&env, id)?).status)
+    }
+
+    // Get the number of proposals filed
+    pub fn get_proposal_count(env: Env) -> u64 {
+        env.storage().persistent().get(&ProposalKey::ProposalCount).unwrap_or(0)
+    }
+
+    // Get how many ledgers new proposals stay open
+    pub fn get_proposal_ttl(env: Env) -> u32 {
+        proposal_ttl(&env)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::{symbol_short, BytesN, String};
+
+    fn metadata(env: &Env) -> ProposalMetadata {
+        ProposalMetadata {
+            title: symbol_short!("limits"),
+            memo: String::from_str(env, "raise the limits"),
+            recipient: None,
+            content_hash: BytesN::from_array(env, &[1; 32]),
+        }
+    }
+
+    #[test]
+    fn test_ratify_limit_proposal() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+
+        let id = client.propose_limits(&operator, &500, &20000, &metadata(&env));
+        assert_eq!(client.get_budget().max, 10000);
+        assert_eq!(client.get_proposal(&id).unwrap().metadata, metadata(&env));
+
+        // Operators cannot ratify their own proposals
+        assert_eq!(client.try_ratify_proposal(&operator, &id), Err(Ok(BudgetError::NotOwner)));
+
+        client.ratify_proposal(&owner, &id);
+        let budget = client.get_budget();
+        assert_eq!((budget.min, budget.max), (500, 20000));
+        assert_eq!(client.get_proposal(&id).unwrap().status, ProposalStatus::Ratified);
+        assert_eq!(client.try_ratify_proposal(&owner, &id), Err(Ok(BudgetError::ProposalClosed)));
+    }
+
+    #[test]
+    fn test_expired_proposal() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_proposal_ttl(&owner, &50);
+
+        let id = client.propose_limits(&operator, &0, &20000, &metadata(&env));
+        assert_eq!(client.get_proposal_status(&id), ProposalStatus::Pending);
+        env.ledger().with_mut(|l| l.sequence_number += 51);
+        assert_eq!(client.try_ratify_proposal(&owner, &id), Err(Ok(BudgetError::ProposalExpired)));
+        assert_eq!(client.get_proposal_status(&id), ProposalStatus::Expired);
+        assert_eq!(client.get_proposal(&id).unwrap().status, ProposalStatus::Expired);
+    }
+
+    #[test]
+    fn test_cancel_and_reject() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let other = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.add_operator(&owner, &other);
+        client.accept_operator(&other);
+
+        let first = client.propose_limits(&operator, &0, &20000, &metadata(&env));
+        assert_eq!(client.try_cancel_proposal(&other, &first), Err(Ok(BudgetError::NotOperator)));
+        client.cancel_proposal(&operator, &first);
+        assert_eq!(client.get_proposal_status(&first), ProposalStatus::Cancelled);
+        assert_eq!(client.try_ratify_proposal(&owner, &first), Err(Ok(BudgetError::ProposalClosed)));
+
+        let second = client.propose_limits(&operator, &0, &20000, &metadata(&env));
+        client.reject_proposal(&owner, &second);
+        assert_eq!(client.get_proposal_status(&second), ProposalStatus::Rejected);
+        assert_eq!(client.try_cancel_proposal(&operator, &second), Err(Ok(BudgetError::ProposalClosed)));
+    }
+}
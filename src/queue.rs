@@ -0,0 +1,248 @@
+// Timelocked adjustment queue.
+// Instead of adjusting right away, an operator can queue an increase or decrease that only becomes
+// executable once the owner-configured adjustment delay has passed. Anyone can then execute a
+// matured item with execute_queued, or up to `limit` of them at once with execute_ready.
+// Execution re-runs every check of a normal adjustment for the operator who queued it; in a batch
+// an item that fails those checks is skipped and stays queued so it can be retried or cancelled.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env, Vec};
+
+use crate::{
+    check_operator, decrease_for, increase_for, read_owner, require_operator, require_owner, rotation, BudgetError,
+    GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient, Receipt,
+};
+#[cfg(feature = "category")]
+use crate::category;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct QueuedAdjustment {
+    pub id: u64,
+    pub caller: Address,
+    pub amount: i128,
+    pub increase: bool,
+    pub ready_ledger: u32,
+}
+// QueuedAdjustment stores one queued adjustment:
+// caller is the operator who queued it and is credited with it when it executes
+// increase tells whether amount is added or subtracted
+// ready_ledger is the first ledger the adjustment can be executed in
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum QueueKey {
+    AdjustmentDelay,
+    QueuedCount,
+    Queue,
+}
+// AdjustmentDelay stores how many ledgers a queued adjustment waits
+// QueuedCount stores the number of adjustments ever queued (the next queue id)
+// Queue stores the waiting adjustments in the order they were queued
+
+
+// The whole queue is read on every queue operation, so it stays bounded.
+const MAX_QUEUED: u32 = 50;
+
+fn delay(env: &Env) -> u32 {
+    env.storage().persistent().get(&QueueKey::AdjustmentDelay).unwrap_or(0)
+}
+
+fn read_queue(env: &Env) -> Vec<QueuedAdjustment> {
+    env.storage().persistent().get(&QueueKey::Queue).unwrap_or(Vec::new(env))
+}
+
+fn write_queue(env: &Env, queue: &Vec<QueuedAdjustment>) {
+    env.storage().persistent().set(&QueueKey::Queue, queue);
+}
+
+// Queued adjustments already waited out the delay, so tiers that must use the queue may execute them.
+fn apply(env: &Env, item: &QueuedAdjustment) -> Result<Receipt, BudgetError> {
+    // Queued by an operator that has rotated its key since, it executes under the current one
+    let caller = rotation::current(env, &item.caller);
+    check_operator(env, &caller)?;
+    #[cfg(feature = "category")]
+    category::check_scope(env, &caller, None)?;
+    if item.increase {
+        increase_for(env, &caller, item.amount)
+    } else {
+        decrease_for(env, &caller, item.amount)
+    }
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Set how many ledgers queued adjustments wait before they can execute (owner only)
+    pub fn set_adjustment_delay(env: Env, caller: Address, ledgers: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().set(&QueueKey::AdjustmentDelay, &ledgers);
+        Ok(())
+    }
+
+    /// Queue an increase or decrease behind the adjustment delay (operators only), returns its id
+    pub fn queue_adjustment(env: Env, caller: Address, amount: i128, increase: bool) -> Result<u64, BudgetError> {
+        require_operator(&env, &caller)?;
+        #[cfg(feature = "category")]
+        category::check_scope(&env, &caller, None)?;
+        if amount <= 0 {
+            return Err(BudgetError::InvalidAmount);
+        }
+        let mut queue = read_queue(&env);
+        if queue.len() >= MAX_QUEUED {
+            return Err(BudgetError::InvalidConfig);
+        }
+
+        let id: u64 = env.storage().persistent().get(&QueueKey::QueuedCount).unwrap_or(0);
+        env.storage().persistent().set(&QueueKey::QueuedCount, &(id + 1));
+        let ready_ledger = env.ledger().sequence().saturating_add(delay(&env));
+        queue.push_back(QueuedAdjustment { id, caller, amount, increase, ready_ledger });
+        write_queue(&env, &queue);
+        Ok(id)
+    }
+
+    /// Drop a queued adjustment (the operator who queued it or the owner)
+    pub fn cancel_queued(env: Env, caller: Address, id: u64) -> Result<(), BudgetError> {
+        caller.require_auth();
+        let mut queue = read_queue(&env);
+        let index = queue.iter().position(|item| item.id == id).ok_or(BudgetError::AdjustmentNotFound)?;
+        if !rotation::is_same(&env, &queue.get_unchecked(index as u32).caller, &caller) && read_owner(&env) != caller {
+            return Err(BudgetError::NotOwner);
+        }
+        queue.remove(index as u32);
+        write_queue(&env, &queue);
+        Ok(())
+    }
+
+    /// Execute one matured queued adjustment (anyone)
+    pub fn execute_queued(env: Env, id: u64) -> Result<Receipt, BudgetError> {
+        let mut queue = read_queue(&env);
+        let index = queue.iter().position(|item| item.id == id).ok_or(BudgetError::AdjustmentNotFound)?;
+        let item = queue.get_unchecked(index as u32);
+        if env.ledger().sequence() < item.ready_ledger {
+            return Err(BudgetError::TimelockActive);
+        }
+        queue.remove(index as u32);
+        write_queue(&env, &queue);
+        apply(&env, &item)
+    }
+
+    /// Execute up to limit matured queued adjustments in queue order (anyone), returns how many were applied
+    pub fn execute_ready(env: Env, limit: u32) -> u32 {
+        let now = env.ledger().sequence();
+        let mut applied = 0;
+        let mut remaining = Vec::new(&env);
+        for item in read_queue(&env).iter() {
+            // Rate limits are only counted once every other check has passed, so a skipped item
+            // uses up no rate-limit slot
+            if applied < limit && now >= item.ready_ledger && apply(&env, &item).is_ok() {
+                applied += 1;
+            } else {
+                remaining.push_back(item);
+            }
+        }
+        write_queue(&env, &remaining);
+        applied
+    }
+
+    // Get the adjustment delay in ledgers
+    pub fn get_adjustment_delay(env: Env) -> u32 {
+        delay(&env)
+    }
+
+    // Get every queued adjustment in queue order
+    pub fn get_queued(env: Env) -> Vec<QueuedAdjustment> {
+        read_queue(&env)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    #[test]
+    fn test_execute_ready_batch() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_adjustment_delay(&owner, &100);
+
+        let first = client.queue_adjustment(&operator, &100, &true);
+        client.queue_adjustment(&operator, &200, &true);
+        client.queue_adjustment(&operator, &5000, &false);
+        env.ledger().with_mut(|l| l.sequence_number = 50);
+        let late = client.queue_adjustment(&operator, &400, &true);
+
+        // Nothing is mature yet
+        assert_eq!(client.try_execute_queued(&first), Err(Ok(BudgetError::TimelockActive)));
+        assert_eq!(client.execute_ready(&10), 0);
+
+        // The decrease below min is skipped and stays queued, the late item is not mature
+        env.ledger().with_mut(|l| l.sequence_number = 100);
+        assert_eq!(client.execute_ready(&10), 2);
+        assert_eq!(client.get_budget().current, 1300);
+        assert_eq!(client.get_queued().len(), 2);
+
+        env.ledger().with_mut(|l| l.sequence_number = 150);
+        assert_eq!(client.execute_queued(&late).new, 1700);
+    }
+
+    #[test]
+    fn test_execute_ready_limit() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        for _ in 0..3 {
+            client.queue_adjustment(&operator, &100, &true);
+        }
+        assert_eq!(client.execute_ready(&2), 2);
+        assert_eq!(client.execute_ready(&2), 1);
+        assert_eq!(client.get_budget().current, 1300);
+    }
+
+    #[test]
+    fn test_skipped_items_use_no_rate_limit() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_rate_limit(&owner, &1, &1);
+
+        // Items that always fail go first and must not take the one slot of this ledger
+        client.queue_adjustment(&operator, &5000, &false);
+        client.queue_adjustment(&operator, &20000, &true);
+        client.queue_adjustment(&operator, &100, &true);
+        assert_eq!(client.execute_ready(&10), 1);
+        assert_eq!(client.get_budget().current, 1100);
+        assert_eq!(client.get_queued().len(), 2);
+    }
+}
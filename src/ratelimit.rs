@@ -0,0 +1,130 @@
+// Per-ledger adjustment rate limit.
+// The owner can cap how many adjustments execute per ledger, in total and per operator, a deputy
+// counting against the operator it stands in for. Excess adjustments fail with RateLimited and can
+// be resubmitted in a later ledger. Counters only keep the ledger they belong to, so they reset
+// themselves when the ledger changes.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env};
+
+use crate::{acting_for, require_owner, BudgetError, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient};
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct RateLimit {
+    pub global: u32,
+    pub per_operator: u32,
+}
+// RateLimit caps the adjustments per ledger, in total and per caller (0 means no cap)
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum RateLimitKey {
+    RateLimit,
+    LedgerOps,
+    OperatorOps(Address),
+}
+// RateLimit stores the RateLimit (missing means no limit)
+// LedgerOps stores (ledger, count) of the adjustments in the last ledger that had any
+// OperatorOps stores (ledger, count) of an operator's adjustments, its deputy's included, in the last ledger it made any
+
+
+// Adjustments already counted under a counter key in this ledger.
+fn used(env: &Env, key: &RateLimitKey) -> u32 {
+    let ledger = env.ledger().sequence();
+    let (at, used): (u32, u32) = env.storage().persistent().get(key).unwrap_or((ledger, 0));
+    if at == ledger { used } else { 0 }
+}
+
+// Counts one more adjustment for this ledger under a counter key.
+fn count(env: &Env, key: RateLimitKey, cap: u32) {
+    if cap > 0 {
+        let used = used(env, &key);
+        env.storage().persistent().set(&key, &(env.ledger().sequence(), used + 1));
+    }
+}
+
+// Called for every adjustment before it is applied. Both caps are checked before either counter
+// moves, so a rejected adjustment is not counted.
+pub(crate) fn check(env: &Env, caller: &Address) -> Result<(), BudgetError> {
+    let limit: RateLimit = match env.storage().persistent().get(&RateLimitKey::RateLimit) {
+        Some(limit) => limit,
+        None => return Ok(()),
+    };
+    peek(env, caller)?;
+    count(env, RateLimitKey::LedgerOps, limit.global);
+    count(env, RateLimitKey::OperatorOps(acting_for(env, caller)), limit.per_operator);
+    Ok(())
+}
+
+// Same as check without counting the adjustment.
+pub(crate) fn peek(env: &Env, caller: &Address) -> Result<(), BudgetError> {
+    let limit: RateLimit = match env.storage().persistent().get(&RateLimitKey::RateLimit) {
+        Some(limit) => limit,
+        None => return Ok(()),
+    };
+    let full = |key: RateLimitKey, cap: u32| cap > 0 && used(env, &key) >= cap;
+    if full(RateLimitKey::LedgerOps, limit.global) || full(RateLimitKey::OperatorOps(acting_for(env, caller)), limit.per_operator) {
+        return Err(BudgetError::RateLimited);
+    }
+    Ok(())
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Cap the adjustments per ledger in total and per caller, 0 meaning no cap (owner only)
+    pub fn set_rate_limit(env: Env, caller: Address, global: u32, per_operator: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().set(&RateLimitKey::RateLimit, &RateLimit { global, per_operator });
+        Ok(())
+    }
+
+    // Get the rate limit, if any
+    pub fn get_rate_limit(env: Env) -> Option<RateLimit> {
+        env.storage().persistent().get(&RateLimitKey::RateLimit)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    #[test]
+    fn test_rate_limited() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let first = Address::generate(&env);
+        let second = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        for operator in [&first, &second] {
+            client.add_operator(&owner, operator);
+            client.accept_operator(operator);
+        }
+        let deputy = Address::generate(&env);
+        client.delegate_operator(&first, &deputy, &100);
+        client.set_rate_limit(&owner, &3, &2);
+
+        // A deputy shares the cap of the operator it stands in for
+        client.increase_budget(&first, &1);
+        client.increase_budget(&deputy, &1);
+        assert_eq!(client.try_increase_budget(&first, &1), Err(Ok(BudgetError::RateLimited)));
+        assert_eq!(client.try_increase_budget(&deputy, &1), Err(Ok(BudgetError::RateLimited)));
+        client.increase_budget(&second, &1);
+        assert_eq!(client.try_increase_budget(&second, &1), Err(Ok(BudgetError::RateLimited)));
+
+        // A new ledger starts from zero
+        env.ledger().with_mut(|l| l.sequence_number = 1);
+        client.decrease_budget(&first, &1);
+        assert_eq!(client.get_budget().current, 1002);
+    }
+}
@@ -0,0 +1,152 @@
+// Bulk reconfiguration.
+// Changing several settings one call at a time leaves the contract in states nobody chose, for
+// example new limits under the old rate limit. reconfigure takes a ConfigUpdate in which every
+// field is optional and applies the fields that are set in a single call: each one is validated
+// exactly as its own setter would (set_limits, set_epoch_length, set_granularity, set_max_operators,
+// set_alert_thresholds, set_rate_limit) before anything is written, so either all of them change or
+// none do. One reconfig event carries the whole update.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env, Vec};
+
+use crate::{
+    admin::check_limits, alerts::{AlertKey, MAX_THRESHOLDS}, events, ratelimit::RateLimitKey, read_budget,
+    require_owner, write_budget, BudgetError, DataKey, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient,
+    RateLimit,
+};
+#[cfg(feature = "token_vote")]
+use crate::token_vote;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ConfigUpdate {
+    pub min: Option<i128>,
+    pub max: Option<i128>,
+    pub epoch_length: Option<u32>,
+    pub granularity: Option<i128>,
+    pub max_operators: Option<u32>,
+    pub alert_thresholds: Option<Vec<i128>>,
+    pub global_rate: Option<u32>,
+    pub operator_rate: Option<u32>,
+}
+// ConfigUpdate lists the settings to change, None keeps a setting as it is:
+// min and max are the budget limits, a missing one keeps its current value (current has to stay within them)
+// global_rate and operator_rate are the two caps of the RateLimit, a missing one keeps its current value
+// the other fields take the same values as their setters
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Apply every setting given in update at once, or none of them if one is invalid (owner only)
+    pub fn reconfigure(env: Env, caller: Address, update: ConfigUpdate) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+
+        // Validate everything before the first write
+        let mut budget = read_budget(&env);
+        let limits = update.min.is_some() || update.max.is_some();
+        if limits {
+            let min = update.min.unwrap_or(budget.min);
+            let max = update.max.unwrap_or(budget.max);
+            check_limits(&env, min, max)?;
+            #[cfg(feature = "token_vote")]
+            token_vote::gate(&env, max)?;
+            if budget.current < min || budget.current > max {
+                return Err(BudgetError::ConfirmationRequired);
+            }
+            budget.min = min;
+            budget.max = max;
+        }
+        if update.granularity.is_some_and(|granularity| granularity < 0) {
+            return Err(BudgetError::InvalidConfig);
+        }
+        if update.alert_thresholds.as_ref().is_some_and(|thresholds| thresholds.len() > MAX_THRESHOLDS) {
+            return Err(BudgetError::InvalidConfig);
+        }
+
+        if limits {
+            write_budget(&env, &budget);
+        }
+        let storage = env.storage().persistent();
+        if let Some(ledgers) = update.epoch_length {
+            storage.set(&DataKey::EpochLength, &ledgers);
+        }
+        if let Some(granularity) = update.granularity {
+            env.storage().instance().set(&DataKey::Granularity, &granularity);
+        }
+        if let Some(max) = update.max_operators {
+            storage.set(&DataKey::MaxOperators, &max);
+        }
+        if let Some(thresholds) = &update.alert_thresholds {
+            storage.set(&AlertKey::AlertThresholds, thresholds);
+        }
+        if update.global_rate.is_some() || update.operator_rate.is_some() {
+            let current: RateLimit = storage.get(&RateLimitKey::RateLimit).unwrap_or(RateLimit { global: 0, per_operator: 0 });
+            let limit = RateLimit {
+                global: update.global_rate.unwrap_or(current.global),
+                per_operator: update.operator_rate.unwrap_or(current.per_operator),
+            };
+            storage.set(&RateLimitKey::RateLimit, &limit);
+        }
+        events::publish(&env, events::RECONFIG, &caller, update);
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Events};
+    use soroban_sdk::vec;
+
+    #[test]
+    fn test_reconfigure() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let alice = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+        env.mock_all_auths();
+
+        let none = ConfigUpdate {
+            min: None,
+            max: None,
+            epoch_length: None,
+            granularity: None,
+            max_operators: None,
+            alert_thresholds: None,
+            global_rate: None,
+            operator_rate: None,
+        };
+        let update = ConfigUpdate {
+            max: Some(5000),
+            epoch_length: Some(100),
+            granularity: Some(10),
+            alert_thresholds: Some(vec![&env, 500, 2000]),
+            operator_rate: Some(1),
+            ..none.clone()
+        };
+        assert_eq!(client.try_reconfigure(&alice, &update), Err(Ok(BudgetError::NotOwner)));
+
+        // One invalid field and nothing changes
+        let invalid = ConfigUpdate { granularity: Some(-1), ..update.clone() };
+        assert_eq!(client.try_reconfigure(&owner, &invalid), Err(Ok(BudgetError::InvalidConfig)));
+        let stranded = ConfigUpdate { min: Some(2000), ..update.clone() };
+        assert_eq!(client.try_reconfigure(&owner, &stranded), Err(Ok(BudgetError::ConfirmationRequired)));
+        assert_eq!(client.get_budget(), BudgetState { current: 1000, min: 0, max: 10000 });
+        assert_eq!((client.get_epoch_length(), client.get_granularity()), (0, 0));
+        assert_eq!(client.get_rate_limit(), None);
+
+        // All given fields in one call with one event, the rest untouched
+        client.reconfigure(&owner, &update);
+        assert_eq!(env.events().all().len(), 1);
+        assert_eq!(client.get_budget(), BudgetState { current: 1000, min: 0, max: 5000 });
+        assert_eq!((client.get_epoch_length(), client.get_granularity()), (100, 10));
+        assert_eq!(client.get_alert_thresholds(), vec![&env, 500, 2000]);
+        assert_eq!(client.get_rate_limit(), Some(RateLimit { global: 0, per_operator: 1 }));
+        assert_eq!(client.get_max_operators(), 0);
+    }
+}
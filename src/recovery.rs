@@ -0,0 +1,239 @@
+// Guardian-triggered owner recovery.
+// The owner registers guardians and how many of them must agree. If the owner key is lost, a
+// guardian starts a recovery naming the new owner and the others approve it. Once enough
+// guardians approved and the mandatory delay since the start has passed, anyone can finalize the
+// recovery, which rotates the owner. Until then the current owner, if it still holds its key, can
+// cancel the recovery. Changing the guardians also cancels a recovery in progress.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env, Vec};
+
+use crate::{
+    is_decommissioned, require_owner, write_owner, BudgetError, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient,
+};
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct GuardianConfig {
+    pub guardians: Vec<Address>,
+    pub threshold: u32,
+    pub delay: u32,
+}
+// GuardianConfig configures recovery:
+// guardians lists each guardian once
+// threshold is how many guardians must approve a recovery
+// delay is how many ledgers a recovery waits after it was started, at least 1
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Recovery {
+    pub new_owner: Address,
+    pub approvals: Vec<Address>,
+    pub ready_ledger: u32,
+}
+// Recovery is the recovery in progress:
+// approvals lists the guardians that approved it, the starting guardian included
+// ready_ledger is the first ledger it can be finalized in
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum RecoveryKey {
+    Guardians,
+    Recovery,
+}
+// Guardians stores the GuardianConfig (missing means recovery is off)
+// Recovery stores the Recovery in progress, if any
+
+
+fn read_config(env: &Env) -> Result<GuardianConfig, BudgetError> {
+    env.storage().persistent().get(&RecoveryKey::Guardians).ok_or(BudgetError::NotRegistered)
+}
+
+fn read_recovery(env: &Env) -> Result<Recovery, BudgetError> {
+    env.storage().persistent().get(&RecoveryKey::Recovery).ok_or(BudgetError::NoPendingAction)
+}
+
+// Authenticates a guardian.
+fn require_guardian(env: &Env, guardian: &Address) -> Result<GuardianConfig, BudgetError> {
+    if is_decommissioned(env) {
+        return Err(BudgetError::Decommissioned);
+    }
+    guardian.require_auth();
+    let config = read_config(env)?;
+    if !config.guardians.contains(guardian) {
+        return Err(BudgetError::NotRegistered);
+    }
+    Ok(config)
+}
+
+pub(crate) fn is_guardian(env: &Env, address: &Address) -> bool {
+    read_config(env).map(|config| config.guardians.contains(address)).unwrap_or(false)
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Set the guardians, how many distinct ones must approve a recovery and the recovery delay, at least 1 ledger (owner only)
+    pub fn set_guardians(env: Env, caller: Address, guardians: Vec<Address>, threshold: u32, delay: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        // A guardian listed twice still approves once, so it must count once towards the threshold
+        let mut unique = Vec::new(&env);
+        for guardian in guardians.iter() {
+            if !unique.contains(&guardian) {
+                unique.push_back(guardian);
+            }
+        }
+        // Without a delay the owner would have no time to cancel a recovery
+        if threshold == 0 || threshold > unique.len() || delay == 0 {
+            return Err(BudgetError::InvalidConfig);
+        }
+        env.storage().persistent().set(&RecoveryKey::Guardians, &GuardianConfig { guardians: unique, threshold, delay });
+        env.storage().persistent().remove(&RecoveryKey::Recovery);
+        Ok(())
+    }
+
+    /// Start recovering ownership to a new owner (guardians only)
+    pub fn start_recovery(env: Env, guardian: Address, new_owner: Address) -> Result<(), BudgetError> {
+        let config = require_guardian(&env, &guardian)?;
+        if env.storage().persistent().has(&RecoveryKey::Recovery) {
+            return Err(BudgetError::AlreadyRegistered);
+        }
+        let recovery = Recovery {
+            new_owner,
+            approvals: Vec::from_array(&env, [guardian]),
+            ready_ledger: env.ledger().sequence().saturating_add(config.delay),
+        };
+        env.storage().persistent().set(&RecoveryKey::Recovery, &recovery);
+        Ok(())
+    }
+
+    /// Approve the recovery in progress (guardians only)
+    pub fn approve_recovery(env: Env, guardian: Address) -> Result<(), BudgetError> {
+        require_guardian(&env, &guardian)?;
+        let mut recovery = read_recovery(&env)?;
+        if recovery.approvals.contains(&guardian) {
+            return Err(BudgetError::AlreadyVoted);
+        }
+        recovery.approvals.push_back(guardian);
+        env.storage().persistent().set(&RecoveryKey::Recovery, &recovery);
+        Ok(())
+    }
+
+    /// Cancel the recovery in progress (owner only)
+    pub fn cancel_recovery(env: Env, caller: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        read_recovery(&env)?;
+        env.storage().persistent().remove(&RecoveryKey::Recovery);
+        Ok(())
+    }
+
+    /// Rotate the owner once enough guardians approved and the delay has passed (anyone)
+    pub fn finalize_recovery(env: Env) -> Result<(), BudgetError> {
+        if is_decommissioned(&env) {
+            return Err(BudgetError::Decommissioned);
+        }
+        let config = read_config(&env)?;
+        let recovery = read_recovery(&env)?;
+        if recovery.approvals.len() < config.threshold {
+            return Err(BudgetError::QuorumNotReached);
+        }
+        if env.ledger().sequence() < recovery.ready_ledger {
+            return Err(BudgetError::TimelockActive);
+        }
+        env.storage().persistent().remove(&RecoveryKey::Recovery);
+        write_owner(&env, &recovery.new_owner);
+        Ok(())
+    }
+
+    // Get the guardian configuration, if any
+    pub fn get_guardians(env: Env) -> Option<GuardianConfig> {
+        env.storage().persistent().get(&RecoveryKey::Guardians)
+    }
+
+    // Get the recovery in progress, if any
+    pub fn get_recovery(env: Env) -> Option<Recovery> {
+        env.storage().persistent().get(&RecoveryKey::Recovery)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::vec;
+
+    #[test]
+    fn test_guardians_rotate_owner() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let new_owner = Address::generate(&env);
+        let alice = Address::generate(&env);
+        let bob = Address::generate(&env);
+        let carol = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.set_guardians(&owner, &vec![&env, alice.clone(), bob.clone(), carol], &2, &100);
+
+        client.start_recovery(&alice, &new_owner);
+        assert_eq!(client.try_finalize_recovery(), Err(Ok(BudgetError::QuorumNotReached)));
+        client.approve_recovery(&bob);
+        assert_eq!(client.try_finalize_recovery(), Err(Ok(BudgetError::TimelockActive)));
+
+        env.ledger().with_mut(|l| l.sequence_number = 100);
+        client.finalize_recovery();
+        assert_eq!(client.get_owner(), new_owner);
+        assert!(client.get_roles(&alice).guardian);
+    }
+
+    #[test]
+    fn test_owner_cancels_recovery() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let attacker = Address::generate(&env);
+        let alice = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.set_guardians(&owner, &vec![&env, alice.clone()], &1, &100);
+        client.start_recovery(&alice, &attacker);
+        client.cancel_recovery(&owner);
+
+        env.ledger().with_mut(|l| l.sequence_number = 100);
+        assert_eq!(client.try_finalize_recovery(), Err(Ok(BudgetError::NoPendingAction)));
+        assert_eq!(client.get_owner(), owner);
+    }
+
+    #[test]
+    fn test_guardian_config_checks() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let alice = Address::generate(&env);
+        let bob = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        let guardians = vec![&env, alice.clone(), alice.clone(), bob.clone()];
+        assert_eq!(client.try_set_guardians(&owner, &guardians, &3, &100), Err(Ok(BudgetError::InvalidConfig)));
+        assert_eq!(client.try_set_guardians(&owner, &guardians, &2, &0), Err(Ok(BudgetError::InvalidConfig)));
+
+        // Duplicates are listed once
+        client.set_guardians(&owner, &guardians, &2, &100);
+        assert_eq!(client.get_guardians().unwrap().guardians, vec![&env, alice, bob]);
+    }
+}
@@ -0,0 +1,146 @@
+// Auto-refill at epoch boundaries.
+// The owner sets a target value; the first time the budget is read in a new epoch, current is
+// topped back up to the target (capped by max, or its vested part) so a standing monthly budget does not need someone
+// to call increase_budget every month. A current value at or above the target is left alone.
+// Refill is applied after decay and needs epochs to be enabled.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env};
+
+use crate::{
+    current_epoch, epoch_at, require_owner, stored_budget, write_budget, BudgetError, BudgetState,
+    GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient,
+};
+#[cfg(feature = "vesting")]
+use crate::vesting;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct RefillPolicy {
+    pub target: i128,
+    pub last_epoch: u32,
+}
+// RefillPolicy defines the refill:
+// target is the value current is topped up to every epoch
+// last_epoch is the epoch the last refill was applied in
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum RefillKey {
+    Refill,
+}
+// Refill stores the RefillPolicy (missing means no refill)
+
+
+fn read_policy(env: &Env) -> Option<RefillPolicy> {
+    env.storage().persistent().get(&RefillKey::Refill)
+}
+
+// Current after a refill at ledger.
+fn refilled(env: &Env, policy: &RefillPolicy, budget: &BudgetState, ledger: u32) -> i128 {
+    #[cfg(feature = "vesting")]
+    let target = policy.target.min(vesting::cap_at(env, budget, ledger));
+    #[cfg(not(feature = "vesting"))]
+    let target = policy.target.min(budget.max);
+    budget.current.max(target)
+}
+
+// Tops current up once if a new epoch started since the last refill.
+pub(crate) fn apply_due(env: &Env) {
+    let mut policy = match read_policy(env) {
+        Some(policy) => policy,
+        None => return,
+    };
+    let epoch = match current_epoch(env) {
+        Some(epoch) if epoch > policy.last_epoch => epoch,
+        _ => return,
+    };
+    policy.last_epoch = epoch;
+    env.storage().persistent().set(&RefillKey::Refill, &policy);
+
+    let mut budget = stored_budget(env);
+    let current = refilled(env, &policy, &budget, env.ledger().sequence());
+    if current != budget.current {
+        budget.current = current;
+        write_budget(env, &budget);
+    }
+}
+
+// Applies the refill due by ledger to budget, without storing anything.
+pub(crate) fn project(env: &Env, budget: &mut BudgetState, ledger: u32) {
+    if let (Some(policy), Some(epoch)) = (read_policy(env), epoch_at(env, ledger)) {
+        if epoch > policy.last_epoch {
+            budget.current = refilled(env, &policy, budget, ledger);
+        }
+    }
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Top current up to target at every epoch boundary from now on (owner only, needs epochs)
+    pub fn set_refill(env: Env, caller: Address, target: i128) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        let epoch = current_epoch(&env).ok_or(BudgetError::InvalidConfig)?;
+        if target <= 0 {
+            return Err(BudgetError::InvalidAmount);
+        }
+        let policy = RefillPolicy { target, last_epoch: epoch };
+        env.storage().persistent().set(&RefillKey::Refill, &policy);
+        Ok(())
+    }
+
+    /// Turn auto-refill off (owner only)
+    pub fn clear_refill(env: Env, caller: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().remove(&RefillKey::Refill);
+        Ok(())
+    }
+
+    // Get the refill policy, if any
+    pub fn get_refill(env: Env) -> Option<RefillPolicy> {
+        read_policy(&env)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    #[test]
+    fn test_refill_each_epoch() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_epoch_length(&owner, &100);
+        client.set_refill(&owner, &1000);
+
+        client.decrease_budget(&operator, &700);
+        assert_eq!(client.get_budget().current, 300);
+
+        env.ledger().with_mut(|l| l.sequence_number = 100);
+        assert_eq!(client.get_budget().current, 1000);
+
+        // Spending again within the epoch is not refilled until the next boundary
+        client.decrease_budget(&operator, &400);
+        env.ledger().with_mut(|l| l.sequence_number = 199);
+        assert_eq!(client.get_budget().current, 600);
+
+        // The target is capped by max
+        client.set_limits(&owner, &0, &800);
+        env.ledger().with_mut(|l| l.sequence_number = 200);
+        assert_eq!(client.get_budget().current, 800);
+    }
+}
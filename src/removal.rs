@@ -0,0 +1,150 @@
+// Grace-period operator removal.
+// With a removal grace configured, remove_operator schedules the removal instead of applying it.
+// Until the grace period ends the leaving operator can no longer decrease the budget, but it stays
+// in the operators list, so it can still vote on and complete proposals already in flight. Like
+// scheduled limits, due removals are applied to the list lazily the next time it is read; the
+// operator's membership key already expires at the effective ledger.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env, Vec};
+
+use crate::{require_owner, set_listed, BudgetError, DataKey, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient};
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum RemovalKey {
+    RemovalGrace,
+    PendingRemovals,
+}
+// RemovalGrace stores the grace period in ledgers (missing or 0 means removals are immediate)
+// PendingRemovals stores the (operator, effective ledger) removals not applied yet
+
+
+pub(crate) fn grace(env: &Env) -> u32 {
+    env.storage().persistent().get(&RemovalKey::RemovalGrace).unwrap_or(0)
+}
+
+fn read_pending(env: &Env) -> Vec<(Address, u32)> {
+    env.storage().persistent().get(&RemovalKey::PendingRemovals).unwrap_or(Vec::new(env))
+}
+
+// The ledger an operator's scheduled removal takes effect, if it has not taken effect yet.
+pub(crate) fn pending(env: &Env, operator: &Address) -> Option<u32> {
+    let now = env.ledger().sequence();
+    read_pending(env).iter().find(|(address, at)| address == operator && *at > now).map(|(_, at)| at)
+}
+
+// Schedules the removal of an operator at the end of the grace period.
+pub(crate) fn schedule(env: &Env, operator: Address) -> Result<(), BudgetError> {
+    if pending(env, &operator).is_some() {
+        return Err(BudgetError::AlreadyRegistered);
+    }
+    let at = env.ledger().sequence().saturating_add(grace(env));
+    // The operator stays listed up to the ledger before its removal takes effect
+    env.storage().persistent().set(&DataKey::OperatorUntil(operator.clone()), &(at - 1));
+    let mut removals = read_pending(env);
+    removals.push_back((operator, at));
+    env.storage().persistent().set(&RemovalKey::PendingRemovals, &removals);
+    Ok(())
+}
+
+// Applies every removal whose grace period has ended.
+pub(crate) fn apply_due(env: &Env) {
+    let removals = read_pending(env);
+    let now = env.ledger().sequence();
+    if !removals.iter().any(|(_, at)| at <= now) {
+        return;
+    }
+
+    let mut operators: Vec<Address> = env.storage().persistent().get(&DataKey::Operators).unwrap();
+    let mut remaining = Vec::new(env);
+    for (operator, at) in removals.iter() {
+        if at > now {
+            remaining.push_back((operator, at));
+        } else if let Some(index) = operators.first_index_of(&operator) {
+            operators.remove(index);
+            set_listed(env, &operator, false);
+        }
+    }
+    env.storage().persistent().set(&DataKey::Operators, &operators);
+    env.storage().persistent().set(&RemovalKey::PendingRemovals, &remaining);
+}
+
+// Leaving operators may not start decreases.
+pub(crate) fn check_not_leaving(env: &Env, caller: &Address) -> Result<(), BudgetError> {
+    let until: u32 = env.storage().persistent().get(&DataKey::OperatorUntil(caller.clone())).unwrap_or(u32::MAX);
+    if until != u32::MAX {
+        return Err(BudgetError::NotOperator);
+    }
+    Ok(())
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Set how many ledgers an operator removal waits before taking effect, 0 makes it immediate (owner only)
+    pub fn set_removal_grace(env: Env, caller: Address, ledgers: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().set(&RemovalKey::RemovalGrace, &ledgers);
+        Ok(())
+    }
+
+    // Get the removal grace period in ledgers
+    pub fn get_removal_grace(env: Env) -> u32 {
+        grace(&env)
+    }
+
+    // Get the ledger a pending removal of an operator takes effect, if any
+    pub fn get_pending_removal(env: Env, operator: Address) -> Option<u32> {
+        pending(&env, &operator)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::{symbol_short, BytesN, String};
+
+    fn metadata(env: &Env) -> ProposalMetadata {
+        ProposalMetadata {
+            title: symbol_short!("limits"),
+            memo: String::from_str(env, "raise the limits"),
+            recipient: None,
+            content_hash: BytesN::from_array(env, &[1; 32]),
+        }
+    }
+
+    #[test]
+    fn test_removal_after_grace() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_quorum(&owner, &1);
+        let id = client.propose_limits(&operator, &0, &20000, &metadata(&env));
+
+        client.set_removal_grace(&owner, &100);
+        client.remove_operator(&owner, &operator);
+        assert_eq!(client.get_pending_removal(&operator), Some(100));
+
+        // During the grace period: no decreases, but the proposal can still be completed
+        assert_eq!(client.try_decrease_budget(&operator, &100), Err(Ok(BudgetError::NotOperator)));
+        client.vote(&operator, &id, &true);
+        client.execute_proposal(&id);
+        assert!(client.is_operator(&operator));
+
+        env.ledger().with_mut(|l| l.sequence_number = 100);
+        assert!(!client.is_operator(&operator));
+        assert_eq!(client.get_pending_removal(&operator), None);
+    }
+}
@@ -0,0 +1,109 @@
+// Per-epoch reports.
+// When epochs are enabled every change of current is folded into the report of the epoch it
+// happens in: totals of increases and decreases, the net change and the highest and lowest value
+// current reached. Changes of all kinds count (adjustments, vetoes, clamps, decay, refill), so a
+// report always explains how current moved from opening to opening + net during the epoch.
+// Epochs without any change have no report.
+
+use soroban_sdk::{contractimpl, contracttype, Env};
+
+use crate::{current_epoch, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient};
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct EpochReport {
+    pub opening: i128,
+    pub increased: i128,
+    pub decreased: i128,
+    pub net: i128,
+    pub peak: i128,
+    pub trough: i128,
+}
+// EpochReport stores the totals of one epoch:
+// opening is current before the first change of the epoch
+// increased and decreased are the summed upward and downward changes, net their difference
+// peak and trough are the highest and lowest value of current during the epoch
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum ReportKey {
+    EpochReport(u32),
+}
+// EpochReport stores the EpochReport of an epoch
+
+
+// Called for every change of current while epochs are enabled.
+pub(crate) fn on_change(env: &Env, previous: i128, current: i128) {
+    let epoch = match current_epoch(env) {
+        Some(epoch) => epoch,
+        None => return,
+    };
+    if previous == current {
+        return;
+    }
+    let key = ReportKey::EpochReport(epoch);
+    let mut report = env.storage().persistent().get(&key).unwrap_or(EpochReport {
+        opening: previous,
+        increased: 0,
+        decreased: 0,
+        net: 0,
+        peak: previous,
+        trough: previous,
+    });
+    if current > previous {
+        report.increased = report.increased.saturating_add(current - previous);
+    } else {
+        report.decreased = report.decreased.saturating_add(previous - current);
+    }
+    report.net = report.increased.saturating_sub(report.decreased);
+    report.peak = report.peak.max(current);
+    report.trough = report.trough.min(current);
+    env.storage().persistent().set(&key, &report);
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    // Get the report of an epoch, if anything changed in it
+    pub fn get_epoch_report(env: Env, epoch_id: u32) -> Option<EpochReport> {
+        env.storage().persistent().get(&ReportKey::EpochReport(epoch_id))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    #[test]
+    fn test_epoch_report() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_epoch_length(&owner, &100);
+
+        env.ledger().with_mut(|l| l.sequence_number = 100);
+        client.increase_budget(&operator, &500);
+        client.decrease_budget(&operator, &800);
+        client.increase_budget(&operator, &100);
+
+        let report = client.get_epoch_report(&1).unwrap();
+        assert_eq!(
+            report,
+            EpochReport { opening: 1000, increased: 600, decreased: 800, net: -200, peak: 1500, trough: 700 }
+        );
+        assert_eq!(client.get_epoch_report(&0), None);
+    }
+}
@@ -0,0 +1,178 @@
+// Reservations (earmarks).
+// A team can commit part of the current budget to a project before the invoice arrives. Reserved
+// amounts stay in current, but no decrease of any kind may take current below min plus the reserved
+// total.
+// A reservation is either released (the earmark is dropped and the budget stays) or consumed (the
+// reserved amount is decreased from the budget as a normal operator decrease).
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env, Symbol};
+
+use crate::{
+    apply_decrease, floor, read_budget, read_owner, require_operator, BudgetError, GovernanceBudgetAllocator,
+    GovernanceBudgetAllocatorClient, Receipt,
+};
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Reservation {
+    pub id: u64,
+    pub caller: Address,
+    pub amount: i128,
+    pub tag: Symbol,
+    pub ledger: u32,
+}
+// Reservation stores one earmark:
+// caller is the operator that made it
+// tag names what the funds are committed to
+// ledger is the ledger it was made in
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum ReserveKey {
+    ReservationCount,
+    Reservation(u64),
+    Reserved,
+}
+// ReservationCount stores the number of reservations ever made (the next reservation id)
+// Reservation stores an open Reservation by id
+// Reserved stores the total of all open reservations
+
+
+pub(crate) fn reserved(env: &Env) -> i128 {
+    env.storage().persistent().get(&ReserveKey::Reserved).unwrap_or(0)
+}
+
+fn read_reservation(env: &Env, id: u64) -> Result<Reservation, BudgetError> {
+    env.storage().persistent().get(&ReserveKey::Reservation(id)).ok_or(BudgetError::NotRegistered)
+}
+
+fn remove(env: &Env, reservation: &Reservation) {
+    env.storage().persistent().remove(&ReserveKey::Reservation(reservation.id));
+    env.storage().persistent().set(&ReserveKey::Reserved, &(reserved(env) - reservation.amount));
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Earmark part of the current budget (operators only), returns the reservation id
+    pub fn reserve(env: Env, caller: Address, amount: i128, tag: Symbol) -> Result<u64, BudgetError> {
+        require_operator(&env, &caller)?;
+        if amount <= 0 {
+            return Err(BudgetError::InvalidAmount);
+        }
+        // Only the part of current above min and the open reservations can be reserved
+        let budget = read_budget(&env);
+        let available = budget.current.saturating_sub(floor(&env, &budget));
+        if amount > available {
+            return Err(BudgetError::BelowMin);
+        }
+
+        let id: u64 = env.storage().persistent().get(&ReserveKey::ReservationCount).unwrap_or(0);
+        env.storage().persistent().set(&ReserveKey::ReservationCount, &(id + 1));
+        let reservation = Reservation { id, caller, amount, tag, ledger: env.ledger().sequence() };
+        env.storage().persistent().set(&ReserveKey::Reservation(id), &reservation);
+        env.storage().persistent().set(&ReserveKey::Reserved, &(reserved(&env) + amount));
+        Ok(id)
+    }
+
+    /// Drop an earmark, keeping the budget (the operator that made it or the owner)
+    pub fn release_reservation(env: Env, caller: Address, id: u64) -> Result<(), BudgetError> {
+        caller.require_auth();
+        let reservation = read_reservation(&env, id)?;
+        if reservation.caller != caller && read_owner(&env) != caller {
+            return Err(BudgetError::NotOwner);
+        }
+        remove(&env, &reservation);
+        Ok(())
+    }
+
+    /// Spend an earmark by decreasing the budget by the reserved amount (the operator that made it or the owner)
+    pub fn consume_reservation(env: Env, caller: Address, id: u64) -> Result<Receipt, BudgetError> {
+        caller.require_auth();
+        let reservation = read_reservation(&env, id)?;
+        if reservation.caller != caller && read_owner(&env) != caller {
+            return Err(BudgetError::NotOwner);
+        }
+        remove(&env, &reservation);
+        apply_decrease(&env, &caller, reservation.amount)
+    }
+
+    // Get an open reservation by id
+    pub fn get_reservation(env: Env, id: u64) -> Option<Reservation> {
+        env.storage().persistent().get(&ReserveKey::Reservation(id))
+    }
+
+    // Get the total of all open reservations
+    pub fn get_reserved(env: Env) -> i128 {
+        reserved(&env)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::symbol_short;
+    use soroban_sdk::testutils::Address as _;
+
+    #[test]
+    fn test_reservations_block_decreases() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+
+        let audit = client.reserve(&operator, &600, &symbol_short!("audit"));
+        let infra = client.reserve(&operator, &300, &symbol_short!("infra"));
+        assert_eq!(client.try_reserve(&operator, &200, &symbol_short!("x")), Err(Ok(BudgetError::BelowMin)));
+        assert_eq!(client.get_reserved(), 900);
+
+        // Only the unreserved 100 can be decreased
+        assert_eq!(client.try_decrease_budget(&operator, &101), Err(Ok(BudgetError::BelowMin)));
+        client.decrease_budget(&operator, &100);
+
+        assert_eq!(client.consume_reservation(&operator, &audit).new, 300);
+        client.release_reservation(&owner, &infra);
+        assert_eq!(client.get_reserved(), 0);
+        assert_eq!(client.decrease_budget(&operator, &300).new, 0);
+    }
+
+    #[test]
+    fn test_reservations_stack_on_min() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let other = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &500, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.add_operator(&owner, &other);
+        client.accept_operator(&other);
+
+        // Only the 500 above min can be reserved, and only once
+        let audit = client.reserve(&operator, &500, &symbol_short!("audit"));
+        assert_eq!(client.try_reserve(&operator, &1, &symbol_short!("x")), Err(Ok(BudgetError::BelowMin)));
+        assert_eq!(client.try_decrease_budget(&operator, &1), Err(Ok(BudgetError::BelowMin)));
+
+        // Another operator cannot consume it
+        assert_eq!(client.try_consume_reservation(&other, &audit), Err(Ok(BudgetError::NotOwner)));
+        assert_eq!(client.consume_reservation(&operator, &audit).new, 500);
+        assert_eq!(client.get_reserved(), 0);
+    }
+}
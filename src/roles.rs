@@ -0,0 +1,90 @@
+// Aggregate role query.
+// get_roles answers in one call which roles an address holds, instead of clients combining
+// get_owner, get_admin, is_operator and the feature specific queries themselves.
+// New roles are added to RoleInfo as the features that define them are added.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env};
+
+use crate::{is_listed, read_admin, read_owner, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient};
+#[cfg(feature = "recovery")]
+use crate::recovery;
+
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[contracttype]
+pub struct RoleInfo {
+    pub owner: bool,
+    pub admin: bool,
+    pub operator: bool,
+    pub blacklisted: bool,
+    pub parent: bool,
+    pub child: bool,
+    pub guardian: bool,
+}
+// RoleInfo lists the roles of one address:
+// owner, admin and operator are the access control roles
+// blacklisted means the address may never become an operator
+// parent is set for the parent allocator of this contract
+// child is set for a child allocator this contract allocated to
+// guardian means the address can take part in owner recovery
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    // Get every role an address holds
+    pub fn get_roles(env: Env, address: Address) -> RoleInfo {
+        // Roles of features this build leaves out stay false
+        let mut roles = RoleInfo {
+            owner: read_owner(&env) == address,
+            admin: read_admin(&env) == Some(address.clone()),
+            operator: is_listed(&env, &address),
+            ..RoleInfo::default()
+        };
+        #[cfg(feature = "blacklist")]
+        {
+            roles.blacklisted = Self::is_blacklisted(env.clone(), address.clone());
+        }
+        #[cfg(feature = "federation")]
+        {
+            roles.parent = Self::get_parent(env.clone()) == Some(address.clone());
+            roles.child = Self::get_child(env.clone(), address.clone()).is_some();
+        }
+        #[cfg(feature = "recovery")]
+        {
+            roles.guardian = recovery::is_guardian(&env, &address);
+        }
+        roles
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+
+    #[test]
+    fn test_get_roles() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let admin = Address::generate(&env);
+        let stranger = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.set_admin(&owner, &admin);
+        client.add_operator(&owner, &owner);
+        client.accept_operator(&owner);
+
+        let roles = client.get_roles(&owner);
+        assert!(roles.owner && roles.operator && !roles.admin);
+        assert_eq!(client.get_roles(&admin), RoleInfo { admin: true, ..RoleInfo::default() });
+
+        client.blacklist(&owner, &stranger);
+        assert_eq!(client.get_roles(&stranger), RoleInfo { blacklisted: true, ..RoleInfo::default() });
+    }
+}
@@ -0,0 +1,210 @@
+// Operator key rotation.
+// Removing an operator and adding its new address loses everything kept per address. Instead
+// rotate_operator_key, authorized by both the old and the new address, replaces the operator in
+// place: the new address takes the old one's position in the list, its tier, category scope,
+// stats, bond, deputy and rate limit counters. Records that name the old address stay as they are (receipts, proposals,
+// votes, queued adjustments, invoices); the rotation is recorded both ways instead, and the
+// features acting on such records follow it to the current address with current().
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env, IntoVal, Val, Vec};
+
+use crate::{
+    check_eligible, events, is_listed, read_operators, set_listed, BudgetError, DataKey, GovernanceBudgetAllocator,
+    GovernanceBudgetAllocatorClient, RotatedEvent,
+};
+#[cfg(feature = "blacklist")]
+use crate::blacklist;
+#[cfg(feature = "deputy")]
+use crate::deputy::{Deputy, DeputyKey};
+#[cfg(feature = "removal")]
+use crate::removal;
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum RotationKey {
+    RotatedTo(Address),
+    RotatedFrom(Address),
+}
+// RotatedTo stores the address an operator rotated its key to
+// RotatedFrom stores the address an operator rotated its key from
+
+
+// The address a possibly rotated operator acts under now.
+pub(crate) fn current(env: &Env, address: &Address) -> Address {
+    let mut address = address.clone();
+    while let Some(next) = env.storage().persistent().get(&RotationKey::RotatedTo(address.clone())) {
+        address = next;
+    }
+    address
+}
+
+// Whether an address is, or rotated from, the given address.
+pub(crate) fn is_same(env: &Env, address: &Address, other: &Address) -> bool {
+    current(env, address) == current(env, other)
+}
+
+// The addresses an operator used before its current one, latest first.
+pub(crate) fn previous(env: &Env, address: &Address) -> Vec<Address> {
+    let mut previous = Vec::new(env);
+    let mut address = address.clone();
+    while let Some(prior) = env.storage().persistent().get::<_, Address>(&RotationKey::RotatedFrom(address)) {
+        previous.push_back(prior.clone());
+        address = prior;
+    }
+    previous
+}
+
+fn move_entry(env: &Env, from: impl IntoVal<Env, Val>, to: impl IntoVal<Env, Val>) {
+    let storage = env.storage().persistent();
+    if let Some(value) = storage.get::<_, Val>(&from) {
+        storage.set(&to, &value);
+        storage.remove(&from);
+    }
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Replace an operator's address with a new one, keeping its per-operator state (old and new address)
+    pub fn rotate_operator_key(env: Env, old: Address, new: Address) -> Result<(), BudgetError> {
+        old.require_auth();
+        new.require_auth();
+        if !is_listed(&env, &old) {
+            return Err(BudgetError::NotOperatorFound);
+        }
+        // A leaving operator cannot hand its seat on
+        #[cfg(feature = "removal")]
+        removal::check_not_leaving(&env, &old)?;
+        #[cfg(feature = "blacklist")]
+        blacklist::check(&env, &new)?;
+        if is_listed(&env, &new) || env.storage().persistent().has(&RotationKey::RotatedFrom(new.clone())) {
+            return Err(BudgetError::AlreadyOperator);
+        }
+
+        // Same seat in the list, so the operator count and any lock are unaffected
+        let mut operators = read_operators(&env);
+        let index = operators.first_index_of(&old).ok_or(BudgetError::NotOperatorFound)?;
+        operators.set(index, new.clone());
+        env.storage().persistent().set(&DataKey::Operators, &operators);
+        set_listed(&env, &old, false);
+        set_listed(&env, &new, true);
+        env.storage().persistent().remove(&DataKey::Nominated(new.clone()));
+
+        #[cfg(feature = "tiers")]
+        move_entry(
+            &env,
+            crate::tiers::TierKey::OperatorTier(old.clone()),
+            crate::tiers::TierKey::OperatorTier(new.clone()),
+        );
+        #[cfg(feature = "stats")]
+        move_entry(
+            &env,
+            crate::stats::StatsKey::OperatorStats(old.clone()),
+            crate::stats::StatsKey::OperatorStats(new.clone()),
+        );
+        #[cfg(feature = "category")]
+        move_entry(
+            &env,
+            crate::category::CategoryKey::OperatorScope(old.clone()),
+            crate::category::CategoryKey::OperatorScope(new.clone()),
+        );
+        #[cfg(feature = "ratelimit")]
+        move_entry(
+            &env,
+            crate::ratelimit::RateLimitKey::OperatorOps(old.clone()),
+            crate::ratelimit::RateLimitKey::OperatorOps(new.clone()),
+        );
+        #[cfg(feature = "stake")]
+        {
+            let old_bond = crate::stake::StakeKey::Bond(old.clone());
+            let new_bond = crate::stake::StakeKey::Bond(new.clone());
+            let bond: i128 = env.storage().persistent().get(&old_bond).unwrap_or(0);
+            if bond > 0 {
+                let existing: i128 = env.storage().persistent().get(&new_bond).unwrap_or(0);
+                env.storage().persistent().set(&new_bond, &(existing + bond));
+                env.storage().persistent().remove(&old_bond);
+            }
+        }
+        #[cfg(feature = "deputy")]
+        if let Some(mut deputy) = env.storage().persistent().get::<_, Deputy>(&DeputyKey::DeputyFor(old.clone())) {
+            deputy.operator = new.clone();
+            env.storage().persistent().remove(&DeputyKey::DeputyFor(old.clone()));
+            env.storage().persistent().set(&DeputyKey::DeputyFor(new.clone()), &deputy);
+            env.storage().persistent().set(&DeputyKey::DeputyOf(deputy.deputy.clone()), &deputy);
+        }
+        // The old key's registered signing key is what is being replaced
+        #[cfg(feature = "signed")]
+        env.storage().persistent().remove(&crate::signed::SignedKey::SigningKey(old.clone()));
+
+        // The new address has to meet the operator requirements itself
+        check_eligible(&env, &new)?;
+
+        env.storage().persistent().set(&RotationKey::RotatedTo(old.clone()), &new);
+        env.storage().persistent().set(&RotationKey::RotatedFrom(new.clone()), &old);
+        events::publish(&env, events::ROTATED, &old, RotatedEvent { new });
+        Ok(())
+    }
+
+    // Get the address an operator rotated its key to, if it did
+    pub fn get_rotated_to(env: Env, address: Address) -> Option<Address> {
+        env.storage().persistent().get(&RotationKey::RotatedTo(address))
+    }
+
+    // Get the address an operator rotated its key from, if it did
+    pub fn get_rotated_from(env: Env, address: Address) -> Option<Address> {
+        env.storage().persistent().get(&RotationKey::RotatedFrom(address))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{symbol_short, BytesN, String};
+
+    #[test]
+    fn test_rotate_operator_key() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let old = Address::generate(&env);
+        let new = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &old);
+        client.accept_operator(&old);
+        client.set_operator_tier(&owner, &old, &Some(Tier::Senior));
+        client.increase_budget(&old, &100);
+        client.set_quorum(&owner, &1);
+        let metadata = ProposalMetadata {
+            title: symbol_short!("limits"),
+            memo: String::from_str(&env, "raise the limits"),
+            recipient: None,
+            content_hash: BytesN::from_array(&env, &[1; 32]),
+        };
+        let id = client.propose_limits(&old, &0, &20000, &metadata);
+        client.vote(&old, &id, &true);
+
+        client.rotate_operator_key(&old, &new);
+        assert_eq!(client.get_operators(), soroban_sdk::vec![&env, new.clone()]);
+        assert!(!client.is_operator(&old));
+        assert_eq!(client.get_operator_tier(&new), Some(Tier::Senior));
+        assert_eq!(client.get_operator_stats(&new).op_count, 1);
+        assert_eq!(client.get_rotated_to(&old), Some(new.clone()));
+
+        // The pending proposal is still the new key's, and its vote still counts once
+        assert_eq!(client.try_vote(&new, &id, &true), Err(Ok(BudgetError::AlreadyVoted)));
+        client.cancel_proposal(&new, &id);
+
+        // The old key has no rights left
+        assert_eq!(client.try_increase_budget(&old, &100), Err(Ok(BudgetError::NotOperator)));
+        client.increase_budget(&new, &100);
+        assert_eq!(client.try_rotate_operator_key(&old, &new), Err(Ok(BudgetError::NotOperatorFound)));
+    }
+}
@@ -0,0 +1,200 @@
+// Scheduled future limit changes.
+// The owner announces (effective_ledger, min, max) changes in advance. Nothing runs at the
+// effective ledger itself: the change is activated the first time the budget is read at or after
+// it (any adjustment or query), so nobody has to be online at that exact moment. Like a
+// confirmed SetLimits action, an activated change clamps current into the new limits.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env, Vec};
+
+use crate::{
+    admin::check_limits, require_owner, stored_budget, write_budget, BudgetError, BudgetState,
+    GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient,
+};
+#[cfg(feature = "token_vote")]
+use crate::token_vote;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ScheduledLimits {
+    pub effective_ledger: u32,
+    pub min: i128,
+    pub max: i128,
+}
+// ScheduledLimits is one announced change; min and max apply from effective_ledger on.
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum ScheduleKey {
+    ScheduledLimits,
+}
+// ScheduledLimits stores the pending changes ordered by effective_ledger
+
+
+// Every read walks the list, so it stays short.
+const MAX_SCHEDULED: u32 = 10;
+
+fn read_schedule(env: &Env) -> Vec<ScheduledLimits> {
+    env.storage().persistent().get(&ScheduleKey::ScheduledLimits).unwrap_or(Vec::new(env))
+}
+
+// The last change effective by ledger, if any.
+fn latest_due(schedule: &Vec<ScheduledLimits>, ledger: u32) -> Option<ScheduledLimits> {
+    schedule.iter().filter(|change| change.effective_ledger <= ledger).last()
+}
+
+fn activate(budget: &mut BudgetState, change: &ScheduledLimits) {
+    budget.min = change.min;
+    budget.max = change.max;
+    budget.current = budget.current.clamp(change.min, change.max);
+}
+
+// Activates every change whose effective ledger has been reached, in order.
+pub(crate) fn apply_due(env: &Env) {
+    let schedule = read_schedule(env);
+    let now = env.ledger().sequence();
+    let latest = match latest_due(&schedule, now) {
+        Some(latest) => latest,
+        None => return,
+    };
+
+    let mut remaining = Vec::new(env);
+    for change in schedule.iter() {
+        if change.effective_ledger > now {
+            remaining.push_back(change);
+        }
+    }
+    // Store the shortened list first so the budget write below does not see the change again
+    env.storage().persistent().set(&ScheduleKey::ScheduledLimits, &remaining);
+
+    let mut budget = stored_budget(env);
+    activate(&mut budget, &latest);
+    write_budget(env, &budget);
+}
+
+// Applies the changes due by ledger to budget, without storing anything.
+pub(crate) fn project(env: &Env, budget: &mut BudgetState, ledger: u32) {
+    if let Some(latest) = latest_due(&read_schedule(env), ledger) {
+        activate(budget, &latest);
+    }
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Announce new limits that take effect at a future ledger (owner only)
+    pub fn schedule_limits(env: Env, caller: Address, effective_ledger: u32, min: i128, max: i128) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        check_limits(&env, min, max)?;
+        #[cfg(feature = "token_vote")]
+        token_vote::gate(&env, max)?;
+        if effective_ledger <= env.ledger().sequence() {
+            return Err(BudgetError::InvalidConfig);
+        }
+
+        let schedule = read_schedule(&env);
+        if schedule.len() >= MAX_SCHEDULED {
+            return Err(BudgetError::InvalidConfig);
+        }
+
+        // Keep the list ordered; a change for the same ledger replaces the earlier one
+        let change = ScheduledLimits { effective_ledger, min, max };
+        let mut updated = Vec::new(&env);
+        let mut inserted = false;
+        for existing in schedule.iter() {
+            if !inserted && existing.effective_ledger >= effective_ledger {
+                updated.push_back(change.clone());
+                inserted = true;
+            }
+            if existing.effective_ledger != effective_ledger {
+                updated.push_back(existing);
+            }
+        }
+        if !inserted {
+            updated.push_back(change);
+        }
+        env.storage().persistent().set(&ScheduleKey::ScheduledLimits, &updated);
+        Ok(())
+    }
+
+    /// Withdraw an announced change (owner only)
+    pub fn cancel_scheduled_limits(env: Env, caller: Address, effective_ledger: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+
+        let mut schedule = read_schedule(&env);
+        let index = schedule
+            .iter()
+            .position(|change| change.effective_ledger == effective_ledger)
+            .ok_or(BudgetError::NotRegistered)?;
+        schedule.remove(index as u32);
+        env.storage().persistent().set(&ScheduleKey::ScheduledLimits, &schedule);
+        Ok(())
+    }
+
+    // Get the announced changes that have not been activated yet
+    pub fn get_scheduled_limits(env: Env) -> Vec<ScheduledLimits> {
+        read_schedule(&env)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    #[test]
+    fn test_scheduled_limits_activate_lazily() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &5000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.schedule_limits(&owner, &200, &0, &3000);
+        client.schedule_limits(&owner, &100, &0, &20000);
+        assert_eq!(client.get_scheduled_limits().get(0).unwrap().effective_ledger, 100);
+
+        env.ledger().with_mut(|l| l.sequence_number = 99);
+        assert_eq!(client.get_budget().max, 10000);
+
+        // The first adjustment after the effective ledger already sees the new max
+        env.ledger().with_mut(|l| l.sequence_number = 150);
+        assert_eq!(client.increase_budget(&operator, &10000).new, 15000);
+        assert_eq!(client.get_scheduled_limits().len(), 1);
+
+        // A shrinking change clamps current
+        env.ledger().with_mut(|l| l.sequence_number = 250);
+        let budget = client.get_budget();
+        assert_eq!(budget.max, 3000);
+        assert_eq!(budget.current, 3000);
+        assert_eq!(client.get_scheduled_limits().len(), 0);
+    }
+
+    #[test]
+    fn test_cancel_scheduled_limits() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+
+        client.initialize(&owner, &5000, &0, &10000);
+
+        env.mock_all_auths();
+        assert_eq!(client.try_schedule_limits(&owner, &0, &0, &3000), Err(Ok(BudgetError::InvalidConfig)));
+        client.schedule_limits(&owner, &100, &0, &3000);
+        client.cancel_scheduled_limits(&owner, &100);
+
+        env.ledger().with_mut(|l| l.sequence_number = 150);
+        assert_eq!(client.get_budget().max, 10000);
+        assert_eq!(client.try_cancel_scheduled_limits(&owner, &100), Err(Ok(BudgetError::NotRegistered)));
+    }
+}
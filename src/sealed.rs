@@ -0,0 +1,196 @@
+// Commit-reveal mode for operator voting.
+// With open voting later voters can simply follow earlier ones. In commit-reveal mode operators
+// first commit to a hash of their vote during the commit phase and only reveal the vote itself
+// in the reveal phase that follows. Only revealed votes are tallied, and proposals can only be
+// executed through voting once the reveal phase is over.
+// Both phases are measured from the proposal's creation ledger. The commitment is
+// sha256(xdr(voter, approve, salt)); vote_commitment computes it for clients.
+
+use soroban_sdk::{contractimpl, contracttype, xdr::ToXdr, Address, BytesN, Env};
+
+use crate::{
+    proposal, require_operator, require_owner, voting, BudgetError, GovernanceBudgetAllocator,
+    GovernanceBudgetAllocatorClient, Proposal, Tally,
+};
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct SealedVoting {
+    pub commit_period: u32,
+    pub reveal_period: u32,
+}
+// SealedVoting defines the phases, in ledgers after a proposal is created:
+// commit_period is how long commitments are accepted
+// reveal_period is how long reveals are accepted after the commit phase
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum SealedKey {
+    SealedVoting,
+    Commitment(u64, Address),
+}
+// SealedVoting stores the SealedVoting configuration (missing means open voting)
+// Commitment stores the vote hash an operator committed to for a proposal
+
+
+pub(crate) fn config(env: &Env) -> Option<SealedVoting> {
+    env.storage().persistent().get(&SealedKey::SealedVoting)
+}
+
+fn commitment(env: &Env, voter: &Address, approve: bool, salt: &BytesN<32>) -> BytesN<32> {
+    env.crypto().sha256(&(voter.clone(), approve, salt.clone()).to_xdr(env)).into()
+}
+
+// Fails unless the current ledger is in [created + from, created + to).
+fn require_phase(env: &Env, proposal: &Proposal, from: u32, to: u32) -> Result<(), BudgetError> {
+    let now = env.ledger().sequence();
+    if now < proposal.created_ledger.saturating_add(from) || now >= proposal.created_ledger.saturating_add(to) {
+        return Err(BudgetError::WrongPhase);
+    }
+    Ok(())
+}
+
+// Open votes are not accepted in commit-reveal mode.
+pub(crate) fn check_open_vote(env: &Env) -> Result<(), BudgetError> {
+    if config(env).is_some() {
+        return Err(BudgetError::WrongPhase);
+    }
+    Ok(())
+}
+
+// In commit-reveal mode a proposal can only be executed by vote after its reveal phase.
+pub(crate) fn check_executable(env: &Env, proposal: &Proposal) -> Result<(), BudgetError> {
+    match config(env) {
+        Some(config) => require_phase(env, proposal, config.commit_period.saturating_add(config.reveal_period), u32::MAX),
+        None => Ok(()),
+    }
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Switch operator voting to commit-reveal with the given phase lengths (owner only)
+    pub fn set_sealed_voting(env: Env, caller: Address, commit_period: u32, reveal_period: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        if commit_period == 0 || reveal_period == 0 {
+            return Err(BudgetError::InvalidConfig);
+        }
+        let config = SealedVoting { commit_period, reveal_period };
+        env.storage().persistent().set(&SealedKey::SealedVoting, &config);
+        Ok(())
+    }
+
+    /// Switch operator voting back to open votes (owner only)
+    pub fn clear_sealed_voting(env: Env, caller: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().remove(&SealedKey::SealedVoting);
+        Ok(())
+    }
+
+    /// Commit to a vote on an open proposal during its commit phase (operators only)
+    pub fn commit_vote(env: Env, caller: Address, id: u64, hash: BytesN<32>) -> Result<(), BudgetError> {
+        require_operator(&env, &caller)?;
+        let config = config(&env).ok_or(BudgetError::WrongPhase)?;
+        let proposal = proposal::read_proposal(&env, id)?;
+        proposal::require_open(&env, &proposal)?;
+        require_phase(&env, &proposal, 0, config.commit_period)?;
+
+        let key = SealedKey::Commitment(id, caller);
+        if env.storage().persistent().has(&key) {
+            return Err(BudgetError::AlreadyVoted);
+        }
+        env.storage().persistent().set(&key, &hash);
+        Ok(())
+    }
+
+    /// Reveal a committed vote during the reveal phase, adding it to the tally (operators only)
+    pub fn reveal_vote(env: Env, caller: Address, id: u64, approve: bool, salt: BytesN<32>) -> Result<Tally, BudgetError> {
+        require_operator(&env, &caller)?;
+        let config = config(&env).ok_or(BudgetError::WrongPhase)?;
+        let proposal = proposal::read_proposal(&env, id)?;
+        proposal::require_open(&env, &proposal)?;
+        require_phase(&env, &proposal, config.commit_period, config.commit_period.saturating_add(config.reveal_period))?;
+
+        let committed: BytesN<32> = env
+            .storage()
+            .persistent()
+            .get(&SealedKey::Commitment(id, caller.clone()))
+            .ok_or(BudgetError::NotRegistered)?;
+        if committed != commitment(&env, &caller, approve, &salt) {
+            return Err(BudgetError::InvalidReveal);
+        }
+        let weight = voting::cast_operator_weight(&env, &caller, &proposal);
+        voting::record_vote(&env, id, &caller, approve, weight)
+    }
+
+    // Get the commit-reveal configuration, if voting is sealed
+    pub fn get_sealed_voting(env: Env) -> Option<SealedVoting> {
+        config(&env)
+    }
+
+    // Compute the commitment for a vote, to be passed to commit_vote
+    pub fn vote_commitment(env: Env, voter: Address, approve: bool, salt: BytesN<32>) -> BytesN<32> {
+        commitment(&env, &voter, approve, &salt)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::{symbol_short, BytesN, String};
+
+    fn metadata(env: &Env) -> ProposalMetadata {
+        ProposalMetadata {
+            title: symbol_short!("limits"),
+            memo: String::from_str(env, "raise the limits"),
+            recipient: None,
+            content_hash: BytesN::from_array(env, &[1; 32]),
+        }
+    }
+
+    #[test]
+    fn test_commit_reveal_vote() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let first = Address::generate(&env);
+        let second = Address::generate(&env);
+        let salt = BytesN::from_array(&env, &[9; 32]);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &first);
+        client.accept_operator(&first);
+        client.add_operator(&owner, &second);
+        client.accept_operator(&second);
+        client.set_quorum(&owner, &2);
+        client.set_sealed_voting(&owner, &10, &10);
+
+        let id = client.propose_limits(&first, &0, &20000, &metadata(&env));
+        assert_eq!(client.try_vote(&first, &id, &true), Err(Ok(BudgetError::WrongPhase)));
+
+        client.commit_vote(&first, &id, &client.vote_commitment(&first, &true, &salt));
+        client.commit_vote(&second, &id, &client.vote_commitment(&second, &true, &salt));
+
+        // Nothing is visible and nothing can be revealed during the commit phase
+        assert_eq!(client.get_tally(&id), Tally::default());
+        assert_eq!(client.try_reveal_vote(&first, &id, &true, &salt), Err(Ok(BudgetError::WrongPhase)));
+
+        env.ledger().with_mut(|l| l.sequence_number = 10);
+        assert_eq!(client.try_reveal_vote(&first, &id, &false, &salt), Err(Ok(BudgetError::InvalidReveal)));
+        client.reveal_vote(&first, &id, &true, &salt);
+        client.reveal_vote(&second, &id, &true, &salt);
+        assert_eq!(client.try_execute_proposal(&id), Err(Ok(BudgetError::WrongPhase)));
+
+        env.ledger().with_mut(|l| l.sequence_number = 20);
+        client.execute_proposal(&id);
+        assert_eq!(client.get_budget().max, 20000);
+    }
+}
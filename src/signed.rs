@@ -0,0 +1,209 @@
+// Meta-transaction support via ed25519 signature authorization.
+// An operator registers an ed25519 public key once. Afterwards anyone (a relayer) can submit a
+// SignedAdjustment together with the operator's signature over
+// (contract address, function name, payload) serialized as XDR. The signature replaces
+// require_auth, every other check of the normal increase/decrease path still applies.
+// Each payload must carry the operator's next nonce, so a signature can only be used once and
+// signed operations are applied in the order they were signed.
+
+use soroban_sdk::{contractimpl, contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env, Symbol};
+
+use crate::{
+    apply_decrease, apply_increase, require_operator, BudgetError, DataKey, GovernanceBudgetAllocator,
+    GovernanceBudgetAllocatorClient, Receipt,
+};
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct SignedAdjustment {
+    pub operator: Address,
+    pub amount: i128,
+    pub nonce: u64,
+    pub expiry: u32,
+}
+// SignedAdjustment is the payload an operator signs:
+// operator is the operator the adjustment is attributed to
+// nonce must equal the operator's next nonce (see get_nonce)
+// expiry is the last ledger the signature is accepted in
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum SignedKey {
+    SigningKey(Address),
+}
+// SigningKey stores the ed25519 public key registered by an operator
+
+
+// The bytes an operator signs for a given entrypoint.
+pub(crate) fn message(env: &Env, function: &str, payload: &SignedAdjustment) -> Bytes {
+    (env.current_contract_address(), Symbol::new(env, function), payload.clone()).to_xdr(env)
+}
+
+pub(crate) fn next_nonce(env: &Env, address: &Address) -> u64 {
+    env.storage().persistent().get(&DataKey::Nonce(address.clone())).unwrap_or(0)
+}
+
+// Checks expiry, nonce and the signature of the payload's operator, then consumes the nonce.
+fn verify(env: &Env, function: &str, payload: &SignedAdjustment, signature: &BytesN<64>) -> Result<(), BudgetError> {
+    if env.ledger().sequence() > payload.expiry {
+        return Err(BudgetError::SignatureExpired);
+    }
+    let nonce = next_nonce(env, &payload.operator);
+    if payload.nonce != nonce {
+        return Err(BudgetError::BadNonce);
+    }
+    let public_key: BytesN<32> = env
+        .storage()
+        .persistent()
+        .get(&SignedKey::SigningKey(payload.operator.clone()))
+        .ok_or(BudgetError::NotRegistered)?;
+
+    // Traps if the signature does not match
+    env.crypto().ed25519_verify(&public_key, &message(env, function, payload), signature);
+
+    env.storage().persistent().set(&DataKey::Nonce(payload.operator.clone()), &(nonce + 1));
+    Ok(())
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Register the ed25519 public key used to sign relayed adjustments (operators only)
+    pub fn set_signing_key(env: Env, caller: Address, public_key: BytesN<32>) -> Result<(), BudgetError> {
+        require_operator(&env, &caller)?;
+        env.storage().persistent().set(&SignedKey::SigningKey(caller), &public_key);
+        Ok(())
+    }
+
+    /// Increase the budget on behalf of an operator that signed the payload
+    pub fn increase_budget_signed(env: Env, payload: SignedAdjustment, signature: BytesN<64>) -> Result<Receipt, BudgetError> {
+        verify(&env, "increase_budget_signed", &payload, &signature)?;
+        apply_increase(&env, &payload.operator, payload.amount)
+    }
+
+    /// Decrease the budget on behalf of an operator that signed the payload
+    pub fn decrease_budget_signed(env: Env, payload: SignedAdjustment, signature: BytesN<64>) -> Result<Receipt, BudgetError> {
+        verify(&env, "decrease_budget_signed", &payload, &signature)?;
+        apply_decrease(&env, &payload.operator, payload.amount)
+    }
+
+    // Get the nonce the next signed operation of an address must use
+    pub fn get_nonce(env: Env, address: Address) -> u64 {
+        next_nonce(&env, &address)
+    }
+
+    // Get the ed25519 public key registered by an operator
+    pub fn get_signing_key(env: Env, address: Address) -> Option<BytesN<32>> {
+        env.storage().persistent().get(&SignedKey::SigningKey(address))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::message;
+    use crate::*;
+    use ed25519_dalek::{Signer, SigningKey};
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::{Bytes, BytesN};
+
+    fn sign(env: &Env, key: &SigningKey, msg: &Bytes) -> BytesN<64> {
+        let mut buf = [0u8; 512];
+        let len = msg.len() as usize;
+        msg.copy_into_slice(&mut buf[..len]);
+        BytesN::from_array(env, &key.sign(&buf[..len]).to_bytes())
+    }
+
+    #[test]
+    fn test_relayed_increase() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_signing_key(&operator, &BytesN::from_array(&env, &key.verifying_key().to_bytes()));
+        env.set_auths(&[]);
+
+        let payload = SignedAdjustment { operator: operator.clone(), amount: 500, nonce: 0, expiry: 100 };
+        let signature = env.as_contract(&contract_id, || {
+            sign(&env, &key, &message(&env, "increase_budget_signed", &payload))
+        });
+
+        // No operator auth is needed, the signature is enough
+        assert_eq!(client.increase_budget_signed(&payload, &signature).new, 1500);
+        assert_eq!(client.get_operator_stats(&operator).op_count, 1);
+        assert_eq!(client.get_nonce(&operator), 1);
+
+        // The signature does not carry over to the other entrypoint
+        assert!(client.try_decrease_budget_signed(&payload, &signature).is_err());
+    }
+
+    #[test]
+    fn test_expired_signature() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_signing_key(&operator, &BytesN::from_array(&env, &key.verifying_key().to_bytes()));
+
+        let payload = SignedAdjustment { operator, amount: 500, nonce: 0, expiry: 100 };
+        let signature = env.as_contract(&contract_id, || {
+            sign(&env, &key, &message(&env, "increase_budget_signed", &payload))
+        });
+
+        env.ledger().with_mut(|l| l.sequence_number = 101);
+        assert_eq!(client.try_increase_budget_signed(&payload, &signature), Err(Ok(BudgetError::SignatureExpired)));
+    }
+
+    #[test]
+    fn test_replay_rejected() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_signing_key(&operator, &BytesN::from_array(&env, &key.verifying_key().to_bytes()));
+
+        let first = SignedAdjustment { operator: operator.clone(), amount: 500, nonce: 0, expiry: 100 };
+        let skipped = SignedAdjustment { operator, amount: 500, nonce: 2, expiry: 100 };
+        let (first_sig, skipped_sig) = env.as_contract(&contract_id, || {
+            (
+                sign(&env, &key, &message(&env, "increase_budget_signed", &first)),
+                sign(&env, &key, &message(&env, "increase_budget_signed", &skipped)),
+            )
+        });
+
+        // Out of order and replayed nonces are both rejected
+        assert_eq!(client.try_increase_budget_signed(&skipped, &skipped_sig), Err(Ok(BudgetError::BadNonce)));
+        client.increase_budget_signed(&first, &first_sig);
+        assert_eq!(client.try_increase_budget_signed(&first, &first_sig), Err(Ok(BudgetError::BadNonce)));
+        assert_eq!(client.get_budget().current, 1500);
+    }
+}
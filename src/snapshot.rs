@@ -0,0 +1,172 @@
+// Snapshot and checkpoint system.
+// A checkpoint is the BudgetState recorded at a ledger. Operators can take one at any time with
+// snapshot(), and when epochs are enabled one is taken automatically for the first change of
+// every new epoch, so the state at each epoch boundary is always available to governance votes.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env};
+
+use crate::{
+    current_epoch, epoch_length, read_budget, require_operator, stored_budget, BudgetError, BudgetState,
+    GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient,
+};
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Checkpoint {
+    pub ledger: u32,
+    pub budget: BudgetState,
+}
+// Checkpoint stores the budget as it was at the given ledger.
+// Checkpoints are appended in ledger order, which lets get_budget_at binary search them.
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum SnapshotKey {
+    SnapshotCount,
+    Snapshot(u32),
+    LastSnapshotEpoch,
+}
+// SnapshotCount stores the number of checkpoints (the next checkpoint index)
+// Snapshot stores a Checkpoint by index
+// LastSnapshotEpoch stores the epoch of the last automatic checkpoint
+
+
+fn count(env: &Env) -> u32 {
+    env.storage().persistent().get(&SnapshotKey::SnapshotCount).unwrap_or(0)
+}
+
+fn push(env: &Env, ledger: u32, budget: BudgetState) -> u32 {
+    let index = count(env);
+    let checkpoint = Checkpoint { ledger, budget };
+    env.storage().persistent().set(&SnapshotKey::Snapshot(index), &checkpoint);
+    env.storage().persistent().set(&SnapshotKey::SnapshotCount, &(index + 1));
+    index
+}
+
+// Called before the budget is written. On the first change of a new epoch the state about to be
+// replaced is still the state at the epoch boundary, so it is recorded at the boundary ledger.
+pub(crate) fn on_budget_change(env: &Env) {
+    let epoch = match current_epoch(env) {
+        Some(epoch) => epoch,
+        None => return,
+    };
+    let last: Option<u32> = env.storage().persistent().get(&SnapshotKey::LastSnapshotEpoch);
+    if last == Some(epoch) {
+        return;
+    }
+    push(env, epoch * epoch_length(env), stored_budget(env));
+    env.storage().persistent().set(&SnapshotKey::LastSnapshotEpoch, &epoch);
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Record a checkpoint of the current budget (operators only), returns its index
+    pub fn snapshot(env: Env, caller: Address) -> Result<u32, BudgetError> {
+        require_operator(&env, &caller)?;
+        // Record the boundary checkpoint first so checkpoints stay in ledger order
+        on_budget_change(&env);
+        Ok(push(&env, env.ledger().sequence(), read_budget(&env)))
+    }
+
+    // Get the number of checkpoints taken so far
+    pub fn get_snapshot_count(env: Env) -> u32 {
+        count(&env)
+    }
+
+    // Get a checkpoint by index
+    pub fn get_snapshot(env: Env, index: u32) -> Option<Checkpoint> {
+        env.storage().persistent().get(&SnapshotKey::Snapshot(index))
+    }
+
+    // Get the budget recorded by the latest checkpoint at or before the given ledger
+    pub fn get_budget_at(env: Env, ledger: u32) -> Option<BudgetState> {
+        // Binary search for the first checkpoint after the ledger
+        let mut low = 0;
+        let mut high = count(&env);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let checkpoint: Checkpoint = env.storage().persistent().get(&SnapshotKey::Snapshot(mid)).unwrap();
+            if checkpoint.ledger <= ledger {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        if low == 0 {
+            return None;
+        }
+        let checkpoint: Checkpoint = env.storage().persistent().get(&SnapshotKey::Snapshot(low - 1)).unwrap();
+        Some(checkpoint.budget)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    #[test]
+    fn test_manual_snapshots() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+
+        env.ledger().with_mut(|l| l.sequence_number = 10);
+        assert_eq!(client.snapshot(&operator), 0);
+
+        env.ledger().with_mut(|l| l.sequence_number = 20);
+        client.increase_budget(&operator, &500);
+        assert_eq!(client.snapshot(&operator), 1);
+
+        assert_eq!(client.get_snapshot_count(), 2);
+        assert_eq!(client.get_snapshot(&1).unwrap().ledger, 20);
+        assert_eq!(client.get_budget_at(&5), None);
+        assert_eq!(client.get_budget_at(&15).unwrap().current, 1000);
+        assert_eq!(client.get_budget_at(&25).unwrap().current, 1500);
+    }
+
+    #[test]
+    fn test_epoch_boundary_snapshot() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_epoch_length(&owner, &100);
+
+        env.ledger().with_mut(|l| l.sequence_number = 150);
+        client.increase_budget(&operator, &500);
+        client.increase_budget(&operator, &500);
+
+        // Only the first change in epoch 1 records the boundary state
+        assert_eq!(client.get_snapshot_count(), 1);
+        let checkpoint = client.get_snapshot(&0).unwrap();
+        assert_eq!(checkpoint.ledger, 100);
+        assert_eq!(checkpoint.budget.current, 1000);
+
+        env.ledger().with_mut(|l| l.sequence_number = 230);
+        client.decrease_budget(&operator, &100);
+        assert_eq!(client.get_snapshot(&1).unwrap().budget.current, 2000);
+        assert_eq!(client.get_budget_at(&210).unwrap().current, 2000);
+    }
+}
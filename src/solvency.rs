@@ -0,0 +1,163 @@
+// Solvency check for token-backed mode.
+// verify_backing compares, for the backing token, every allowlisted token and every other token
+// the contract holds on someone's behalf (e.g. a bond taken before the backing token changed), the
+// balance the contract actually holds with what the accounting says it should hold (see
+// treasury.rs). It returns one report per token and emits an "unbacked" event for
+// every token that falls short.
+// With auto-pause on, a shortfall also pauses the contract so no more funds leave until the owner
+// has looked into it. Anyone can run the check.
+
+use soroban_sdk::{contractimpl, contracttype, token, Address, Env, Vec};
+
+use crate::{
+    events, require_owner, set_paused, treasury, BudgetError, GovernanceBudgetAllocator,
+    GovernanceBudgetAllocatorClient, UnbackedEvent,
+};
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct BackingReport {
+    pub token: Address,
+    pub balance: i128,
+    pub accounted: i128,
+    pub shortfall: i128,
+}
+// BackingReport is the result of the check for one token:
+// balance is what the contract holds, accounted is what the accounting expects it to hold
+// shortfall is accounted - balance when positive, 0 when the token is fully backed
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum SolvencyKey {
+    BackingAutoPause,
+}
+// BackingAutoPause stores whether a failed check pauses the contract
+
+
+fn check(env: &Env, token: Address) -> BackingReport {
+    let balance = token::Client::new(env, &token).balance(&env.current_contract_address());
+    let accounted = treasury::accounted(env, &token);
+    let shortfall = (accounted - balance).max(0);
+    BackingReport { token, balance, accounted, shortfall }
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Pause the contract whenever verify_backing finds a shortfall (owner only)
+    pub fn set_backing_auto_pause(env: Env, caller: Address, enabled: bool) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().set(&SolvencyKey::BackingAutoPause, &enabled);
+        Ok(())
+    }
+
+    /// Compare token balances with the accounting, per token (anyone)
+    pub fn verify_backing(env: Env) -> Vec<BackingReport> {
+        let mut tokens = treasury::allowed_tokens(&env);
+        if let Ok(backing) = treasury::backing_token(&env) {
+            if !tokens.contains(&backing) {
+                tokens.push_front(backing);
+            }
+        }
+        for token in treasury::held_tokens(&env).iter() {
+            if !tokens.contains(&token) {
+                tokens.push_back(token);
+            }
+        }
+
+        let mut reports = Vec::new(&env);
+        let mut solvent = true;
+        for token in tokens.iter() {
+            let report = check(&env, token);
+            if report.shortfall > 0 {
+                solvent = false;
+                events::publish(&env, events::UNBACKED, &report.token, UnbackedEvent { shortfall: report.shortfall });
+            }
+            reports.push_back(report);
+        }
+
+        if !solvent && env.storage().persistent().get(&SolvencyKey::BackingAutoPause).unwrap_or(false) {
+            set_paused(&env, true);
+        }
+        reports
+    }
+
+    // Check if a failed backing check pauses the contract
+    pub fn is_backing_auto_pause(env: Env) -> bool {
+        env.storage().persistent().get(&SolvencyKey::BackingAutoPause).unwrap_or(false)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::token;
+
+    #[test]
+    fn test_shortfall_pauses() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        let sac = env.register_stellar_asset_contract_v2(owner.clone());
+        token::StellarAssetClient::new(&env, &sac.address()).mint(&contract_id, &1000);
+        client.set_token(&owner, &sac.address());
+        client.set_backing_auto_pause(&owner, &true);
+
+        let report = client.verify_backing().get(0).unwrap();
+        assert_eq!(report.shortfall, 0);
+        assert!(!client.is_paused());
+
+        // Accounting grows past the funds held
+        client.add_operator(&owner, &owner);
+        client.accept_operator(&owner);
+        client.increase_budget(&owner, &200);
+        let report = client.verify_backing().get(0).unwrap();
+        assert_eq!(report.accounted, 1200);
+        assert_eq!(report.shortfall, 200);
+        assert!(client.is_paused());
+    }
+
+    #[test]
+    fn test_checks_held_tokens() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let staker = Address::generate(&env);
+
+        client.initialize(&owner, &0, &0, &10000);
+
+        env.mock_all_auths();
+        let old = mocks::register_token(&env, &owner);
+        old.mint(&staker, &500);
+        client.set_token(&owner, &old.address);
+        client.stake(&staker, &500);
+
+        // The bond stays held in the old token after the backing token changes
+        let new = mocks::register_token(&env, &owner);
+        client.set_token(&owner, &new.address);
+        token::Client::new(&env, &old.address).burn(&contract_id, &200);
+        let reports = client.verify_backing();
+        assert_eq!(reports.len(), 2);
+        let report = reports.get(1).unwrap();
+        assert_eq!((report.token, report.accounted, report.shortfall), (old.address.clone(), 500, 200));
+
+        // Released in full, it is no longer checked
+        old.mint(&contract_id, &200);
+        client.set_token(&owner, &old.address);
+        client.unstake(&staker, &500);
+        client.set_token(&owner, &new.address);
+        assert_eq!(client.verify_backing().len(), 1);
+    }
+}
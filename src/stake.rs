@@ -0,0 +1,166 @@
+// Operator staking and slashing.
+// With staking configured, operators lock a bond of the backing token in the contract and must
+// keep at least min_bond locked to act (checked like the other operator requirements). The owner,
+// typically a governance contract, can slash a bond for proven misbehavior; the slashed tokens are
+// added to the budget, as far as max allows. A bond can be withdrawn down to min_bond while the
+// address is an operator, and completely once it is not.
+
+use soroban_sdk::{contractimpl, contracttype, token, Address, BytesN, Env};
+
+use crate::{
+    commit_adjustment, events, exempt_from_veto, is_listed, read_budget, require_owner, treasury, BudgetError,
+    GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient, SlashEvent,
+};
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum StakeKey {
+    MinBond,
+    Bond(Address),
+}
+// MinBond stores the bond operators must keep locked (missing means staking is off)
+// Bond stores the amount an address has locked
+
+
+fn min_bond(env: &Env) -> Option<i128> {
+    env.storage().persistent().get(&StakeKey::MinBond)
+}
+
+fn bond(env: &Env, address: &Address) -> i128 {
+    env.storage().persistent().get(&StakeKey::Bond(address.clone())).unwrap_or(0)
+}
+
+fn set_bond(env: &Env, address: &Address, amount: i128) {
+    env.storage().persistent().set(&StakeKey::Bond(address.clone()), &amount);
+}
+
+// Fails if staking is on and the address has not locked the minimum bond.
+pub(crate) fn check(env: &Env, address: &Address) -> Result<(), BudgetError> {
+    match min_bond(env) {
+        Some(min) if bond(env, address) < min => Err(BudgetError::InsufficientStake),
+        _ => Ok(()),
+    }
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Require operators to lock a bond of the backing token, 0 turns staking off (owner only)
+    pub fn set_min_bond(env: Env, caller: Address, amount: i128) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        treasury::backing_token(&env)?;
+        if amount < 0 {
+            return Err(BudgetError::InvalidAmount);
+        }
+        if amount == 0 {
+            env.storage().persistent().remove(&StakeKey::MinBond);
+        } else {
+            env.storage().persistent().set(&StakeKey::MinBond, &amount);
+        }
+        Ok(())
+    }
+
+    /// Lock backing tokens as bond (anyone, usually operators and nominees)
+    pub fn stake(env: Env, from: Address, amount: i128) -> Result<i128, BudgetError> {
+        from.require_auth();
+        if amount <= 0 {
+            return Err(BudgetError::InvalidAmount);
+        }
+        let token = treasury::backing_token(&env)?;
+        token::Client::new(&env, &token).transfer(&from, &env.current_contract_address(), &amount);
+        treasury::hold(&env, &token, amount);
+        let total = bond(&env, &from) + amount;
+        set_bond(&env, &from, total);
+        Ok(total)
+    }
+
+    /// Withdraw bond, operators must leave min_bond locked (bond holder only)
+    pub fn unstake(env: Env, from: Address, amount: i128) -> Result<i128, BudgetError> {
+        from.require_auth();
+        let locked = bond(&env, &from);
+        if amount <= 0 || amount > locked {
+            return Err(BudgetError::InvalidAmount);
+        }
+        let remaining = locked - amount;
+        if is_listed(&env, &from) && remaining < min_bond(&env).unwrap_or(0) {
+            return Err(BudgetError::InsufficientStake);
+        }
+        let token = treasury::backing_token(&env)?;
+        set_bond(&env, &from, remaining);
+        treasury::release(&env, &token, amount);
+        token::Client::new(&env, &token).transfer(&env.current_contract_address(), &from, &amount);
+        Ok(remaining)
+    }
+
+    /// Slash an operator's bond and add it to the budget (owner only), returns the amount added
+    pub fn slash(env: Env, caller: Address, operator: Address, amount: i128, evidence: BytesN<32>) -> Result<i128, BudgetError> {
+        require_owner(&env, &caller)?;
+        let locked = bond(&env, &operator);
+        if amount <= 0 || amount > locked {
+            return Err(BudgetError::InvalidAmount);
+        }
+        let token = treasury::backing_token(&env)?;
+        set_bond(&env, &operator, locked - amount);
+        treasury::release(&env, &token, amount);
+
+        // What does not fit under max stays in the contract as an unaccounted balance
+        let mut budget = read_budget(&env);
+        let added = amount.min(budget.max - budget.current).max(0);
+        if added > 0 {
+            let new_value = budget.current + added;
+            let receipt = commit_adjustment(&env, &caller, &mut budget, new_value);
+            exempt_from_veto(&env, &receipt);
+        }
+        events::publish(&env, events::SLASH, &operator, SlashEvent { amount, evidence });
+        Ok(added)
+    }
+
+    // Get the bond operators must keep locked, if staking is on
+    pub fn get_min_bond(env: Env) -> Option<i128> {
+        min_bond(&env)
+    }
+
+    // Get the bond an address has locked
+    pub fn get_bond(env: Env, address: Address) -> i128 {
+        bond(&env, &address)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{token, BytesN};
+
+    #[test]
+    fn test_stake_and_slash() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        let sac = env.register_stellar_asset_contract_v2(owner.clone());
+        token::StellarAssetClient::new(&env, &sac.address()).mint(&operator, &1000);
+        client.set_token(&owner, &sac.address());
+        client.set_min_bond(&owner, &500);
+
+        assert_eq!(client.try_add_operator(&owner, &operator), Err(Ok(BudgetError::InsufficientStake)));
+        client.stake(&operator, &600);
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        assert_eq!(client.try_unstake(&operator, &200), Err(Ok(BudgetError::InsufficientStake)));
+
+        // Slashed below the minimum, the operator can no longer act
+        assert_eq!(client.slash(&owner, &operator, &300, &BytesN::from_array(&env, &[9; 32])), 300);
+        assert_eq!(client.get_budget().current, 1300);
+        assert_eq!(client.get_bond(&operator), 300);
+        assert_eq!(client.try_increase_budget(&operator, &100), Err(Ok(BudgetError::InsufficientStake)));
+    }
+}
@@ -0,0 +1,98 @@
+// Per-operator statistics.
+// Every committed adjustment updates running totals for the operator that made it, so the owner
+// can audit who actually uses their privileges without replaying history.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env};
+
+use crate::{GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient};
+
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[contracttype]
+pub struct OperatorStats {
+    pub total_increased: i128,
+    pub total_decreased: i128,
+    pub op_count: u32,
+    pub last_action_ledger: u32,
+}
+// OperatorStats stores the running totals for one operator:
+// total_increased and total_decreased are the summed adjustment amounts
+// op_count is the number of adjustments made
+// last_action_ledger is the ledger of the most recent adjustment (0 if none yet)
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum StatsKey {
+    OperatorStats(Address),
+}
+// OperatorStats stores the OperatorStats of an address
+
+
+pub(crate) fn read(env: &Env, operator: &Address) -> OperatorStats {
+    env.storage()
+        .persistent()
+        .get(&StatsKey::OperatorStats(operator.clone()))
+        .unwrap_or_default()
+}
+
+// Called for every committed adjustment.
+pub(crate) fn record(env: &Env, operator: &Address, previous: i128, new: i128) {
+    let mut stats = read(env, operator);
+    if new >= previous {
+        stats.total_increased = stats.total_increased.saturating_add(new - previous);
+    } else {
+        stats.total_decreased = stats.total_decreased.saturating_add(previous - new);
+    }
+    stats.op_count = stats.op_count.saturating_add(1);
+    stats.last_action_ledger = env.ledger().sequence();
+    env.storage().persistent().set(&StatsKey::OperatorStats(operator.clone()), &stats);
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    // Get the adjustment statistics of an operator (all zero if it never adjusted the budget)
+    pub fn get_operator_stats(env: Env, address: Address) -> OperatorStats {
+        read(&env, &address)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    #[test]
+    fn test_operator_stats() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let idle = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.add_operator(&owner, &idle);
+        client.accept_operator(&idle);
+
+        env.ledger().with_mut(|l| l.sequence_number = 42);
+        client.increase_budget(&operator, &500);
+        client.increase_budget(&operator, &250);
+        client.decrease_budget(&operator, &100);
+
+        let stats = client.get_operator_stats(&operator);
+        assert_eq!(stats.total_increased, 750);
+        assert_eq!(stats.total_decreased, 100);
+        assert_eq!(stats.op_count, 3);
+        assert_eq!(stats.last_action_ledger, 42);
+
+        assert_eq!(client.get_operator_stats(&idle), OperatorStats::default());
+    }
+}
@@ -0,0 +1,124 @@
+// Reporting tags.
+// A lighter alternative to categories: an adjustment or payment can carry a free-form tag (a
+// Symbol such as "travel"), and running totals are kept per tag for reporting. Tags need no setup
+// and put no limit or scope on anything; the tagged entrypoints behave exactly like their untagged
+// counterparts apart from updating the tag's totals.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env, String, Symbol};
+
+use crate::{
+    BudgetAllocator, BudgetError, BudgetTreasury, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient, Receipt,
+};
+
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[contracttype]
+pub struct TagTotal {
+    pub increased: i128,
+    pub decreased: i128,
+    pub count: u32,
+}
+// TagTotal stores the running totals of a tag:
+// increased and decreased are the summed amounts of its adjustments, payments counting as decreases
+// count is the number of tagged adjustments and payments
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum TagKey {
+    TagTotal(Symbol),
+}
+// TagTotal stores the TagTotal of a tag
+
+
+fn read_total(env: &Env, tag: &Symbol) -> TagTotal {
+    env.storage().persistent().get(&TagKey::TagTotal(tag.clone())).unwrap_or_default()
+}
+
+fn record(env: &Env, tag: &Symbol, amount: i128, increase: bool) {
+    let mut total = read_total(env, tag);
+    if increase {
+        total.increased = total.increased.saturating_add(amount);
+    } else {
+        total.decreased = total.decreased.saturating_add(amount);
+    }
+    total.count = total.count.saturating_add(1);
+    env.storage().persistent().set(&TagKey::TagTotal(tag.clone()), &total);
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Increase the budget and count it under tag (operators only)
+    pub fn increase_tagged(env: Env, caller: Address, amount: i128, tag: Symbol) -> Result<Receipt, BudgetError> {
+        let receipt = Self::increase_budget(env.clone(), caller, amount)?;
+        record(&env, &tag, amount, true);
+        Ok(receipt)
+    }
+
+    /// Decrease the budget and count it under tag (operators only)
+    pub fn decrease_tagged(env: Env, caller: Address, amount: i128, tag: Symbol) -> Result<Receipt, BudgetError> {
+        let receipt = Self::decrease_budget(env.clone(), caller, amount)?;
+        record(&env, &tag, amount, false);
+        Ok(receipt)
+    }
+
+    // Get the running totals of a tag (all zero if it was never used)
+    pub fn get_tag_total(env: Env, tag: Symbol) -> TagTotal {
+        read_total(&env, &tag)
+    }
+}
+
+
+// Payments need token-backed mode.
+#[cfg(feature = "treasury")]
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Pay out of the budget and count it under tag (operators only), returns the payment id
+    pub fn spend_tagged(env: Env, caller: Address, recipient: Address, amount: i128, memo: String, tag: Symbol) -> Result<u64, BudgetError> {
+        let id = Self::spend(env.clone(), caller, recipient, amount, memo)?;
+        record(&env, &tag, amount, false);
+        Ok(id)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{symbol_short, String};
+
+    #[test]
+    fn test_tag_totals() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let recipient = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        let token = mocks::register_token(&env, &owner);
+        token.mint(&contract_id, &1000);
+        client.set_token(&owner, &token.address);
+
+        let travel = symbol_short!("travel");
+        client.increase_tagged(&operator, &500, &travel);
+        client.decrease_tagged(&operator, &200, &travel);
+        client.spend_tagged(&operator, &recipient, &100, &String::from_str(&env, "flight"), &travel);
+        client.decrease_tagged(&operator, &50, &symbol_short!("office"));
+        assert_eq!(client.get_tag_total(&travel), TagTotal { increased: 500, decreased: 300, count: 3 });
+        assert_eq!(client.get_tag_total(&symbol_short!("office")).decreased, 50);
+
+        // A failed adjustment is not counted
+        assert_eq!(client.try_decrease_tagged(&operator, &10000, &travel), Err(Ok(BudgetError::BelowMin)));
+        assert_eq!(client.get_tag_total(&travel).count, 3);
+        assert_eq!(client.get_budget().current, 1150);
+    }
+}
@@ -0,0 +1,79 @@
+// Test fixtures for contracts composing with the allocator.
+// Setting up an allocator through its entrypoints takes an initialize call, a nomination and an
+// acceptance per operator, all with auths. These helpers write the same state straight into the
+// storage of a registered instance instead, for tests that are not about that setup. Available to
+// this crate's tests and, with the testutils feature, to downstream tests.
+
+use soroban_sdk::{Address, Env, Vec};
+
+use crate::{
+    set_listed, write_meta, write_owner, BudgetState, DataKey, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient,
+};
+
+
+// Writes the owner and budget of an initialized allocator, replacing any operators with none.
+pub fn seed_allocator(env: &Env, contract_id: &Address, owner: &Address, budget: &BudgetState) {
+    env.as_contract(contract_id, || {
+        write_owner(env, owner);
+        env.storage().instance().set(&DataKey::Budget, budget);
+        env.storage().persistent().set(&DataKey::Operators, &Vec::<Address>::new(env));
+        write_meta(env, None, None);
+    });
+}
+
+// Lists operators as if they had been nominated and accepted.
+pub fn seed_operators(env: &Env, contract_id: &Address, operators: &Vec<Address>) {
+    env.as_contract(contract_id, || {
+        let mut listed: Vec<Address> = env.storage().persistent().get(&DataKey::Operators).unwrap_or(Vec::new(env));
+        for operator in operators.iter() {
+            if !listed.contains(&operator) {
+                set_listed(env, &operator, true);
+                listed.push_back(operator);
+            }
+        }
+        env.storage().persistent().set(&DataKey::Operators, &listed);
+    });
+}
+
+// Registers an allocator already set up with the given owner, operators and budget.
+pub fn register_allocator(
+    env: &Env,
+    owner: &Address,
+    operators: &Vec<Address>,
+    budget: &BudgetState,
+) -> GovernanceBudgetAllocatorClient<'static> {
+    let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+    seed_allocator(env, &contract_id, owner, budget);
+    seed_operators(env, &contract_id, operators);
+    GovernanceBudgetAllocatorClient::new(env, &contract_id)
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::vec;
+
+    #[test]
+    fn test_register_seeded_allocator() {
+        let env = Env::default();
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let budget = BudgetState { current: 1000, min: 0, max: 10000 };
+
+        let client = testutils::register_allocator(&env, &owner, &vec![&env, operator.clone()], &budget);
+        assert_eq!(client.get_owner(), owner);
+        assert_eq!(client.get_budget(), budget);
+        assert!(client.is_operator(&operator));
+
+        // Behaves like an allocator set up through its entrypoints
+        env.mock_all_auths();
+        client.increase_budget(&operator, &500);
+        assert_eq!(client.get_budget_meta().last_op_id, Some(0));
+        let stranger = Address::generate(&env);
+        assert_eq!(client.try_increase_budget(&stranger, &500), Err(Ok(BudgetError::NotOperator)));
+        client.remove_operator(&owner, &operator);
+        assert_eq!(client.get_operators().len(), 0);
+    }
+}
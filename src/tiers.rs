@@ -0,0 +1,145 @@
+// Operator tiers.
+// The owner can assign operators a tier and give every tier a policy: the largest single
+// adjustment its operators may make, and whether they must go through the timelocked queue
+// (see queue.rs) instead of adjusting directly. A deputy is held to the policy of the operator it
+// stands in for. Operators without a tier, and tiers without a policy, are not restricted.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env};
+
+use crate::{acting_for, check_operator, require_owner, BudgetError, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient};
+
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[contracttype]
+pub enum Tier {
+    Junior,
+    Senior,
+    Lead,
+}
+// Tier is the rank an operator is assigned; what a tier may do is set by its TierPolicy
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct TierPolicy {
+    pub max_adjustment: i128,
+    pub timelocked: bool,
+}
+// TierPolicy restricts the operators of a tier:
+// max_adjustment is the largest amount of a single adjustment (0 means no limit)
+// timelocked forces adjustments through the queue
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum TierKey {
+    OperatorTier(Address),
+    Policy(Tier),
+}
+// OperatorTier stores the Tier of an operator (missing means untiered)
+// Policy stores the TierPolicy of a tier (missing means unrestricted)
+
+
+// A deputy is held to the tier of the operator it stands in for.
+fn policy_of(env: &Env, address: &Address) -> Option<TierPolicy> {
+    let tier: Tier = env.storage().persistent().get(&TierKey::OperatorTier(acting_for(env, address)))?;
+    env.storage().persistent().get(&TierKey::Policy(tier))
+}
+
+// Rejects direct adjustments by operators whose tier has to use the queue.
+pub(crate) fn check_direct(env: &Env, caller: &Address) -> Result<(), BudgetError> {
+    match policy_of(env, caller) {
+        Some(policy) if policy.timelocked => Err(BudgetError::TimelockActive),
+        _ => Ok(()),
+    }
+}
+
+// Rejects adjustments larger than the caller's tier allows.
+pub(crate) fn check_size(env: &Env, caller: &Address, amount: i128) -> Result<(), BudgetError> {
+    match policy_of(env, caller) {
+        Some(policy) if policy.max_adjustment > 0 && amount > policy.max_adjustment => Err(BudgetError::TierLimitExceeded),
+        _ => Ok(()),
+    }
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Set the policy of a tier (owner only)
+    pub fn set_tier_policy(env: Env, caller: Address, tier: Tier, max_adjustment: i128, timelocked: bool) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        if max_adjustment < 0 {
+            return Err(BudgetError::InvalidConfig);
+        }
+        env.storage().persistent().set(&TierKey::Policy(tier), &TierPolicy { max_adjustment, timelocked });
+        Ok(())
+    }
+
+    /// Assign an operator to a tier, None removes it from its tier (owner only)
+    pub fn set_operator_tier(env: Env, caller: Address, operator: Address, tier: Option<Tier>) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        check_operator(&env, &operator)?;
+        let key = TierKey::OperatorTier(operator);
+        match tier {
+            Some(tier) => env.storage().persistent().set(&key, &tier),
+            None => env.storage().persistent().remove(&key),
+        }
+        Ok(())
+    }
+
+    // Get the tier of an operator, if any
+    pub fn get_operator_tier(env: Env, operator: Address) -> Option<Tier> {
+        env.storage().persistent().get(&TierKey::OperatorTier(operator))
+    }
+
+    // Get the policy of a tier, if any
+    pub fn get_tier_policy(env: Env, tier: Tier) -> Option<TierPolicy> {
+        env.storage().persistent().get(&TierKey::Policy(tier))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    #[test]
+    fn test_tier_limits() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let junior = Address::generate(&env);
+        let lead = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &junior);
+        client.accept_operator(&junior);
+        client.add_operator(&owner, &lead);
+        client.accept_operator(&lead);
+        client.set_tier_policy(&owner, &Tier::Junior, &100, &true);
+        client.set_tier_policy(&owner, &Tier::Lead, &0, &false);
+        client.set_operator_tier(&owner, &junior, &Some(Tier::Junior));
+        client.set_operator_tier(&owner, &lead, &Some(Tier::Lead));
+
+        assert_eq!(client.increase_budget(&lead, &5000).new, 6000);
+
+        // Juniors must queue, and only small amounts
+        assert_eq!(client.try_increase_budget(&junior, &50), Err(Ok(BudgetError::TimelockActive)));
+
+        // Their deputies too
+        let deputy = Address::generate(&env);
+        client.delegate_operator(&junior, &deputy, &100);
+        assert_eq!(client.try_increase_budget(&deputy, &5000), Err(Ok(BudgetError::TimelockActive)));
+        client.set_adjustment_delay(&owner, &10);
+        let big = client.queue_adjustment(&junior, &500, &true);
+        let small = client.queue_adjustment(&junior, &50, &true);
+        env.ledger().with_mut(|l| l.sequence_number = 10);
+        assert_eq!(client.try_execute_queued(&big), Err(Ok(BudgetError::TierLimitExceeded)));
+        assert_eq!(client.execute_queued(&small).new, 6050);
+    }
+}
@@ -0,0 +1,244 @@
+// Token-weighted community voting for limit increases.
+// When configured, raising max more than the threshold above the approved max can no longer be
+// done by the owner's signature (set_limits, SetLimits, schedule_limits) or by ratifying/executing a
+// proposal the usual way. Such increases have to go through a limit proposal that governance token
+// holders vote on. The approved max is max when token voting was set up, and only a passed token
+// vote moves it, so a series of small increases adds up against the same threshold.
+// Votes are weighted by the voter's balance at the ledger the proposal was created in, which the
+// governance token must provide through VotesToken::balance_at (a checkpointed balance), so
+// tokens moved after the proposal was filed cannot be used to vote twice. Balances delegated to a
+// holder at that ledger (see delegation.rs) are added to its own.
+
+use soroban_sdk::{contractclient, contractimpl, contracttype, Address, Env};
+
+use crate::{
+    delegation, proposal, read_budget, require_owner, BudgetError, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient,
+    Proposal, ProposalAction,
+};
+
+
+// Interface the governance token has to implement on top of the token interface.
+#[contractclient(name = "VotesTokenClient")]
+pub trait VotesToken {
+    fn balance_at(env: Env, id: Address, ledger: u32) -> i128;
+}
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct TokenVotingConfig {
+    pub token: Address,
+    pub threshold: i128,
+    pub quorum: i128,
+}
+// TokenVotingConfig defines which increases need a token vote:
+// token is the governance token implementing VotesToken
+// threshold is how much max may grow above the approved max without a vote
+// quorum is the token amount in favour needed to pass
+
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[contracttype]
+pub struct TokenTally {
+    pub for_votes: i128,
+    pub against_votes: i128,
+}
+// TokenTally stores the summed token balances for and against a proposal.
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum TokenVoteKey {
+    TokenVoting,
+    HolderTally(u64),
+    HolderVoted(u64, Address),
+    ApprovedMax,
+}
+// TokenVoting stores the TokenVotingConfig (missing means token voting is off)
+// HolderTally stores the TokenTally of a proposal
+// HolderVoted marks that an address voted on a proposal with tokens
+// ApprovedMax stores the max the owner can raise max from by up to the threshold without a vote
+
+
+fn config(env: &Env) -> Option<TokenVotingConfig> {
+    env.storage().persistent().get(&TokenVoteKey::TokenVoting)
+}
+
+fn read_tally(env: &Env, id: u64) -> TokenTally {
+    env.storage().persistent().get(&TokenVoteKey::HolderTally(id)).unwrap_or_default()
+}
+
+fn approved_max(env: &Env) -> i128 {
+    env.storage().persistent().get(&TokenVoteKey::ApprovedMax).unwrap_or(read_budget(env).max)
+}
+
+fn needs_vote(env: &Env, max: i128) -> bool {
+    match config(env) {
+        Some(config) => max > approved_max(env).saturating_add(config.threshold),
+        None => false,
+    }
+}
+
+// Fails if setting this max without a token vote would exceed the threshold.
+pub(crate) fn gate(env: &Env, max: i128) -> Result<(), BudgetError> {
+    if needs_vote(env, max) {
+        return Err(BudgetError::TokenVoteRequired);
+    }
+    Ok(())
+}
+
+// Same check for a proposal executed through owner ratification or operator voting.
+pub(crate) fn gate_proposal(env: &Env, proposal: &Proposal) -> Result<(), BudgetError> {
+    match proposal.action {
+        ProposalAction::SetLimits(_, max) => gate(env, max),
+    }
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Require a token vote for increases of max above the threshold (owner only)
+    pub fn set_token_voting(env: Env, caller: Address, token: Address, threshold: i128, quorum: i128) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        if threshold < 0 || quorum <= 0 {
+            return Err(BudgetError::InvalidConfig);
+        }
+        let config = TokenVotingConfig { token, threshold, quorum };
+        env.storage().persistent().set(&TokenVoteKey::TokenVoting, &config);
+        env.storage().persistent().set(&TokenVoteKey::ApprovedMax, &read_budget(&env).max);
+        Ok(())
+    }
+
+    /// Turn token voting off (owner only)
+    pub fn clear_token_voting(env: Env, caller: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().remove(&TokenVoteKey::TokenVoting);
+        env.storage().persistent().remove(&TokenVoteKey::ApprovedMax);
+        Ok(())
+    }
+
+    /// Vote on an open proposal with the balance held when it was created (token holders)
+    pub fn token_vote(env: Env, caller: Address, id: u64, approve: bool) -> Result<TokenTally, BudgetError> {
+        caller.require_auth();
+        let config = config(&env).ok_or(BudgetError::InvalidConfig)?;
+        let proposal = proposal::read_proposal(&env, id)?;
+        proposal::require_open(&env, &proposal)?;
+
+        let voted = TokenVoteKey::HolderVoted(id, caller.clone());
+        if env.storage().persistent().has(&voted) {
+            return Err(BudgetError::AlreadyVoted);
+        }
+        let token = VotesTokenClient::new(&env, &config.token);
+        let used = delegation::DelegationKey::TokenWeightUsed;
+        let weight = delegation::cast_weight(&env, used, id, &caller, proposal.created_ledger, |holder| {
+            token.balance_at(holder, &proposal.created_ledger)
+        });
+        if weight <= 0 {
+            return Err(BudgetError::InvalidAmount);
+        }
+        env.storage().persistent().set(&voted, &true);
+
+        let mut tally = read_tally(&env, id);
+        if approve {
+            tally.for_votes = tally.for_votes.saturating_add(weight);
+        } else {
+            tally.against_votes = tally.against_votes.saturating_add(weight);
+        }
+        env.storage().persistent().set(&TokenVoteKey::HolderTally(id), &tally);
+        Ok(tally)
+    }
+
+    /// Execute an open proposal that passed the token vote (anyone)
+    pub fn execute_token_vote(env: Env, id: u64) -> Result<(), BudgetError> {
+        let config = config(&env).ok_or(BudgetError::InvalidConfig)?;
+        let mut proposal = proposal::read_proposal(&env, id)?;
+        proposal::require_open(&env, &proposal)?;
+
+        let tally = read_tally(&env, id);
+        if tally.for_votes < config.quorum || tally.for_votes <= tally.against_votes {
+            return Err(BudgetError::QuorumNotReached);
+        }
+        proposal::execute_unratified(&env, &mut proposal)?;
+        let ProposalAction::SetLimits(_, max) = proposal.action;
+        env.storage().persistent().set(&TokenVoteKey::ApprovedMax, &max);
+        Ok(())
+    }
+
+    // Get the token voting configuration, if any
+    pub fn get_token_voting(env: Env) -> Option<TokenVotingConfig> {
+        config(&env)
+    }
+
+    // Get the max that increases without a token vote are measured from (only set with token voting on)
+    pub fn get_approved_max(env: Env) -> Option<i128> {
+        config(&env).map(|_| approved_max(&env))
+    }
+
+    // Get the token votes cast on a proposal
+    pub fn get_token_tally(env: Env, id: u64) -> TokenTally {
+        read_tally(&env, id)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::{symbol_short, BytesN, String};
+
+    fn metadata(env: &Env) -> ProposalMetadata {
+        ProposalMetadata {
+            title: symbol_short!("limits"),
+            memo: String::from_str(env, "raise the limits"),
+            recipient: None,
+            content_hash: BytesN::from_array(env, &[1; 32]),
+        }
+    }
+
+    #[test]
+    fn test_large_increase_needs_token_vote() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+        let token = mocks::register_votes_token(&env);
+        let token_id = token.address.clone();
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let holder = Address::generate(&env);
+        let late = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_token_voting(&owner, &token_id, &1000, &500);
+
+        // Small increases still only need the owner, but add up
+        client.set_limits(&owner, &0, &10600);
+        client.set_limits(&owner, &0, &11000);
+        assert_eq!(client.try_set_limits(&owner, &0, &11001), Err(Ok(BudgetError::TokenVoteRequired)));
+        assert_eq!(client.try_set_limits(&owner, &0, &20000), Err(Ok(BudgetError::TokenVoteRequired)));
+        assert_eq!(client.get_approved_max(), Some(10000));
+
+        env.ledger().with_mut(|l| l.sequence_number = 10);
+        token.set_balance(&holder, &10, &600);
+        let id = client.propose_limits(&operator, &0, &20000, &metadata(&env));
+        assert_eq!(client.try_ratify_proposal(&owner, &id), Err(Ok(BudgetError::TokenVoteRequired)));
+
+        // Only balances at the proposal's creation ledger count
+        env.ledger().with_mut(|l| l.sequence_number = 11);
+        token.set_balance(&late, &11, &1000);
+        assert_eq!(client.try_token_vote(&late, &id, &false), Err(Ok(BudgetError::InvalidAmount)));
+
+        client.token_vote(&holder, &id, &true);
+        client.execute_token_vote(&id);
+        assert_eq!(client.get_budget().max, 20000);
+
+        // A passed vote moves the approved max
+        assert_eq!(client.get_approved_max(), Some(20000));
+        client.set_limits(&owner, &0, &21000);
+    }
+}
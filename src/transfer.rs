@@ -0,0 +1,135 @@
+// Transfers between allocators.
+// Moving budget from one team's allocator to another's used to take a decrease here and an
+// increase there, two transactions that could fail apart. transfer_to_allocator does both in one:
+// it decreases this budget with every check of a normal decrease and calls receive_transfer on
+// the other allocator, which applies the increase within its own limits or fails the whole call.
+// Each owner lists the allocators it exchanges budget with; transfers to or from any other
+// allocator are rejected. In token-backed mode the tokens move along, so both sides need the
+// same backing token.
+
+use soroban_sdk::{contractimpl, contracttype, token, Address, Env};
+
+use crate::{
+    apply_decrease, commit_adjustment, exempt_from_veto, increased_value, read_budget, require_not_paused,
+    require_owner, BudgetError, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient, Receipt,
+};
+#[cfg(feature = "treasury")]
+use crate::treasury;
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum TransferKey {
+    TransferPeer(Address),
+}
+// TransferPeer marks an allocator this one sends budget to and accepts budget from
+
+
+fn is_peer(env: &Env, allocator: &Address) -> bool {
+    env.storage().persistent().has(&TransferKey::TransferPeer(allocator.clone()))
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Allow budget transfers to and from another allocator (owner only)
+    pub fn allow_transfer_peer(env: Env, caller: Address, allocator: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().set(&TransferKey::TransferPeer(allocator), &true);
+        Ok(())
+    }
+
+    /// Stop budget transfers to and from another allocator (owner only)
+    pub fn disallow_transfer_peer(env: Env, caller: Address, allocator: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        if !is_peer(&env, &allocator) {
+            return Err(BudgetError::NotRegistered);
+        }
+        env.storage().persistent().remove(&TransferKey::TransferPeer(allocator));
+        Ok(())
+    }
+
+    /// Move budget to another allocator in one step, both sides checking their limits (operators only)
+    pub fn transfer_to_allocator(env: Env, caller: Address, other_contract: Address, amount: i128) -> Result<Receipt, BudgetError> {
+        caller.require_auth();
+        if !is_peer(&env, &other_contract) {
+            return Err(BudgetError::NotRegistered);
+        }
+        let receipt = apply_decrease(&env, &caller, amount)?;
+        exempt_from_veto(&env, &receipt);
+
+        let other = GovernanceBudgetAllocatorClient::new(&env, &other_contract);
+        #[cfg(feature = "treasury")]
+        if let Ok(backing) = treasury::backing_token(&env) {
+            if other.get_token() != Some(backing.clone()) {
+                return Err(BudgetError::TokenNotAllowed);
+            }
+            token::Client::new(&env, &backing).transfer(&env.current_contract_address(), &other_contract, &amount);
+        }
+        other.receive_transfer(&env.current_contract_address(), &amount);
+        Ok(receipt)
+    }
+
+    /// Accept budget moved here by transfer_to_allocator (peer allocator contracts only), returns the new current value
+    pub fn receive_transfer(env: Env, from: Address, amount: i128) -> Result<i128, BudgetError> {
+        from.require_auth();
+        if !is_peer(&env, &from) {
+            return Err(BudgetError::NotRegistered);
+        }
+        require_not_paused(&env)?;
+
+        let mut budget = read_budget(&env);
+        let new_value = increased_value(&env, &budget, amount).map_err(|violation| violation.error)?;
+        let receipt = commit_adjustment(&env, &from, &mut budget, new_value);
+        exempt_from_veto(&env, &receipt);
+        Ok(new_value)
+    }
+
+    // Check if budget may be transferred to and from an allocator
+    pub fn is_transfer_peer(env: Env, allocator: Address) -> bool {
+        is_peer(&env, &allocator)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+
+    #[test]
+    fn test_transfer_between_allocators() {
+        let env = Env::default();
+        let marketing_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let engineering_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let marketing = GovernanceBudgetAllocatorClient::new(&env, &marketing_id);
+        let engineering = GovernanceBudgetAllocatorClient::new(&env, &engineering_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        marketing.initialize(&owner, &1000, &0, &10000);
+        engineering.initialize(&owner, &1000, &0, &1500);
+
+        env.mock_all_auths();
+        marketing.add_operator(&owner, &operator);
+        marketing.accept_operator(&operator);
+
+        // Both sides have to list each other
+        assert_eq!(marketing.try_transfer_to_allocator(&operator, &engineering_id, &300), Err(Ok(BudgetError::NotRegistered)));
+        marketing.allow_transfer_peer(&owner, &engineering_id);
+        let result = marketing.try_transfer_to_allocator(&operator, &engineering_id, &300);
+        assert_eq!(result, Err(Ok(BudgetError::NotRegistered)));
+        engineering.allow_transfer_peer(&owner, &marketing_id);
+
+        marketing.transfer_to_allocator(&operator, &engineering_id, &300);
+        assert_eq!(marketing.get_budget().current, 700);
+        assert_eq!(engineering.get_budget().current, 1300);
+
+        // The receiving side's max fails the whole transfer
+        let result = marketing.try_transfer_to_allocator(&operator, &engineering_id, &300);
+        assert_eq!(result, Err(Ok(BudgetError::ExceedsMax)));
+        assert_eq!(marketing.get_budget().current, 700);
+        assert_eq!(engineering.get_budget().current, 1300);
+    }
+}
@@ -0,0 +1,495 @@
+// Token-backed mode.
+// Once the owner sets a backing token the budget is expected to be backed by that token held
+// by this contract. spend() then decreases the budget and pays the recipient in one call, so
+// accounting and funds can never drift apart between two separate transactions. Every payment
+// is kept in a payments log. spend_batch pays several recipients against a single budget
+// decrease, for payroll-style runs.
+// Funds come in through deposit(), which only accepts the backing token and the tokens on an
+// owner-managed allowlist, so spam tokens cannot inflate the holdings.
+// Native XLM is handled through its Stellar Asset Contract, whose address differs per network;
+// native_token() resolves it for the network the contract runs on. deposit() always accepts it and
+// use_native_token() makes it the backing token, after which spend() pays out plain XLM.
+// Besides the budget itself, the contract holds tokens on behalf of others (proposal deposits,
+// challenge bonds, escrowed payouts); these are tracked per token as held. Anything beyond the
+// backing budget and the held amounts belongs to the contract itself, whether deposited as a
+// reserve in another token or sent directly, and can be swept out by the owner.
+
+use soroban_sdk::{contractimpl, contracttype, token, Address, Bytes, Env, String, Vec};
+
+use crate::{
+    apply_decrease, events, exempt_from_veto, read_budget, require_owner, BudgetError, BudgetTreasury, DepositEvent,
+    GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient, SpendEvent,
+};
+#[cfg(feature = "fee")]
+use crate::fee;
+#[cfg(feature = "payee")]
+use crate::payee;
+#[cfg(feature = "policy")]
+use crate::policy;
+#[cfg(feature = "vault")]
+use crate::vault;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Payment {
+    pub id: u64,
+    pub caller: Address,
+    pub recipient: Address,
+    pub amount: i128,
+    pub memo: String,
+    pub ledger: u32,
+}
+// Payment is one entry of the payments log:
+// caller is the operator that made the payment
+// memo is free text supplied by the operator (invoice number, purpose...)
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum TreasuryKey {
+    Token,
+    PaymentCount,
+    Payment(u64),
+    AllowedTokens,
+    Held(Address),
+    HeldTokens,
+}
+// Token stores the backing token contract (missing means token-backed mode is off)
+// PaymentCount stores the number of payments (the next payment id)
+// Payment stores a Payment by id
+// AllowedTokens stores the tokens deposit() accepts besides the backing token
+// Held stores the amount of a token the contract holds for others outside the budget
+// HeldTokens stores the tokens with a non-zero Held amount
+
+
+// Upper bound on recipients per spend_batch call, keeps a batch within the instruction limit.
+const MAX_BATCH: u32 = 50;
+
+pub(crate) fn backing_token(env: &Env) -> Result<Address, BudgetError> {
+    env.storage().persistent().get(&TreasuryKey::Token).ok_or(BudgetError::TokenNotSet)
+}
+
+pub(crate) fn allowed_tokens(env: &Env) -> Vec<Address> {
+    env.storage().persistent().get(&TreasuryKey::AllowedTokens).unwrap_or(Vec::new(env))
+}
+
+pub(crate) fn held(env: &Env, token: &Address) -> i128 {
+    env.storage().persistent().get(&TreasuryKey::Held(token.clone())).unwrap_or(0)
+}
+
+pub(crate) fn held_tokens(env: &Env) -> Vec<Address> {
+    env.storage().persistent().get(&TreasuryKey::HeldTokens).unwrap_or(Vec::new(env))
+}
+
+fn set_held(env: &Env, token: &Address, amount: i128) {
+    env.storage().persistent().set(&TreasuryKey::Held(token.clone()), &amount);
+    let mut tokens = held_tokens(env);
+    match (tokens.first_index_of(token), amount != 0) {
+        (None, true) => tokens.push_back(token.clone()),
+        (Some(index), false) => {
+            tokens.remove(index);
+        }
+        _ => return,
+    }
+    env.storage().persistent().set(&TreasuryKey::HeldTokens, &tokens);
+}
+
+// Records tokens taken in for others outside the budget (proposal deposits, bonds, escrow).
+pub(crate) fn hold(env: &Env, token: &Address, amount: i128) {
+    set_held(env, token, held(env, token) + amount);
+}
+
+// Records held tokens that were paid out or forfeited.
+pub(crate) fn release(env: &Env, token: &Address, amount: i128) {
+    set_held(env, token, held(env, token) - amount);
+}
+
+// The part of the contract's balance of a token that the accounting accounts for.
+pub(crate) fn accounted(env: &Env, token: &Address) -> i128 {
+    let mut accounted = held(env, token);
+    if backing_token(env).ok().as_ref() == Some(token) {
+        // Principal invested in yield vaults backs the budget from outside the contract
+        accounted += read_budget(env).current.max(0);
+        #[cfg(feature = "vault")]
+        {
+            accounted -= vault::invested(env);
+        }
+    }
+    accounted
+}
+
+// The part of a payment left to the recipient after the fee.
+pub(crate) fn net_of_fee(env: &Env, amount: i128, token: Option<&Address>) -> i128 {
+    #[cfg(feature = "fee")]
+    { amount - fee::settle(env, amount, token) }
+    #[cfg(not(feature = "fee"))]
+    { amount }
+}
+
+// The address of the native XLM Stellar Asset Contract on the current network.
+pub(crate) fn native_token(env: &Env) -> Address {
+    // Asset XDR of the native asset: the ASSET_TYPE_NATIVE discriminant alone
+    env.deployer().with_stellar_asset(Bytes::from_array(env, &[0; 4])).deployed_address()
+}
+
+// Checks that deposit() accepts a token.
+pub(crate) fn check_allowed(env: &Env, token: &Address) -> Result<(), BudgetError> {
+    let backing = backing_token(env).ok();
+    if backing.as_ref() == Some(token) || *token == native_token(env) || allowed_tokens(env).contains(token) {
+        return Ok(());
+    }
+    Err(BudgetError::TokenNotAllowed)
+}
+
+// Appends a payment to the log and emits a "spend" event.
+pub(crate) fn log_payment(env: &Env, caller: &Address, recipient: &Address, amount: i128, memo: String) -> u64 {
+    let id: u64 = env.storage().persistent().get(&TreasuryKey::PaymentCount).unwrap_or(0);
+    let payment = Payment {
+        id,
+        caller: caller.clone(),
+        recipient: recipient.clone(),
+        amount,
+        memo,
+        ledger: env.ledger().sequence(),
+    };
+    env.storage().persistent().set(&TreasuryKey::Payment(id), &payment);
+    env.storage().persistent().set(&TreasuryKey::PaymentCount, &(id + 1));
+    events::publish(env, events::SPEND, recipient, SpendEvent { payment_id: id, amount });
+    id
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Set the token that backs the budget (owner only)
+    pub fn set_token(env: Env, caller: Address, token: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().set(&TreasuryKey::Token, &token);
+        Ok(())
+    }
+
+    /// Back the budget with native XLM (owner only), returns the native token address
+    pub fn use_native_token(env: Env, caller: Address) -> Result<Address, BudgetError> {
+        require_owner(&env, &caller)?;
+        let token = native_token(&env);
+        env.storage().persistent().set(&TreasuryKey::Token, &token);
+        Ok(token)
+    }
+
+    /// Add a token to the tokens deposit() accepts (owner only)
+    pub fn allow_token(env: Env, caller: Address, token: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        let mut tokens = allowed_tokens(&env);
+        if tokens.contains(&token) {
+            return Err(BudgetError::AlreadyRegistered);
+        }
+        tokens.push_back(token);
+        env.storage().persistent().set(&TreasuryKey::AllowedTokens, &tokens);
+        Ok(())
+    }
+
+    /// Remove a token from the tokens deposit() accepts (owner only)
+    pub fn disallow_token(env: Env, caller: Address, token: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        let mut tokens = allowed_tokens(&env);
+        let index = tokens.first_index_of(&token).ok_or(BudgetError::NotRegistered)?;
+        tokens.remove(index);
+        env.storage().persistent().set(&TreasuryKey::AllowedTokens, &tokens);
+        Ok(())
+    }
+
+    /// Transfer an accepted token into the contract (anyone)
+    pub fn deposit(env: Env, from: Address, token: Address, amount: i128) -> Result<(), BudgetError> {
+        from.require_auth();
+        if amount <= 0 {
+            return Err(BudgetError::InvalidAmount);
+        }
+        check_allowed(&env, &token)?;
+        token::Client::new(&env, &token).transfer(&from, &env.current_contract_address(), &amount);
+        // Deposits of the backing token back the budget, other tokens are reserves the owner can sweep
+        events::publish(&env, events::DEPOSIT, &from, DepositEvent { token, amount });
+        Ok(())
+    }
+
+    /// Pay several recipients with one aggregated budget decrease, all or nothing (operators only)
+    pub fn spend_batch(env: Env, caller: Address, payments: Vec<(Address, i128)>) -> Result<Vec<u64>, BudgetError> {
+        caller.require_auth();
+        if payments.is_empty() || payments.len() > MAX_BATCH {
+            return Err(BudgetError::InvalidConfig);
+        }
+        let token = backing_token(&env)?;
+
+        let mut total: i128 = 0;
+        for (recipient, amount) in payments.iter() {
+            if amount <= 0 {
+                return Err(BudgetError::InvalidAmount);
+            }
+            #[cfg(feature = "payee")]
+            payee::charge(&env, &recipient, amount)?;
+            #[cfg(feature = "policy")]
+            policy::check_recipient(&env, &recipient, amount)?;
+            total = total.checked_add(amount).ok_or(BudgetError::Overflow)?;
+        }
+        let receipt = apply_decrease(&env, &caller, total)?;
+        exempt_from_veto(&env, &receipt);
+
+        let client = token::Client::new(&env, &token);
+        let memo = String::from_str(&env, "batch");
+        let mut ids = Vec::new(&env);
+        // Each payment settles the fee on its own amount, rounding leftovers of the batch stay accrued
+        for (recipient, amount) in payments.iter() {
+            let net = net_of_fee(&env, amount, Some(&token));
+            client.transfer(&env.current_contract_address(), &recipient, &net);
+            ids.push_back(log_payment(&env, &caller, &recipient, net, memo.clone()));
+        }
+        Ok(ids)
+    }
+
+    /// Transfer the balance of a token that is not accounted for to a recipient (owner only), returns the amount swept
+    pub fn sweep(env: Env, caller: Address, token: Address, recipient: Address) -> Result<i128, BudgetError> {
+        require_owner(&env, &caller)?;
+        let client = token::Client::new(&env, &token);
+        let stray = client.balance(&env.current_contract_address()) - accounted(&env, &token);
+        if stray <= 0 {
+            return Err(BudgetError::InvalidAmount);
+        }
+        client.transfer(&env.current_contract_address(), &recipient, &stray);
+        Ok(stray)
+    }
+
+    // Get the address of the native XLM token on this network
+    pub fn get_native_token(env: Env) -> Address {
+        native_token(&env)
+    }
+
+    // Get the tokens deposit() accepts besides the backing token
+    pub fn get_allowed_tokens(env: Env) -> Vec<Address> {
+        allowed_tokens(&env)
+    }
+
+    // Get the amount of a token held outside the budget (deposits, bonds, escrow)
+    pub fn get_held(env: Env, token: Address) -> i128 {
+        held(&env, &token)
+    }
+
+    // Get the number of payments made
+    pub fn get_payment_count(env: Env) -> u64 {
+        env.storage().persistent().get(&TreasuryKey::PaymentCount).unwrap_or(0)
+    }
+
+    // Get a payment by id
+    pub fn get_payment(env: Env, id: u64) -> Option<Payment> {
+        env.storage().persistent().get(&TreasuryKey::Payment(id))
+    }
+}
+
+
+#[contractimpl]
+impl BudgetTreasury for GovernanceBudgetAllocator {
+    fn spend(env: Env, caller: Address, recipient: Address, amount: i128, memo: String) -> Result<u64, BudgetError> {
+        caller.require_auth();
+        if amount <= 0 {
+            return Err(BudgetError::InvalidAmount);
+        }
+        let token = backing_token(&env)?;
+        #[cfg(feature = "payee")]
+        payee::charge(&env, &recipient, amount)?;
+        #[cfg(feature = "policy")]
+        policy::check_recipient(&env, &recipient, amount)?;
+
+        // All operator, pause and limit checks of a normal decrease apply
+        let receipt = apply_decrease(&env, &caller, amount)?;
+        exempt_from_veto(&env, &receipt);
+
+        // Fails (and rolls back the decrease) if the contract does not hold enough
+        let net = net_of_fee(&env, amount, Some(&token));
+        token::Client::new(&env, &token).transfer(&env.current_contract_address(), &recipient, &net);
+
+        Ok(log_payment(&env, &caller, &recipient, net, memo))
+    }
+
+    fn get_token(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&TreasuryKey::Token)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{token, vec, Bytes, String};
+
+    #[test]
+    fn test_spend_transfers_and_logs() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let recipient = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+
+        let sac = env.register_stellar_asset_contract_v2(owner.clone());
+        token::StellarAssetClient::new(&env, &sac.address()).mint(&contract_id, &1000);
+        client.set_token(&owner, &sac.address());
+
+        let memo = String::from_str(&env, "invoice 42");
+        assert_eq!(client.spend(&operator, &recipient, &300, &memo), 0);
+
+        assert_eq!(client.get_budget().current, 700);
+        assert_eq!(token::Client::new(&env, &sac.address()).balance(&recipient), 300);
+        let payment = client.get_payment(&0).unwrap();
+        assert_eq!(payment.recipient, recipient);
+        assert_eq!(payment.memo, memo);
+    }
+
+    #[test]
+    fn test_spend_is_atomic() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let recipient = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+
+        let memo = String::from_str(&env, "");
+        assert_eq!(client.try_spend(&operator, &recipient, &300, &memo), Err(Ok(BudgetError::TokenNotSet)));
+
+        // Not enough tokens held: the transfer fails and the decrease is rolled back
+        let sac = env.register_stellar_asset_contract_v2(owner.clone());
+        token::StellarAssetClient::new(&env, &sac.address()).mint(&contract_id, &100);
+        client.set_token(&owner, &sac.address());
+        assert!(client.try_spend(&operator, &recipient, &300, &memo).is_err());
+        assert_eq!(client.get_budget().current, 1000);
+        assert_eq!(client.get_payment_count(), 0);
+    }
+
+    #[test]
+    fn test_spend_batch() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let alice = Address::generate(&env);
+        let bob = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+
+        let sac = env.register_stellar_asset_contract_v2(owner.clone());
+        token::StellarAssetClient::new(&env, &sac.address()).mint(&contract_id, &1000);
+        client.set_token(&owner, &sac.address());
+
+        // Together the payments exceed the budget, so nothing is paid
+        let too_much = vec![&env, (alice.clone(), 600), (bob.clone(), 600)];
+        assert_eq!(client.try_spend_batch(&operator, &too_much), Err(Ok(BudgetError::BelowMin)));
+        assert_eq!(client.get_payment_count(), 0);
+
+        let payroll = vec![&env, (alice.clone(), 300), (bob.clone(), 200)];
+        assert_eq!(client.spend_batch(&operator, &payroll), vec![&env, 0, 1]);
+        assert_eq!(client.get_budget().current, 500);
+        assert_eq!(client.get_operator_stats(&operator).op_count, 1);
+        assert_eq!(token::Client::new(&env, &sac.address()).balance(&bob), 200);
+    }
+
+    #[test]
+    fn test_deposit_allowlist() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let donor = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        let backing = env.register_stellar_asset_contract_v2(owner.clone()).address();
+        let stable = env.register_stellar_asset_contract_v2(owner.clone()).address();
+        let spam = env.register_stellar_asset_contract_v2(owner.clone()).address();
+        for token in [&backing, &stable, &spam] {
+            token::StellarAssetClient::new(&env, token).mint(&donor, &100);
+        }
+        client.set_token(&owner, &backing);
+        client.allow_token(&owner, &stable);
+
+        client.deposit(&donor, &backing, &100);
+        client.deposit(&donor, &stable, &100);
+        assert_eq!(client.try_deposit(&donor, &spam, &100), Err(Ok(BudgetError::TokenNotAllowed)));
+        assert_eq!(token::Client::new(&env, &stable).balance(&contract_id), 100);
+
+        // Reserves are not held for anyone, the owner can move them out
+        assert_eq!(client.get_held(&stable), 0);
+        assert_eq!(client.sweep(&owner, &stable, &owner), 100);
+
+        client.disallow_token(&owner, &stable);
+        assert_eq!(client.get_allowed_tokens().len(), 0);
+    }
+
+    #[test]
+    fn test_sweep_stray_tokens() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let recipient = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        let sac = env.register_stellar_asset_contract_v2(owner.clone());
+        let backing = token::Client::new(&env, &sac.address());
+        token::StellarAssetClient::new(&env, &sac.address()).mint(&contract_id, &1500);
+        client.set_token(&owner, &sac.address());
+
+        // 300 of the backing goes into escrow and stays accounted for until claimed
+        client.create_payout(&operator, &recipient, &300, &100);
+        assert_eq!(client.sweep(&owner, &sac.address(), &owner), 500);
+        assert_eq!(backing.balance(&owner), 500);
+        assert_eq!(backing.balance(&contract_id), 1000);
+        assert_eq!(client.try_sweep(&owner, &sac.address(), &owner), Err(Ok(BudgetError::InvalidAmount)));
+    }
+
+    #[test]
+    fn test_native_token() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        // The test network has no native token contract until it is deployed
+        let native = env.deployer().with_stellar_asset(Bytes::from_array(&env, &[0; 4])).deploy();
+        assert_eq!(client.get_native_token(), native);
+        assert_eq!(client.use_native_token(&owner), native);
+        assert_eq!(client.get_token(), Some(native.clone()));
+        assert_eq!(token::Client::new(&env, &native).symbol(), String::from_str(&env, "native"));
+    }
+}
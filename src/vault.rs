@@ -0,0 +1,214 @@
+// Yield on idle backing tokens.
+// The owner whitelists yield vaults (contracts implementing YieldVault) and can invest part of
+// the backing tokens in them. The invested principal is tracked per vault and still counts as
+// backing, so the solvency check does not flag it. Interest is whatever a vault holds for this
+// contract beyond the principal; harvest() withdraws it and raises the budget by the same amount,
+// but never beyond max. Interest that does not fit under max stays in the vault.
+
+use soroban_sdk::{
+    auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
+    contractclient, contractimpl, contracttype, vec, Address, Env, IntoVal, Symbol, Vec,
+};
+
+use crate::{
+    commit_adjustment, exempt_from_veto, read_budget, require_owner, treasury, BudgetError, GovernanceBudgetAllocator,
+    GovernanceBudgetAllocatorClient,
+};
+
+
+// Interface yield vaults implement. deposit pulls tokens from `from` with a token transfer.
+#[contractclient(name = "YieldVaultClient")]
+pub trait YieldVault {
+    fn deposit(env: Env, from: Address, amount: i128);
+    fn withdraw(env: Env, to: Address, amount: i128);
+    fn balance(env: Env, owner: Address) -> i128;
+}
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum VaultKey {
+    Vaults,
+    Invested(Address),
+}
+// Vaults stores the whitelisted yield vaults
+// Invested stores the principal invested in a vault
+
+
+fn read_vaults(env: &Env) -> Vec<Address> {
+    env.storage().persistent().get(&VaultKey::Vaults).unwrap_or(Vec::new(env))
+}
+
+fn principal(env: &Env, vault: &Address) -> i128 {
+    env.storage().persistent().get(&VaultKey::Invested(vault.clone())).unwrap_or(0)
+}
+
+// The backing tokens currently invested across all vaults.
+pub(crate) fn invested(env: &Env) -> i128 {
+    read_vaults(env).iter().map(|vault| principal(env, &vault)).sum()
+}
+
+fn check_vault(env: &Env, vault: &Address) -> Result<(), BudgetError> {
+    if !read_vaults(env).contains(vault) {
+        return Err(BudgetError::NotRegistered);
+    }
+    Ok(())
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Whitelist a yield vault (owner only)
+    pub fn allow_vault(env: Env, caller: Address, vault: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        let mut vaults = read_vaults(&env);
+        if vaults.contains(&vault) {
+            return Err(BudgetError::AlreadyRegistered);
+        }
+        vaults.push_back(vault);
+        env.storage().persistent().set(&VaultKey::Vaults, &vaults);
+        Ok(())
+    }
+
+    /// Invest backing tokens held by the contract in a whitelisted vault (owner only)
+    pub fn invest(env: Env, caller: Address, vault: Address, amount: i128) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        check_vault(&env, &vault)?;
+        if amount <= 0 {
+            return Err(BudgetError::InvalidAmount);
+        }
+        let token = treasury::backing_token(&env)?;
+        let this = env.current_contract_address();
+
+        // The vault pulls the tokens, so authorize that transfer on behalf of this contract
+        env.authorize_as_current_contract(vec![
+            &env,
+            InvokerContractAuthEntry::Contract(SubContractInvocation {
+                context: ContractContext {
+                    contract: token,
+                    fn_name: Symbol::new(&env, "transfer"),
+                    args: (this.clone(), vault.clone(), amount).into_val(&env),
+                },
+                sub_invocations: Vec::new(&env),
+            }),
+        ]);
+        YieldVaultClient::new(&env, &vault).deposit(&this, &amount);
+
+        let key = VaultKey::Invested(vault.clone());
+        env.storage().persistent().set(&key, &(principal(&env, &vault) + amount));
+        Ok(())
+    }
+
+    /// Withdraw principal from a vault back into the contract (owner only)
+    pub fn divest(env: Env, caller: Address, vault: Address, amount: i128) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        let invested = principal(&env, &vault);
+        if amount <= 0 || amount > invested {
+            return Err(BudgetError::InvalidAmount);
+        }
+        YieldVaultClient::new(&env, &vault).withdraw(&env.current_contract_address(), &amount);
+        env.storage().persistent().set(&VaultKey::Invested(vault), &(invested - amount));
+        Ok(())
+    }
+
+    /// Withdraw the interest a vault accrued and add it to the budget within max (anyone), returns the amount added
+    pub fn harvest(env: Env, vault: Address) -> Result<i128, BudgetError> {
+        check_vault(&env, &vault)?;
+        let this = env.current_contract_address();
+        let client = YieldVaultClient::new(&env, &vault);
+
+        let mut budget = read_budget(&env);
+        let interest = (client.balance(&this) - principal(&env, &vault)).min(budget.max - budget.current);
+        if interest <= 0 {
+            return Ok(0);
+        }
+        client.withdraw(&this, &interest);
+        let new_value = budget.current + interest;
+        let receipt = commit_adjustment(&env, &vault, &mut budget, new_value);
+        exempt_from_veto(&env, &receipt);
+        Ok(interest)
+    }
+
+    // Get the whitelisted yield vaults
+    pub fn get_vaults(env: Env) -> Vec<Address> {
+        read_vaults(&env)
+    }
+
+    // Get the principal invested in a vault
+    pub fn get_invested(env: Env, vault: Address) -> i128 {
+        principal(&env, &vault)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{contract, contractimpl, symbol_short, token};
+
+    // Vault that credits deposits one to one; interest is simulated by minting to it
+    #[contract]
+    pub struct Vault;
+
+    #[contractimpl]
+    impl Vault {
+        pub fn init(env: Env, token: Address) {
+            env.storage().instance().set(&symbol_short!("token"), &token);
+        }
+
+        pub fn deposit(env: Env, from: Address, amount: i128) {
+            from.require_auth();
+            let token: Address = env.storage().instance().get(&symbol_short!("token")).unwrap();
+            token::Client::new(&env, &token).transfer(&from, &env.current_contract_address(), &amount);
+        }
+
+        pub fn withdraw(env: Env, to: Address, amount: i128) {
+            to.require_auth();
+            let token: Address = env.storage().instance().get(&symbol_short!("token")).unwrap();
+            token::Client::new(&env, &token).transfer(&env.current_contract_address(), &to, &amount);
+        }
+
+        // Single depositor, so everything the vault holds belongs to it
+        pub fn balance(env: Env, _owner: Address) -> i128 {
+            let token: Address = env.storage().instance().get(&symbol_short!("token")).unwrap();
+            token::Client::new(&env, &token).balance(&env.current_contract_address())
+        }
+    }
+
+    #[test]
+    fn test_invest_and_harvest() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+        let vault_id = env.register_contract(None, Vault);
+
+        let owner = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &1050);
+
+        env.mock_all_auths();
+        let sac = env.register_stellar_asset_contract_v2(owner.clone());
+        let minter = token::StellarAssetClient::new(&env, &sac.address());
+        minter.mint(&contract_id, &1000);
+        client.set_token(&owner, &sac.address());
+        VaultClient::new(&env, &vault_id).init(&sac.address());
+
+        assert_eq!(client.try_invest(&owner, &vault_id, &600), Err(Ok(BudgetError::NotRegistered)));
+        client.allow_vault(&owner, &vault_id);
+        client.invest(&owner, &vault_id, &600);
+        assert_eq!(client.get_invested(&vault_id), 600);
+
+        // Invested principal still backs the budget
+        assert_eq!(client.verify_backing().get(0).unwrap().shortfall, 0);
+
+        // 80 interest accrues but only 50 fits under max
+        minter.mint(&vault_id, &80);
+        assert_eq!(client.harvest(&vault_id), 50);
+        assert_eq!(client.get_budget().current, 1050);
+        assert_eq!(client.harvest(&vault_id), 0);
+
+        client.divest(&owner, &vault_id, &600);
+        assert_eq!(token::Client::new(&env, &sac.address()).balance(&contract_id), 1050);
+    }
+}
@@ -0,0 +1,124 @@
+// Vesting of the budget maximum.
+// With a vesting schedule only the unlocked part of max can be reached by increases: nothing
+// before the cliff ledger, then a share growing linearly from start to end, and all of max from
+// end on. Setting cliff equal to end gives a pure cliff. Decreases are not affected, and a
+// budget already above the unlocked amount is not cut back.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env};
+
+use crate::{
+    require_owner, BudgetAllocator, BudgetError, BudgetState, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient,
+};
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Vesting {
+    pub start: u32,
+    pub cliff: u32,
+    pub end: u32,
+}
+// Vesting defines when max unlocks:
+// start is the ledger the linear unlock is measured from
+// cliff is the first ledger anything is unlocked
+// end is the ledger all of max is unlocked
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum VestingKey {
+    Vesting,
+}
+// Vesting stores the Vesting schedule (missing means max is fully available)
+
+
+// The highest value increases may take current to at the current ledger.
+pub(crate) fn cap(env: &Env, budget: &BudgetState) -> i128 {
+    cap_at(env, budget, env.ledger().sequence())
+}
+
+// Same as cap at any ledger.
+pub(crate) fn cap_at(env: &Env, budget: &BudgetState, now: u32) -> i128 {
+    let vesting: Vesting = match env.storage().persistent().get(&VestingKey::Vesting) {
+        Some(vesting) => vesting,
+        None => return budget.max,
+    };
+    if now >= vesting.end {
+        return budget.max;
+    }
+    if now < vesting.cliff {
+        return 0;
+    }
+    // max * elapsed / duration without overflowing for large max values
+    let elapsed = (now - vesting.start) as i128;
+    let duration = (vesting.end - vesting.start) as i128;
+    (budget.max / duration) * elapsed + (budget.max % duration) * elapsed / duration
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Unlock max gradually between start and end with a cliff (owner only)
+    pub fn set_vesting(env: Env, caller: Address, start: u32, cliff: u32, end: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        if start > cliff || cliff > end || start == end {
+            return Err(BudgetError::InvalidConfig);
+        }
+        let vesting = Vesting { start, cliff, end };
+        env.storage().persistent().set(&VestingKey::Vesting, &vesting);
+        Ok(())
+    }
+
+    /// Make all of max available again (owner only)
+    pub fn clear_vesting(env: Env, caller: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().remove(&VestingKey::Vesting);
+        Ok(())
+    }
+
+    // Get the vesting schedule, if any
+    pub fn get_vesting(env: Env) -> Option<Vesting> {
+        env.storage().persistent().get(&VestingKey::Vesting)
+    }
+
+    // Get the part of max unlocked at the current ledger
+    pub fn get_vested_max(env: Env) -> i128 {
+        cap(&env, &Self::get_budget(env.clone()))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    #[test]
+    fn test_linear_vesting_with_cliff() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &0, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_vesting(&owner, &0, &100, &1000);
+
+        assert_eq!(client.try_increase_budget(&operator, &1), Err(Ok(BudgetError::ExceedsMax)));
+
+        env.ledger().with_mut(|l| l.sequence_number = 250);
+        assert_eq!(client.get_vested_max(), 2500);
+        assert_eq!(client.get_headroom(), 2500);
+        client.increase_budget(&operator, &2500);
+        assert_eq!(client.try_increase_budget(&operator, &1), Err(Ok(BudgetError::ExceedsMax)));
+
+        env.ledger().with_mut(|l| l.sequence_number = 1000);
+        assert_eq!(client.get_vested_max(), 10000);
+        client.increase_budget(&operator, &7500);
+    }
+}
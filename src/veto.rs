@@ -0,0 +1,300 @@
+// Owner veto window on operator adjustments.
+// When the owner sets a veto window of K ledgers, every increase/decrease is still applied
+// immediately but is also recorded as provisional. Until K ledgers have passed the owner can
+// call veto(adjustment_id) to revert that change. A window of 0 turns the mode off.
+// Adjustments that move tokens (payments, escrow, transfers between allocators, donations and the
+// like) are not recorded: reverting one would restore current while the tokens are already gone,
+// or leave tokens that came in outside the accounting. Each new record removes the records whose
+// window has passed, a few at a time from the oldest.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env};
+
+use crate::{
+    cap, floor, read_budget, require_owner, write_budget, BudgetError, GovernanceBudgetAllocator,
+    GovernanceBudgetAllocatorClient,
+};
+#[cfg(feature = "federation")]
+use crate::federation;
+#[cfg(feature = "hooks")]
+use crate::hooks;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ProvisionalAdjustment {
+    pub id: u64,
+    pub caller: Address,
+    pub previous: i128,
+    pub new: i128,
+    pub ledger: u32,
+}
+// ProvisionalAdjustment remembers what an adjustment changed so it can be reverted:
+// previous and new are the current values before and after the adjustment
+// ledger is the ledger sequence the adjustment was applied in
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum VetoKey {
+    VetoWindow,
+    Provisional(u64),
+    OldestProvisional,
+}
+// VetoWindow stores the window length in ledgers (missing or 0 means disabled)
+// Provisional stores a ProvisionalAdjustment by adjustment id
+// OldestProvisional stores the lowest adjustment id that may still have a Provisional entry
+
+
+// How many old adjustment ids one record looks at for entries to remove
+const MAX_PRUNED: u64 = 5;
+
+
+pub(crate) fn window(env: &Env) -> u32 {
+    env.storage().persistent().get(&VetoKey::VetoWindow).unwrap_or(0)
+}
+
+// Whether the owner can still veto an adjustment.
+fn is_open(env: &Env, adjustment: &ProvisionalAdjustment) -> bool {
+    // The window is measured from the ledger the adjustment was applied in
+    env.ledger().sequence() <= adjustment.ledger.saturating_add(window(env))
+}
+
+// Removes the entries of up to MAX_PRUNED adjustments before id, oldest first, stopping at one still open.
+fn prune(env: &Env, id: u64) {
+    let storage = env.storage().persistent();
+    let mut oldest: u64 = storage.get(&VetoKey::OldestProvisional).unwrap_or(id);
+    let end = oldest.saturating_add(MAX_PRUNED).min(id);
+    while oldest < end {
+        if let Some(adjustment) = storage.get::<_, ProvisionalAdjustment>(&VetoKey::Provisional(oldest)) {
+            if is_open(env, &adjustment) {
+                break;
+            }
+            storage.remove(&VetoKey::Provisional(oldest));
+        }
+        oldest += 1;
+    }
+    storage.set(&VetoKey::OldestProvisional, &oldest);
+}
+
+// Called for every committed adjustment; only records it when the veto mode is on.
+pub(crate) fn record(env: &Env, id: u64, caller: &Address, previous: i128, new: i128) {
+    if window(env) == 0 {
+        return;
+    }
+    prune(env, id);
+    let adjustment = ProvisionalAdjustment {
+        id,
+        caller: caller.clone(),
+        previous,
+        new,
+        ledger: env.ledger().sequence(),
+    };
+    env.storage().persistent().set(&VetoKey::Provisional(id), &adjustment);
+}
+
+// Called after an adjustment that moved tokens, so it cannot be vetoed.
+pub(crate) fn exempt(env: &Env, id: u64) {
+    env.storage().persistent().remove(&VetoKey::Provisional(id));
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Set the veto window in ledgers (owner only, 0 disables provisional adjustments)
+    pub fn set_veto_window(env: Env, caller: Address, ledgers: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().set(&VetoKey::VetoWindow, &ledgers);
+        Ok(())
+    }
+
+    /// Revert a provisional adjustment that is still inside the veto window (owner only)
+    pub fn veto(env: Env, caller: Address, adjustment_id: u64) -> Result<i128, BudgetError> {
+        require_owner(&env, &caller)?;
+
+        let key = VetoKey::Provisional(adjustment_id);
+        let adjustment: ProvisionalAdjustment = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(BudgetError::AdjustmentNotFound)?;
+
+        if !is_open(&env, &adjustment) {
+            return Err(BudgetError::VetoWindowClosed);
+        }
+
+        // Undo only the delta of this adjustment so later adjustments are kept
+        let mut budget = read_budget(&env);
+        let reverted = budget
+            .current
+            .checked_sub(adjustment.new)
+            .and_then(|v| v.checked_add(adjustment.previous))
+            .ok_or(BudgetError::Overflow)?;
+
+        // The revert still has to respect the limits
+        if reverted > cap(&env, &budget) {
+            return Err(BudgetError::ExceedsMax);
+        }
+        if reverted < floor(&env, &budget) {
+            return Err(BudgetError::BelowMin);
+        }
+
+        budget.current = reverted;
+        write_budget(&env, &budget);
+        env.storage().persistent().remove(&key);
+        #[cfg(feature = "hooks")]
+        hooks::notify(&env, adjustment.new, reverted, &caller);
+        #[cfg(feature = "federation")]
+        federation::report(&env, reverted);
+
+        Ok(reverted)
+    }
+
+    // Get the veto window in ledgers (0 means disabled)
+    pub fn get_veto_window(env: Env) -> u32 {
+        window(&env)
+    }
+
+    // Get a provisional adjustment that has not been vetoed
+    pub fn get_provisional(env: Env, adjustment_id: u64) -> Option<ProvisionalAdjustment> {
+        env.storage().persistent().get(&VetoKey::Provisional(adjustment_id))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::{vec, String};
+
+    #[test]
+    fn test_veto_reverts_adjustment() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_veto_window(&owner, &10);
+
+        client.increase_budget(&operator, &500);
+        client.decrease_budget(&operator, &200);
+        assert_eq!(client.get_budget().current, 1300);
+
+        // Vetoing the increase keeps the later decrease
+        assert_eq!(client.veto(&owner, &0), 800);
+        assert_eq!(client.get_provisional(&0), None);
+        assert!(client.get_provisional(&1).is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #11)")]
+    fn test_veto_after_window() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_veto_window(&owner, &10);
+        client.increase_budget(&operator, &500);
+
+        env.ledger().with_mut(|l| l.sequence_number += 11);
+        client.veto(&owner, &0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #10)")]
+    fn test_veto_disabled_by_default() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.increase_budget(&operator, &500);
+        client.veto(&owner, &0);
+    }
+
+    #[test]
+    fn test_payments_cannot_be_vetoed() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let recipient = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_veto_window(&owner, &10);
+        let backing = mocks::register_token(&env, &owner);
+        backing.mint(&contract_id, &1000);
+        client.set_token(&owner, &backing.address);
+
+        // The tokens are gone, restoring current would leave it unbacked
+        client.spend(&operator, &recipient, &300, &String::from_str(&env, "rent"));
+        client.spend_batch(&operator, &vec![&env, (recipient.clone(), 100)]);
+        assert_eq!(client.get_provisional(&0), None);
+        assert_eq!(client.try_veto(&owner, &0), Err(Ok(BudgetError::AdjustmentNotFound)));
+        assert_eq!(client.try_veto(&owner, &1), Err(Ok(BudgetError::AdjustmentNotFound)));
+
+        // Adjustments that only change the accounting are still recorded
+        client.decrease_budget(&operator, &100);
+        assert_eq!(client.veto(&owner, &2), 600);
+    }
+
+    #[test]
+    fn test_expired_records_are_removed() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_veto_window(&owner, &10);
+        client.increase_budget(&operator, &100);
+        client.increase_budget(&operator, &100);
+
+        // A record still inside its window stays
+        env.ledger().with_mut(|l| l.sequence_number += 5);
+        client.increase_budget(&operator, &100);
+        assert!(client.get_provisional(&0).is_some());
+
+        // Once the first two have expired the next record removes them, not the third
+        env.ledger().with_mut(|l| l.sequence_number += 6);
+        client.increase_budget(&operator, &100);
+        assert_eq!(client.get_provisional(&0), None);
+        assert_eq!(client.get_provisional(&1), None);
+        assert!(client.get_provisional(&2).is_some());
+        assert!(client.get_provisional(&3).is_some());
+    }
+}
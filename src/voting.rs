@@ -0,0 +1,259 @@
+// Weighted operator voting on proposals.
+// As an alternative to owner ratification, operators can vote on proposals. Each operator votes
+// with a weight set by the owner (1 unless configured), and once the weight in favour reaches the
+// quorum and outweighs the weight against, anyone can execute the proposal. A quorum of 0 (the
+// default) turns voting off and leaves ratification to the owner alone. Limits that would leave
+// current outside them still need the owner to ratify, as a vote cannot clamp the budget.
+// In commit-reveal mode (see sealed.rs) votes are cast through commit_vote/reveal_vote instead.
+// Weights delegated to an operator (see delegation.rs) are added to its own.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env};
+
+use crate::{
+    delegation, proposal, read_operators, require_operator, require_owner, rotation, BudgetError,
+    GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient, Proposal,
+};
+#[cfg(feature = "sealed")]
+use crate::sealed;
+#[cfg(feature = "token_vote")]
+use crate::token_vote;
+
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[contracttype]
+pub struct Tally {
+    pub for_weight: u32,
+    pub against_weight: u32,
+}
+// Tally stores the summed voting weight for and against a proposal.
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum VotingKey {
+    VotingWeight(Address),
+    Quorum,
+    Tally(u64),
+    Voted(u64, Address),
+}
+// VotingWeight stores the weight of an operator (missing means 1)
+// Quorum stores the weight in favour needed to pass a proposal (missing or 0 means voting is off)
+// Tally stores the Tally of a proposal
+// Voted marks that an address voted on a proposal
+
+
+pub(crate) fn voting_weight(env: &Env, address: &Address) -> u32 {
+    env.storage().persistent().get(&VotingKey::VotingWeight(address.clone())).unwrap_or(1)
+}
+
+pub(crate) fn quorum(env: &Env) -> u32 {
+    env.storage().persistent().get(&VotingKey::Quorum).unwrap_or(0)
+}
+
+pub(crate) fn read_tally(env: &Env, id: u64) -> Tally {
+    env.storage().persistent().get(&VotingKey::Tally(id)).unwrap_or_default()
+}
+
+// The weight an operator votes with on a proposal, delegations included, as of the ledger it was
+// created in. Only current operators contribute weight, whether voting themselves or through a delegate.
+pub(crate) fn cast_operator_weight(env: &Env, voter: &Address, proposal: &Proposal) -> u32 {
+    let operators = read_operators(env);
+    let used = delegation::DelegationKey::OperatorWeightUsed;
+    let weight = delegation::cast_weight(env, used, proposal.id, voter, proposal.created_ledger, |address| {
+        if operators.contains(address) {
+            voting_weight(env, address) as i128
+        } else {
+            0
+        }
+    });
+    weight.min(u32::MAX as i128) as u32
+}
+
+// Adds a vote with the given weight, rejecting a second vote from the same address.
+pub(crate) fn record_vote(env: &Env, id: u64, voter: &Address, approve: bool, weight: u32) -> Result<Tally, BudgetError> {
+    let voted = VotingKey::Voted(id, voter.clone());
+    if env.storage().persistent().has(&voted) {
+        return Err(BudgetError::AlreadyVoted);
+    }
+    // A rotated operator already voted if any of its earlier keys did
+    for previous in rotation::previous(env, voter).iter() {
+        if env.storage().persistent().has(&VotingKey::Voted(id, previous)) {
+            return Err(BudgetError::AlreadyVoted);
+        }
+    }
+    env.storage().persistent().set(&voted, &true);
+
+    let mut tally = read_tally(env, id);
+    if approve {
+        tally.for_weight = tally.for_weight.saturating_add(weight);
+    } else {
+        tally.against_weight = tally.against_weight.saturating_add(weight);
+    }
+    env.storage().persistent().set(&VotingKey::Tally(id), &tally);
+    Ok(tally)
+}
+
+// Checks that a tally passes the configured quorum.
+pub(crate) fn check_passed(env: &Env, tally: &Tally) -> Result<(), BudgetError> {
+    let quorum = quorum(env);
+    if quorum == 0 || tally.for_weight < quorum || tally.for_weight <= tally.against_weight {
+        return Err(BudgetError::QuorumNotReached);
+    }
+    Ok(())
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Set the voting weight of an operator (owner only)
+    pub fn set_voting_weight(env: Env, caller: Address, operator: Address, weight: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().set(&VotingKey::VotingWeight(operator), &weight);
+        Ok(())
+    }
+
+    /// Set the weight in favour needed to pass a proposal, 0 turns voting off (owner only)
+    pub fn set_quorum(env: Env, caller: Address, weight: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().set(&VotingKey::Quorum, &weight);
+        Ok(())
+    }
+
+    /// Vote for or against an open proposal with the operator's weight (operators only)
+    pub fn vote(env: Env, caller: Address, id: u64, approve: bool) -> Result<Tally, BudgetError> {
+        require_operator(&env, &caller)?;
+        #[cfg(feature = "sealed")]
+        sealed::check_open_vote(&env)?;
+        let proposal = proposal::read_proposal(&env, id)?;
+        proposal::require_open(&env, &proposal)?;
+        let weight = cast_operator_weight(&env, &caller, &proposal);
+        record_vote(&env, id, &caller, approve, weight)
+    }
+
+    /// Execute an open proposal whose votes reached the quorum (anyone)
+    pub fn execute_proposal(env: Env, id: u64) -> Result<(), BudgetError> {
+        let mut proposal = proposal::read_proposal(&env, id)?;
+        proposal::require_open(&env, &proposal)?;
+        #[cfg(feature = "sealed")]
+        sealed::check_executable(&env, &proposal)?;
+        check_passed(&env, &read_tally(&env, id))?;
+        #[cfg(feature = "token_vote")]
+        token_vote::gate_proposal(&env, &proposal)?;
+        proposal::execute_unratified(&env, &mut proposal)
+    }
+
+    // Get the voting weight of an address
+    pub fn get_voting_weight(env: Env, address: Address) -> u32 {
+        voting_weight(&env, &address)
+    }
+
+    // Get the quorum in weight units (0 means voting is off)
+    pub fn get_quorum(env: Env) -> u32 {
+        quorum(&env)
+    }
+
+    // Get the votes cast on a proposal
+    pub fn get_tally(env: Env, id: u64) -> Tally {
+        read_tally(&env, id)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{symbol_short, BytesN, String};
+
+    fn metadata(env: &Env) -> ProposalMetadata {
+        ProposalMetadata {
+            title: symbol_short!("limits"),
+            memo: String::from_str(env, "raise the limits"),
+            recipient: None,
+            content_hash: BytesN::from_array(env, &[1; 32]),
+        }
+    }
+
+    #[test]
+    fn test_weighted_quorum() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let lead = Address::generate(&env);
+        let member = Address::generate(&env);
+        let other = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &lead);
+        client.accept_operator(&lead);
+        client.add_operator(&owner, &member);
+        client.accept_operator(&member);
+        client.add_operator(&owner, &other);
+        client.accept_operator(&other);
+        client.set_voting_weight(&owner, &lead, &5);
+        client.set_quorum(&owner, &6);
+
+        let id = client.propose_limits(&member, &0, &20000, &metadata(&env));
+
+        // Weight 5 in favour is not enough on its own
+        client.vote(&lead, &id, &true);
+        assert_eq!(client.try_execute_proposal(&id), Err(Ok(BudgetError::QuorumNotReached)));
+        assert_eq!(client.try_vote(&lead, &id, &true), Err(Ok(BudgetError::AlreadyVoted)));
+
+        client.vote(&member, &id, &true);
+        client.vote(&other, &id, &false);
+        assert_eq!(client.get_tally(&id), Tally { for_weight: 6, against_weight: 1 });
+
+        client.execute_proposal(&id);
+        assert_eq!(client.get_budget().max, 20000);
+    }
+
+    #[test]
+    fn test_voting_off_by_default() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+
+        let id = client.propose_limits(&operator, &0, &20000, &metadata(&env));
+        client.vote(&operator, &id, &true);
+        assert_eq!(client.try_execute_proposal(&id), Err(Ok(BudgetError::QuorumNotReached)));
+    }
+
+    #[test]
+    fn test_vote_cannot_shrink_below_current() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let operator = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.add_operator(&owner, &operator);
+        client.accept_operator(&operator);
+        client.set_quorum(&owner, &1);
+
+        // A passed vote alone cannot clamp current, the owner has to ratify
+        let id = client.propose_limits(&operator, &0, &500, &metadata(&env));
+        client.vote(&operator, &id, &true);
+        assert_eq!(client.try_execute_proposal(&id), Err(Ok(BudgetError::ConfirmationRequired)));
+        assert_eq!(client.get_budget(), BudgetState { current: 1000, min: 0, max: 10000 });
+        client.ratify_proposal(&owner, &id);
+        assert_eq!(client.get_budget(), BudgetState { current: 500, min: 0, max: 500 });
+    }
+}
@@ -0,0 +1,122 @@
+// Owner heartbeat with failover.
+// The owner can name a fallback address and a timeout. From then on the owner is expected to call
+// heartbeat() at least once per timeout; whenever more than timeout ledgers have passed since the
+// last heartbeat, the fallback passes every owner check as well. A heartbeat from the owner resets
+// the clock and ends the fallback's powers again.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env};
+
+use crate::{read_owner, require_owner, BudgetError, GovernanceBudgetAllocator, GovernanceBudgetAllocatorClient};
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Watchdog {
+    pub fallback: Address,
+    pub timeout: u32,
+    pub last_heartbeat: u32,
+}
+// Watchdog stores the failover configuration:
+// timeout is how many ledgers may pass between heartbeats
+// last_heartbeat is the ledger of the last heartbeat
+
+
+#[derive(Clone)]
+#[contracttype]
+pub enum WatchdogKey {
+    Watchdog,
+}
+// Watchdog stores the Watchdog (missing means failover is off)
+
+
+fn read_watchdog(env: &Env) -> Option<Watchdog> {
+    env.storage().persistent().get(&WatchdogKey::Watchdog)
+}
+
+// Whether the owner missed its heartbeat.
+fn expired(env: &Env, watchdog: &Watchdog) -> bool {
+    env.ledger().sequence() > watchdog.last_heartbeat.saturating_add(watchdog.timeout)
+}
+
+// Whether an address currently holds owner powers as the fallback.
+pub(crate) fn is_acting_owner(env: &Env, address: &Address) -> bool {
+    match read_watchdog(env) {
+        Some(watchdog) => watchdog.fallback == *address && expired(env, &watchdog),
+        None => false,
+    }
+}
+
+
+#[contractimpl]
+impl GovernanceBudgetAllocator {
+    /// Name the fallback that gains owner powers when no heartbeat came for timeout ledgers (owner only)
+    pub fn set_watchdog(env: Env, caller: Address, fallback: Address, timeout: u32) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        if timeout == 0 {
+            return Err(BudgetError::InvalidConfig);
+        }
+        let watchdog = Watchdog { fallback, timeout, last_heartbeat: env.ledger().sequence() };
+        env.storage().persistent().set(&WatchdogKey::Watchdog, &watchdog);
+        Ok(())
+    }
+
+    /// Turn failover off (owner only)
+    pub fn clear_watchdog(env: Env, caller: Address) -> Result<(), BudgetError> {
+        require_owner(&env, &caller)?;
+        env.storage().persistent().remove(&WatchdogKey::Watchdog);
+        Ok(())
+    }
+
+    /// Prove the owner is still around (owner only)
+    pub fn heartbeat(env: Env, caller: Address) -> Result<(), BudgetError> {
+        caller.require_auth();
+        if caller != read_owner(&env) {
+            return Err(BudgetError::NotOwner);
+        }
+        let mut watchdog = read_watchdog(&env).ok_or(BudgetError::NotRegistered)?;
+        watchdog.last_heartbeat = env.ledger().sequence();
+        env.storage().persistent().set(&WatchdogKey::Watchdog, &watchdog);
+        Ok(())
+    }
+
+    // Get the failover configuration, if any
+    pub fn get_watchdog(env: Env) -> Option<Watchdog> {
+        read_watchdog(&env)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    #[test]
+    fn test_fallback_after_missed_heartbeat() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, GovernanceBudgetAllocator);
+        let client = GovernanceBudgetAllocatorClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let fallback = Address::generate(&env);
+
+        client.initialize(&owner, &1000, &0, &10000);
+
+        env.mock_all_auths();
+        client.set_watchdog(&owner, &fallback, &100);
+
+        env.ledger().with_mut(|l| l.sequence_number = 100);
+        client.heartbeat(&owner);
+        env.ledger().with_mut(|l| l.sequence_number = 200);
+        assert_eq!(client.try_pause(&fallback), Err(Ok(BudgetError::NotOwner)));
+
+        // No heartbeat since ledger 100
+        env.ledger().with_mut(|l| l.sequence_number = 201);
+        client.pause(&fallback);
+        assert!(client.is_paused());
+
+        // The owner coming back ends the fallback's powers
+        client.heartbeat(&owner);
+        assert_eq!(client.try_unpause(&fallback), Err(Ok(BudgetError::NotOwner)));
+    }
+}